@@ -0,0 +1,1292 @@
+"""
+Headless downloading of several mods and their dependencies at once.
+
+Running this module downloads the mods given on the command line and exits
+with the outcome's exit code, see exit_codes.py:
+
+    python -m factorio_mod_downloader.downloader.batch Krastorio2 space-exploration@0.6.138 \\
+        --output /opt/factorio/mods --report report.json
+"""
+
+import dataclasses
+import json
+import math
+import os
+import shutil
+import signal
+import sys
+import time
+import zipfile
+from concurrent.futures import Future
+from concurrent.futures import ThreadPoolExecutor
+from concurrent.futures import as_completed
+from contextlib import nullcontext
+from dataclasses import asdict
+from dataclasses import dataclass
+from dataclasses import field
+from datetime import datetime
+from datetime import timezone
+from typing import Callable
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Sequence
+from typing import Tuple
+from typing import Union
+
+import requests
+
+from factorio_mod_downloader.downloader.changes import ChangeKind
+from factorio_mod_downloader.downloader.changes import PlannedChange
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.config_file import load_settings
+from factorio_mod_downloader.downloader.dependencies import Dependency
+from factorio_mod_downloader.downloader.exit_codes import EXIT_INTERRUPTED
+from factorio_mod_downloader.downloader.exit_codes import EXIT_NETWORK_ERROR
+from factorio_mod_downloader.downloader.exit_codes import EXIT_PARTIAL_FAILURE
+from factorio_mod_downloader.downloader.exit_codes import EXIT_RESOLUTION_ERROR
+from factorio_mod_downloader.downloader.exit_codes import EXIT_SUCCESS
+from factorio_mod_downloader.downloader.exit_codes import ArgumentParser
+from factorio_mod_downloader.downloader.exit_codes import exit_code_for_error
+from factorio_mod_downloader.downloader.graph import DependencyGraph
+from factorio_mod_downloader.downloader.health import check_sources
+from factorio_mod_downloader.downloader.helpers import parse_date
+from factorio_mod_downloader.downloader.history import record_run
+from factorio_mod_downloader.downloader.lock import DirectoryLock
+from factorio_mod_downloader.downloader.notify import Notifier
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import find_release
+from factorio_mod_downloader.downloader.progress import STDOUT_TARGET
+from factorio_mod_downloader.downloader.progress import EventKind
+from factorio_mod_downloader.downloader.progress import JsonLinesProgress
+from factorio_mod_downloader.downloader.progress import ProgressEvent
+from factorio_mod_downloader.downloader.resolver import ReleaseChoice
+from factorio_mod_downloader.downloader.resolver import ResolutionError
+from factorio_mod_downloader.downloader.resolver import ResolutionResult
+from factorio_mod_downloader.downloader.resolver import ResolutionWarning
+from factorio_mod_downloader.downloader.resolver import ResolvedMod
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.resolver import WarningKind
+from factorio_mod_downloader.downloader.resolver import check_download_size
+from factorio_mod_downloader.downloader.resolver import fetch_release_sizes
+from factorio_mod_downloader.downloader.resume import ResumeState
+from factorio_mod_downloader.downloader.sources import fetch_github_source
+from factorio_mod_downloader.downloader.sources import fetch_zip_source
+from factorio_mod_downloader.downloader.sources import is_github_source
+from factorio_mod_downloader.downloader.sources import is_zip_source
+from factorio_mod_downloader.downloader.state import InstallState
+from factorio_mod_downloader.downloader.tracing import span
+from factorio_mod_downloader.downloader.tracing import tracing
+from factorio_mod_downloader.downloader.transaction import Transaction
+from factorio_mod_downloader.downloader.verification import IntegrityError
+from factorio_mod_downloader.downloader.verification import check_zip
+from factorio_mod_downloader.downloader.verification import then
+from factorio_mod_downloader.downloader.verification import verification_pool
+from factorio_mod_downloader.game.extract import extract_mod
+from factorio_mod_downloader.game.installation import target_builtin_mods
+from factorio_mod_downloader.game.installation import target_factorio_version
+from factorio_mod_downloader.game.installation import target_has_dlc
+from factorio_mod_downloader.game.installed import installed_versions
+from factorio_mod_downloader.game.modpack import ModOptions
+from factorio_mod_downloader.game.modpack import ModpackError
+from factorio_mod_downloader.game.modpack import parse_mod_spec
+
+
+MAX_DOWNLOAD_ATTEMPTS: Final = 3
+RETRY_DELAY: Final = 2  # seconds
+DEFAULT_MAX_WORKERS: Final = 4
+
+
+@dataclass
+class ModEntry:
+    """A mod release placed in the output directory."""
+
+    name: str
+    version: str
+    file_name: str
+    # Release zip, or the mod folder when extracting
+    path: str
+    # Size and checksum of the release zip
+    size_bytes: int
+    sha1: str
+    # Whether an identical file was already present and nothing was downloaded
+    was_cached: bool
+    # Seconds spent checking and downloading the file, including retries
+    duration: float
+    # Failed attempts before the download succeeded
+    retries: int = 0
+    # Storage the file was downloaded from, None if it was cached
+    source: Optional[str] = None
+
+    @property
+    def bytes_per_second(self) -> float:
+        """Average download speed, 0 for cached files."""
+        if self.was_cached or self.duration <= 0:
+            return 0.0
+        return self.size_bytes / self.duration
+
+
+def _percentile(values: Sequence[float], percent: int) -> float:
+    """Nearest-rank percentile of a non empty sequence."""
+    ordered = sorted(values)
+    rank = max(math.ceil(percent / 100 * len(ordered)), 1)
+    return ordered[rank - 1]
+
+
+@dataclass
+class Failure:
+    """A mod that could not be resolved or downloaded."""
+
+    # None for failures not tied to a single mod, e.g. an invalid entry
+    mod: Optional[str]
+    reason: str
+    # Entries of the requested mods that pulled the mod in
+    requested_by: List[str] = field(default_factory=list)
+    # Version selected for the mod, None if resolution failed
+    version: Optional[str] = None
+    # Similarly named mods, when the portal does not know the mod
+    suggestions: List[str] = field(default_factory=list)
+
+    def __str__(self) -> str:
+        return f"{self.mod}: {self.reason}" if self.mod else self.reason
+
+
+@dataclass
+class DownloadResult:
+    """Outcome of batch_download_mods, lists follow the resolution's dependency order."""
+
+    mods: List[ModEntry] = field(default_factory=list)
+    # Invalid entries and mods no release could be selected for
+    resolution_failures: List[Failure] = field(default_factory=list)
+    # Resolved mods whose download failed
+    download_failures: List[Failure] = field(default_factory=list)
+    graph: DependencyGraph = field(default_factory=DependencyGraph)
+    # Mods left out on purpose, shipped with the game or rejected by the mod filters
+    skipped_mods: List[str] = field(default_factory=list)
+    # Resolved mods likely to cause trouble, e.g. releases for another Factorio version
+    warnings: List[ResolutionWarning] = field(default_factory=list)
+    # Seconds spent resolving all mods together, then downloading them
+    resolve_duration: float = 0.0
+    download_duration: float = 0.0
+    # Seconds spent loading the metadata of each requested mod and its
+    # dependencies, shared dependencies count for every mod requiring them
+    root_durations: Dict[str, float] = field(default_factory=dict)
+    # Whether an atomic download failed and the output directory was restored
+    rolled_back: bool = False
+    # Whether confirm_callback declined the download
+    cancelled: bool = False
+    # Whether Ctrl+C or SIGTERM stopped the downloads, the mods downloaded until then are kept
+    interrupted: bool = False
+    # What a dry run left out, see Config.dry_run
+    planned_changes: List[PlannedChange] = field(default_factory=list)
+    # Files deleted by the run, e.g. releases replaced by an update
+    removed_files: List[str] = field(default_factory=list)
+
+    def __repr__(self) -> str:
+        return (
+            f"DownloadResult(downloaded={len(self.mods) - len(self.already_installed)}, "
+            f"already_installed={len(self.already_installed)}, "
+            f"skipped={len(self.skipped_mods)}, failed={len(self.errors)})"
+        )
+
+    @property
+    def downloaded_mods(self) -> List[str]:
+        """Paths of the release zips or mod folders, kept for callers predating mods."""
+        return [entry.path for entry in self.mods]
+
+    @property
+    def errors(self) -> List[str]:
+        """Every failure in human readable form, resolution failures first."""
+        return [str(failure) for failure in self.resolution_failures + self.download_failures]
+
+    @property
+    def exit_code(self) -> int:
+        """
+        Exit code of the run, see exit_codes.py.
+
+        An interruption takes precedence, then resolution failures. Download
+        failures count as a network error when nothing at all could be downloaded.
+        """
+        if self.interrupted:
+            return EXIT_INTERRUPTED
+        if self.resolution_failures:
+            return EXIT_RESOLUTION_ERROR
+        if self.download_failures:
+            return EXIT_PARTIAL_FAILURE if self.mods else EXIT_NETWORK_ERROR
+        return EXIT_SUCCESS
+
+    @property
+    def already_installed(self) -> List[str]:
+        """Names of the mods whose file was already present and reused."""
+        return [entry.name for entry in self.mods if entry.was_cached]
+
+    @classmethod
+    def from_dict(cls, data: dict) -> "DownloadResult":
+        """
+        Rebuild a result from its report, the dependency graph is not part of it.
+
+        Args:
+            data: Report returned by to_dict
+
+        Returns:
+            The reported result
+        """
+        return cls(
+            mods=[ModEntry(**entry) for entry in data["downloaded"] + data["already_installed"]],
+            resolution_failures=[Failure(**failure) for failure in data["resolution_failures"]],
+            download_failures=[Failure(**failure) for failure in data["download_failures"]],
+            skipped_mods=list(data["skipped"]),
+            removed_files=list(data.get("removed", [])),
+            warnings=[
+                ResolutionWarning(**{**warning, "kind": WarningKind(warning["kind"])})
+                for warning in data.get("warnings", [])
+            ],
+            planned_changes=[
+                PlannedChange(**{**change, "kind": ChangeKind(change["kind"])})
+                for change in data.get("planned_changes", [])
+            ],
+            **data.get("timings", {}),
+        )
+
+    def to_dict(self) -> dict:
+        """
+        Report of the download, serializable as JSON.
+
+        Returns:
+            Entries of the downloaded and reused mods, names of the skipped
+            mods and the failures of each phase
+        """
+        return {
+            "downloaded": [asdict(entry) for entry in self.mods if not entry.was_cached],
+            "already_installed": [asdict(entry) for entry in self.mods if entry.was_cached],
+            "skipped": list(self.skipped_mods),
+            "removed": list(self.removed_files),
+            "resolution_failures": [asdict(failure) for failure in self.resolution_failures],
+            "download_failures": [asdict(failure) for failure in self.download_failures],
+            "warnings": [
+                {**asdict(warning), "kind": warning.kind.value} for warning in self.warnings
+            ],
+            "planned_changes": [
+                {**asdict(change), "kind": change.kind.value} for change in self.planned_changes
+            ],
+            "timings": {
+                "resolve_duration": self.resolve_duration,
+                "download_duration": self.download_duration,
+                "root_durations": dict(self.root_durations),
+            },
+        }
+
+    def record(self, operation: str, output_path: str, config: Config, started_at: datetime):
+        """
+        Append the result to the run history, unless config.keep_history is off.
+
+        Args:
+            operation: What was run, e.g. "download"
+            output_path: Directory the run worked on
+            config: Options of the run
+            started_at: When the run started, timezone aware
+        """
+        if config.keep_history:
+            record_run(
+                operation,
+                output_path,
+                self.to_dict(),
+                self.exit_code,
+                started_at,
+                config.dry_run,
+                config.history_dir,
+            )
+
+    def percentiles(self, percents: Sequence[int] = (50, 90, 99)) -> Dict[str, Dict[int, float]]:
+        """
+        Summarise the duration and speed of the downloads.
+
+        Cached mods are left out, they were not downloaded.
+
+        Args:
+            percents: Percentiles to compute
+
+        Returns:
+            Percentiles of "duration" in seconds and "bytes_per_second",
+            empty if nothing was downloaded
+        """
+        downloaded = [entry for entry in self.mods if not entry.was_cached]
+        if not downloaded:
+            return {}
+
+        durations = [entry.duration for entry in downloaded]
+        speeds = [entry.bytes_per_second for entry in downloaded]
+        return {
+            "duration": {percent: _percentile(durations, percent) for percent in percents},
+            "bytes_per_second": {percent: _percentile(speeds, percent) for percent in percents},
+        }
+
+
+@dataclass
+class _FetchedMod:
+    """A release in place in the output directory, not checked yet."""
+
+    mod: ResolvedMod
+    file_path: str
+    # sha1 of a release that was already in place, None if it was downloaded
+    sha1: Optional[str]
+    retries: int
+    source: Optional[str]
+    start: float
+
+
+def _report(
+    on_progress: Optional[Callable[[ProgressEvent], None]],
+    kind: EventKind,
+    status: str,
+    mod: Optional[ResolvedMod] = None,
+    **data,
+):
+    if not on_progress:
+        return
+    if mod:
+        on_progress(ProgressEvent(kind, status, mod.name, mod.version, data))
+    else:
+        on_progress(ProgressEvent(kind, status, data=data))
+
+
+def _fetch_mod(
+    mod: ResolvedMod,
+    output_path: str,
+    client: PortalClient,
+    state: InstallState,
+    on_progress: Optional[Callable[[ProgressEvent], None]] = None,
+) -> _FetchedMod:
+    start = time.monotonic()
+    file_path = os.path.join(output_path, mod.file_name)
+
+    sha1 = state.sha1(file_path, mod.name, mod.version) if os.path.exists(file_path) else None
+    if sha1 is not None and sha1 == mod.release.get("sha1"):
+        _report(on_progress, EventKind.DOWNLOAD, "cached", mod)
+        return _FetchedMod(mod, file_path, sha1, 0, None, start)
+
+    _report(on_progress, EventKind.DOWNLOAD, "started", mod)
+    retries = 0
+    with span("download", mod=mod.name, version=mod.version, source=client.source):
+        for attempt in range(1, MAX_DOWNLOAD_ATTEMPTS + 1):
+            try:
+                file_path = client.download_release(mod.name, mod.version, output_path)
+                break
+            except requests.RequestException as e:
+                if attempt == MAX_DOWNLOAD_ATTEMPTS:
+                    _report(on_progress, EventKind.DOWNLOAD, "failed", mod, error=str(e))
+                    raise
+                retries += 1
+                time.sleep(RETRY_DELAY)
+    _report(
+        on_progress,
+        EventKind.DOWNLOAD,
+        "finished",
+        mod,
+        size_bytes=os.path.getsize(file_path),
+        retries=retries,
+    )
+    return _FetchedMod(mod, file_path, None, retries, client.source, start)
+
+
+def _verify_mod(
+    fetched: _FetchedMod,
+    output_path: str,
+    extract: bool,
+    state: InstallState,
+    on_progress: Optional[Callable[[ProgressEvent], None]] = None,
+) -> ModEntry:
+    mod, file_path = fetched.mod, fetched.file_path
+    was_cached = fetched.sha1 is not None
+
+    sha1 = fetched.sha1
+    if not was_cached:
+        try:
+            with span("verify", mod=mod.name, version=mod.version):
+                sha1 = state.sha1(file_path, mod.name, mod.version)
+                check_zip(file_path, sha1, mod.release.get("sha1"))
+        except (IntegrityError, zipfile.BadZipFile) as e:
+            _report(on_progress, EventKind.VERIFY, "failed", mod, error=str(e))
+            # Left in place, the game would try to load it
+            os.remove(file_path)
+            raise
+    _report(on_progress, EventKind.VERIFY, "passed", mod, sha1=sha1)
+
+    size_bytes = os.path.getsize(file_path)
+    if extract:
+        zip_path = file_path
+        file_path = extract_mod(zip_path, output_path, mod.name, mod.version)
+        os.remove(zip_path)
+
+    return ModEntry(
+        name=mod.name,
+        version=mod.version,
+        file_name=mod.file_name,
+        path=file_path,
+        size_bytes=size_bytes,
+        sha1=sha1,
+        was_cached=was_cached,
+        duration=time.monotonic() - fetched.start,
+        retries=fetched.retries,
+        source=fetched.source,
+    )
+
+
+def _download_mod(
+    mod: ResolvedMod,
+    output_path: str,
+    client: PortalClient,
+    executor: ThreadPoolExecutor,
+    verifier: ThreadPoolExecutor,
+    extract: bool = False,
+    state: Optional[InstallState] = None,
+    on_progress: Optional[Callable[[ProgressEvent], None]] = None,
+) -> Future:
+    """Download a release on executor, then check it on verifier, see verification.py."""
+    state = state or InstallState(output_path)
+    fetched = executor.submit(_fetch_mod, mod, output_path, client, state, on_progress)
+    return then(
+        fetched,
+        verifier,
+        lambda result: _verify_mod(result, output_path, extract, state, on_progress),
+    )
+
+
+def _planned_downloads(
+    resolution: ResolutionResult,
+    output_path: str,
+    state: InstallState,
+    log: Callable[[str], None],
+) -> List[PlannedChange]:
+    """Downloads of the resolved mods whose release is not in place yet."""
+    changes = []
+    for mod in resolution.mods.values():
+        file_path = os.path.join(output_path, mod.file_name)
+        if os.path.exists(file_path):
+            if state.sha1(file_path, mod.name, mod.version) == mod.release.get("sha1"):
+                continue
+        change = PlannedChange(ChangeKind.DOWNLOAD, mod.file_name, mod.name, mod.version)
+        changes.append(change)
+        log(f"{change}.\n")
+    return changes
+
+
+def _remove_superseded(future: Future, final: Optional[ResolvedMod], log: Callable[[str], None]):
+    """Remove a pipelined download whose release the resolution replaced."""
+    try:
+        entry = future.result()
+    except (requests.RequestException, OSError, zipfile.BadZipFile, ValueError):
+        return
+    # Releases that were there before the download are left alone
+    if entry.was_cached:
+        return
+
+    if os.path.isdir(entry.path):
+        shutil.rmtree(entry.path)
+    elif os.path.exists(entry.path):
+        os.remove(entry.path)
+    replacement = f", replaced by version {final.version}" if final else ""
+    log(f"Removed {entry.file_name}{replacement}.\n")
+
+
+def confirm_in_terminal(plan: ResolutionResult) -> bool:
+    """
+    Ask on the terminal whether to download the resolved mods, for confirm_callback.
+
+    Args:
+        plan: Resolution about to be downloaded
+
+    Returns:
+        True if the user answered yes
+    """
+    answer = input(f"This will download {plan.summary()}. Continue? [y/N] ")
+    return answer.strip().lower() in ("y", "yes")
+
+
+def _required_failures(
+    failures: List[Failure], graph: DependencyGraph, roots: List[str]
+) -> List[Failure]:
+    """Failures of the roots, their required dependencies, or of no mod in particular."""
+    required = graph.required_mods(roots)
+    return [failure for failure in failures if failure.mod is None or failure.mod in required]
+
+
+def batch_download_mods(
+    mod_urls: List[str],
+    output_path: str,
+    config: Optional[Config] = None,
+    max_workers: Optional[int] = None,
+    log: Optional[Callable[[str], None]] = None,
+    prioritize_required: bool = False,
+    on_playable: Optional[Callable[[List[ModEntry]], None]] = None,
+    extract: bool = False,
+    client: Optional[PortalClient] = None,
+    atomic: bool = False,
+    confirm_callback: Optional[Callable[[ResolutionResult], bool]] = None,
+    optional_filter: Optional[Callable[[str, Dependency], bool]] = None,
+    pipelined: bool = False,
+    on_progress: Optional[Callable[[ProgressEvent], None]] = None,
+    mod_options: Optional[Dict[str, ModOptions]] = None,
+    resume: bool = False,
+) -> DownloadResult:
+    """
+    Download several mods and all their dependencies.
+
+    All mods are resolved together first, so dependencies they share are
+    looked up once and get a version satisfying every requester. The union
+    is then downloaded concurrently.
+
+    Args:
+        mod_urls: Mod portal URLs or plain mod names, optionally pinned with `@version`,
+            direct links and local paths to mod zips, or GitHub repositories
+        output_path: Directory to save downloaded mods
+        config: Resolution options, defaults to those of the configuration files
+        max_workers: Number of concurrent downloads, defaults to the configured
+            concurrency or DEFAULT_MAX_WORKERS
+        log: Callback receiving progress messages, defaults to standard output
+        prioritize_required: Start the downloads of required mods before
+            optional ones, each in dependency order
+        on_playable: Called once with the entries of the requested mods and
+            their required dependencies as soon as all of them are in place
+        extract: Unpack every mod into a `name_version` folder instead of
+            keeping the zip
+        client: Source of metadata and releases, defaults to the endpoints in config
+        atomic: Leave the output directory as it was, mod-list.json included,
+            when a requested mod or one of its required dependencies fails
+        confirm_callback: Called with the resolution, release sizes filled in,
+            before anything is downloaded. Nothing is downloaded unless it
+            returns True, see confirm_in_terminal.
+        optional_filter: Called with the requesting mod and an optional
+            dependency, returns whether to download it, see Resolver
+        pipelined: Start downloading each mod as soon as it is resolved
+            rather than once the whole graph is. Releases a later resolution
+            pass replaces are removed again. Ignored with confirm_callback
+            or config.max_total_size, which need the complete resolution.
+        on_progress: Called with each step of the resolution, the downloads
+            and their verification, from several threads, see progress.py
+        mod_options: Options of the requested mods by name, as given by a
+            modpack. Mods that are not required do not roll an atomic
+            download back, nor hold back on_playable.
+        resume: Continue the run of the same mods interrupted in output_path,
+            reusing its resolution and skipping the mods it downloaded, see
+            resume.py. Without such a run, starts over.
+
+    Returns:
+        Downloaded mods, failures and the dependency graph
+
+    Raises:
+        DownloadSizeError: If config.max_total_size is exceeded
+        DirectoryLockedError: If another instance is running on the output
+            directory, see config.lock_timeout
+    """
+    log = log or sys.stdout.write
+    if config is None or max_workers is None:
+        settings = load_settings()
+        config = config or settings.config
+        max_workers = max_workers or settings.concurrency or DEFAULT_MAX_WORKERS
+
+    started_at = datetime.now(timezone.utc)
+    try:
+        # A dry run touches nothing, not even the lock file
+        lock = nullcontext() if config.dry_run else DirectoryLock(output_path, config.lock_timeout)
+        with lock, tracing(config.trace_file):
+            result = _batch_download_mods(
+                mod_urls,
+                output_path,
+                config,
+                max_workers,
+                log,
+                prioritize_required,
+                on_playable,
+                extract,
+                client,
+                atomic,
+                confirm_callback,
+                optional_filter,
+                pipelined,
+                on_progress,
+                dict(mod_options or {}),
+                resume,
+            )
+    except (Exception, KeyboardInterrupt) as e:
+        _report(
+            on_progress,
+            EventKind.COMPLETE,
+            "failed",
+            exit_code=exit_code_for_error(e),
+            error=str(e),
+        )
+        raise
+    result.record("download", output_path, config, started_at)
+    _report(
+        on_progress,
+        EventKind.COMPLETE,
+        "finished",
+        exit_code=result.exit_code,
+        downloaded=len(result.mods),
+        failed=len(result.errors),
+    )
+    return result
+
+
+def _batch_download_mods(
+    mod_urls: List[str],
+    output_path: str,
+    config: Config,
+    max_workers: int,
+    log: Callable[[str], None],
+    prioritize_required: bool,
+    on_playable: Optional[Callable[[List[ModEntry]], None]],
+    extract: bool,
+    client: Optional[PortalClient],
+    atomic: bool,
+    confirm_callback: Optional[Callable[[ResolutionResult], bool]],
+    optional_filter: Optional[Callable[[str, Dependency], bool]],
+    pipelined: bool,
+    on_progress: Optional[Callable[[ProgressEvent], None]],
+    mod_options: Dict[str, ModOptions],
+    resume: bool,
+) -> DownloadResult:
+    client = client or PortalClient.from_config(config, max_workers, log)
+    if config.metadata_mirrors or config.storage_mirrors:
+        check_sources(config, client, log=log)
+    factorio_version = target_factorio_version(output_path, config.factorio_version, config.channel)
+    log(f"Selecting releases for Factorio {factorio_version}.\n")
+
+    transaction = Transaction(output_path) if atomic and not config.dry_run else None
+
+    builtin_mods = target_builtin_mods(output_path, config.builtin_mods)
+    resolver = Resolver(
+        factorio_version,
+        config,
+        log=log,
+        client=client,
+        optional_filter=optional_filter,
+        builtin_mods=builtin_mods,
+        has_dlc=target_has_dlc(output_path, config.has_dlc),
+        include_optional={
+            mod_name: options.include_optional
+            for mod_name, options in mod_options.items()
+            if options.include_optional is not None
+        },
+        installed=installed_versions(output_path),
+        release_choices={
+            mod_name: ReleaseChoice(
+                parse_date(options.released_before) if options.released_before else None,
+                options.skip_newest,
+            )
+            for mod_name, options in mod_options.items()
+            if options.released_before or options.skip_newest
+        },
+    )
+    mod_names = []
+    pins: Dict[str, str] = {}
+    provided: Dict[str, dict] = {}
+    # Entry each requested mod was given as, to attribute failures to it
+    entries: Dict[str, str] = {}
+    entry_failures = []
+    planned_sources: List[PlannedChange] = []
+    for mod_url in mod_urls:
+        if (is_zip_source(mod_url) or is_github_source(mod_url)) and config.dry_run:
+            # Fetching the source would write it, so its dependencies are not resolved
+            change = PlannedChange(ChangeKind.DOWNLOAD, mod_url)
+            planned_sources.append(change)
+            log(f"{change}.\n")
+            continue
+        if is_zip_source(mod_url) or is_github_source(mod_url):
+            fetch = fetch_zip_source if is_zip_source(mod_url) else fetch_github_source
+            try:
+                release = fetch(mod_url, output_path, client)
+            except (requests.RequestException, OSError, zipfile.BadZipFile, ValueError) as e:
+                entry_failures.append(Failure(None, f"{mod_url}: {e}", [mod_url]))
+                log(f"Failed to fetch {mod_url}: {e}\n")
+                continue
+            mod_name = release["info_json"]["name"]
+            log(f"Fetched {release['file_name']} from {mod_url}.\n")
+            mod_names.append(mod_name)
+            provided[mod_name] = release
+            entries.setdefault(mod_name, mod_url)
+            continue
+
+        try:
+            mod_name, version = parse_mod_spec(mod_url)
+        except ModpackError as e:
+            entry_failures.append(Failure(None, str(e), [mod_url]))
+            continue
+        mod_names.append(mod_name)
+        entries.setdefault(mod_name, mod_url)
+        if version:
+            pins[mod_name] = version
+
+    resumed = None
+    if resume and not config.dry_run:
+        resumed = ResumeState.load(output_path)
+        if not resumed or resumed.requested != list(mod_urls):
+            log("No interrupted run of these mods to resume, starting over.\n")
+            resumed = None
+
+    state = InstallState(output_path)
+    executor = ThreadPoolExecutor(max_workers=max_workers)
+    verifier = verification_pool(config.verify_workers)
+    pipelined = (
+        resumed is None
+        and pipelined
+        and confirm_callback is None
+        and config.max_total_size is None
+        and not config.dry_run
+    )
+    # Downloads started during resolution, by mod and version
+    streamed: Dict[Tuple[str, str], Future] = {}
+
+    def start_download(mod: ResolvedMod):
+        if (mod.name, mod.version) not in streamed:
+            os.makedirs(output_path, exist_ok=True)
+            streamed[mod.name, mod.version] = _download_mod(
+                mod, output_path, client, executor, verifier, extract, state, on_progress
+            )
+
+    def on_resolved(mod: ResolvedMod):
+        _report(on_progress, EventKind.RESOLVE, "resolved", mod)
+        if pipelined:
+            start_download(mod)
+
+    _report(on_progress, EventKind.RESOLVE, "started", mods=mod_names)
+    resolve_start = time.monotonic()
+    if resumed:
+        resolution = resumed.resolution
+        log(
+            f"Resuming the interrupted run, {len(resumed.completed)} of "
+            f"{len(resolution.mods)} mods are downloaded.\n"
+        )
+    else:
+        try:
+            with span("resolve", requested=len(mod_names)) as attributes:
+                resolution = resolver.resolve(
+                    mod_names,
+                    pins,
+                    provided,
+                    on_resolved=on_resolved if pipelined or on_progress else None,
+                )
+                attributes["resolved"] = len(resolution.mods)
+        except KeyboardInterrupt:
+            # Pipelined downloads in progress stop and remove their partial files
+            client.cancel()
+            executor.shutdown(cancel_futures=True)
+            verifier.shutdown()
+            client.cancelled.clear()
+            if transaction:
+                transaction.rollback()
+            raise
+        # Selected releases are in the resolution, the rest is not needed while downloading
+        resolver.clear_cache()
+    resolve_duration = time.monotonic() - resolve_start
+    _report(
+        on_progress,
+        EventKind.RESOLVE,
+        "finished",
+        mods=len(resolution.mods),
+        failures=len(resolution.failures) + len(entry_failures),
+    )
+
+    # Failures and options of mods requested with another case follow the portal's name
+    for mod_name, spelling in resolution.renamed.items():
+        entries.setdefault(spelling, entries[mod_name])
+        if mod_name in mod_options:
+            mod_options[spelling] = mod_options.pop(mod_name)
+
+    def requested_by(mod_name: str) -> List[str]:
+        return [entries[root] for root in resolution.graph.roots_of(mod_name) if root in entries]
+
+    mod_failures = {f"{mod_name}: {reason}" for mod_name, reason in resolution.failures.items()}
+    result = DownloadResult(
+        resolution_failures=entry_failures
+        + [
+            Failure(
+                mod_name,
+                reason,
+                requested_by(mod_name),
+                suggestions=resolution.suggestions.get(mod_name, []),
+            )
+            for mod_name, reason in resolution.failures.items()
+        ]
+        + [Failure(None, error) for error in resolution.errors if error not in mod_failures],
+        graph=resolution.graph,
+        warnings=resolution.warnings,
+        resolve_duration=resolve_duration,
+        root_durations={
+            root: sum(
+                resolution.metadata_durations.get(mod_name, 0.0)
+                for mod_name in resolution.graph.depths(root)
+            )
+            for root in resolution.graph.roots
+        },
+        skipped_mods=sorted(
+            {mod_name for mod_name in mod_names if mod_name in builtin_mods}
+            | set(resolution.filtered)
+        ),
+    )
+
+    # Fetched zips are already in place, which also makes _download_mod keep them
+    for mod_name, release in provided.items():
+        if mod_name not in resolution.mods:
+            continue
+        resolution.mods[mod_name].size = os.path.getsize(
+            os.path.join(output_path, release["file_name"])
+        )
+
+    if config.max_total_size is not None:
+        try:
+            check_download_size(resolution, config.max_total_size, client)
+        except Exception:
+            if transaction:
+                transaction.rollback()
+            raise
+
+    if config.dry_run:
+        result.planned_changes = planned_sources + _planned_downloads(
+            resolution, output_path, state, log
+        )
+        return result
+
+    os.makedirs(output_path, exist_ok=True)
+    downloaded: Dict[str, ModEntry] = {}
+    failed: Dict[str, Failure] = {}
+
+    required_roots = [
+        root
+        for root in resolution.graph.roots
+        if mod_options.get(root, ModOptions()).required
+    ]
+    # The minimum playable set, the game can start once these are downloaded
+    required = resolution.graph.required_mods(required_roots) & set(resolution.mods)
+    if transaction and _required_failures(
+        result.resolution_failures, resolution.graph, required_roots
+    ):
+        log("A required mod could not be resolved, nothing was downloaded.\n")
+        executor.shutdown()
+        verifier.shutdown()
+        transaction.rollback()
+        result.rolled_back = True
+        return result
+
+    if confirm_callback:
+        fetch_release_sizes(resolution, client)
+        if not confirm_callback(resolution):
+            log("Download cancelled.\n")
+            if transaction:
+                transaction.rollback()
+            result.cancelled = True
+            return result
+
+    resume_state = resumed or ResumeState(output_path, list(mod_urls), resolution)
+    resume_state.save()
+    for mod_name, entry in resume_state.completed.items():
+        mod = resolution.mods.get(mod_name)
+        if mod and mod.version == entry["version"] and os.path.exists(entry["path"]):
+            downloaded[mod_name] = dataclasses.replace(
+                ModEntry(**entry), was_cached=True, duration=0.0, retries=0, source=None
+            )
+    if required and required <= set(downloaded):
+        log("All required mods are downloaded.\n")
+        if on_playable:
+            on_playable([downloaded[name] for name in resolution.mods if name in required])
+
+    queue = [mod for mod in resolution.mods.values() if mod.name not in downloaded]
+    if prioritize_required:
+        # Stable sort, so both groups keep the resolution's dependency order
+        queue.sort(key=lambda mod: mod.name not in required)
+
+    # Pipelined downloads overlap the resolution, their time counts from its start
+    download_start = resolve_start if pipelined else time.monotonic()
+    interrupted = False
+    with executor, verifier:
+        futures = {
+            streamed.pop((mod.name, mod.version), None)
+            or _download_mod(
+                mod, output_path, client, executor, verifier, extract, state, on_progress
+            ): mod
+            for mod in queue
+        }
+        try:
+            for future in as_completed(futures):
+                mod = futures[future]
+                try:
+                    downloaded[mod.name] = future.result()
+                except (requests.RequestException, OSError, zipfile.BadZipFile, ValueError) as e:
+                    failed[mod.name] = Failure(
+                        mod.name,
+                        f"Failed to download {mod.file_name}: {e}",
+                        requested_by(mod.name),
+                        mod.version,
+                    )
+                    log(f"Failed to download {mod.file_name}: {e}\n")
+                    continue
+
+                resume_state.complete(asdict(downloaded[mod.name]))
+                if downloaded[mod.name].was_cached:
+                    log(f"Already downloaded: {mod.file_name}.\n")
+                else:
+                    log(f"Downloaded: {mod.file_name}.\n")
+
+                if mod.name in required and required <= set(downloaded):
+                    log("All required mods are downloaded.\n")
+                    if on_playable:
+                        on_playable(
+                            [downloaded[name] for name in resolution.mods if name in required]
+                        )
+        except KeyboardInterrupt:
+            log("Interrupted, stopping the downloads in progress.\n")
+            interrupted = True
+            # Running downloads remove their partial files, pending ones never start
+            client.cancel()
+            executor.shutdown(wait=False, cancel_futures=True)
+
+        if not interrupted:
+            for (mod_name, version), future in streamed.items():
+                _remove_superseded(future, resolution.mods.get(mod_name), log)
+
+    # The downloads have stopped, the client can serve another run
+    client.cancelled.clear()
+    result.download_duration = time.monotonic() - download_start
+    result.interrupted = interrupted
+    state.save()
+
+    # Downloads complete in any order, report them in the resolution's order
+    for mod_name in resolution.mods:
+        if mod_name in downloaded:
+            result.mods.append(downloaded[mod_name])
+        elif mod_name in failed:
+            result.download_failures.append(failed[mod_name])
+
+    if transaction and interrupted:
+        log("Restoring the output directory.\n")
+        transaction.rollback()
+        result.rolled_back = True
+        result.mods = [entry for entry in result.mods if os.path.exists(entry.path)]
+    elif transaction and _required_failures(
+        result.download_failures, resolution.graph, required_roots
+    ):
+        log("A required mod failed to download, restoring the output directory.\n")
+        transaction.rollback()
+        result.rolled_back = True
+        result.mods = [entry for entry in result.mods if os.path.exists(entry.path)]
+    elif transaction:
+        transaction.commit()
+    # Failed and interrupted downloads are left to resume
+    if result.rolled_back or not (interrupted or result.download_failures):
+        resume_state.remove()
+
+    notifier = Notifier.from_config(config, log)
+    if notifier:
+        notifier.downloads_completed(
+            [
+                {
+                    "name": entry.name,
+                    "version": entry.version,
+                    "file_name": entry.file_name,
+                    "was_cached": entry.was_cached,
+                }
+                for entry in result.mods
+            ],
+            result.errors,
+        )
+
+    return result
+
+
+def download_mod_release(
+    mod_name: str,
+    version: str,
+    output_path: str,
+    config: Optional[Config] = None,
+    client: Optional[PortalClient] = None,
+    extract: bool = False,
+) -> ModEntry:
+    """
+    Download one specific release, without resolving its dependencies.
+
+    For scripts that already know the release they want. A file already in
+    output_path with the checksum the portal reports is reused, a downloaded
+    one is retried and verified as in batch_download_mods.
+
+    Args:
+        mod_name: Name of the mod
+        version: Release version
+        output_path: Directory to save the release in
+        config: Endpoints and lock options, defaults to those of the configuration files
+        client: Source of metadata and releases, defaults to the endpoints in config
+        extract: Unpack the release into a `name_version` folder instead of keeping the zip
+
+    Returns:
+        Entry with the path and size of the release
+
+    Raises:
+        ResolutionError: If the mod has no such release
+        requests.RequestException: If the portal could not be reached or the
+            download failed
+        IntegrityError: If the downloaded zip does not match its checksum
+        DirectoryLockedError: If another instance is running on the output
+            directory, see config.lock_timeout
+    """
+    config = config or load_settings().config
+    client = client or PortalClient.from_config(config)
+    release = find_release(client.get_mod_releases(mod_name), version)
+    if release is None:
+        raise ResolutionError(f"{mod_name} has no release {version}")
+
+    mod = ResolvedMod(mod_name, release["version"], release)
+    with DirectoryLock(output_path, config.lock_timeout):
+        os.makedirs(output_path, exist_ok=True)
+        state = InstallState(output_path)
+        fetched = _fetch_mod(mod, output_path, client, state)
+        entry = _verify_mod(fetched, output_path, extract, state)
+        state.save()
+    return entry
+
+
+def retry_failed(
+    previous: Union[DownloadResult, dict],
+    output_path: str,
+    config: Optional[Config] = None,
+    max_workers: Optional[int] = None,
+    log: Optional[Callable[[str], None]] = None,
+    extract: bool = False,
+    client: Optional[PortalClient] = None,
+) -> DownloadResult:
+    """
+    Retry the failures of an earlier batch_download_mods run.
+
+    Failed downloads are attempted again at the version selected back then,
+    without resolving anything. Mods whose resolution failed are resolved
+    again, only the requested mods that pulled them in are.
+
+    Args:
+        previous: Earlier result, or its report as returned by to_dict
+        output_path: Directory the earlier run saved mods to
+        config: Resolution options, defaults to those of the configuration files
+        max_workers: Number of concurrent downloads, defaults to the configured
+            concurrency or DEFAULT_MAX_WORKERS
+        log: Callback receiving progress messages, defaults to standard output
+        extract: Unpack every mod into a `name_version` folder instead of
+            keeping the zip
+        client: Source of metadata and releases, defaults to the endpoints in config
+
+    Returns:
+        The earlier result with the outcome of the retries merged in
+
+    Raises:
+        DirectoryLockedError: If another instance is running on the output
+            directory, see config.lock_timeout
+    """
+    log = log or sys.stdout.write
+    if isinstance(previous, dict):
+        previous = DownloadResult.from_dict(previous)
+    if config is None or max_workers is None:
+        settings = load_settings()
+        config = config or settings.config
+        max_workers = max_workers or settings.concurrency or DEFAULT_MAX_WORKERS
+
+    started_at = datetime.now(timezone.utc)
+    with nullcontext() if config.dry_run else DirectoryLock(output_path, config.lock_timeout):
+        result = _retry_failed(previous, output_path, config, max_workers, log, extract, client)
+    result.record("retry", output_path, config, started_at)
+    return result
+
+
+def _retry_failed(
+    previous: DownloadResult,
+    output_path: str,
+    config: Config,
+    max_workers: int,
+    log: Callable[[str], None],
+    extract: bool,
+    client: Optional[PortalClient],
+) -> DownloadResult:
+    client = client or PortalClient.from_config(config, max_workers, log)
+
+    retried: Dict[str, ModEntry] = {}
+    result = DownloadResult(graph=previous.graph, skipped_mods=list(previous.skipped_mods))
+
+    retrying = previous.download_failures
+    if config.dry_run:
+        for failure in retrying:
+            file_name = f"{failure.mod}_{failure.version}.zip"
+            change = PlannedChange(ChangeKind.DOWNLOAD, file_name, failure.mod, failure.version)
+            result.planned_changes.append(change)
+            log(f"{change}.\n")
+        result.download_failures = list(retrying)
+        retrying = []
+
+    def failed(failure: Failure, error: Exception):
+        file_name = f"{failure.mod}_{failure.version}.zip"
+        result.download_failures.append(
+            Failure(
+                failure.mod,
+                f"Failed to download {file_name}: {error}",
+                failure.requested_by,
+                failure.version,
+            )
+        )
+        log(f"Failed to download {file_name}: {error}\n")
+
+    # The release again, the retried download is checked against its sha1
+    mods: List[Tuple[Failure, ResolvedMod]] = []
+    for failure in retrying:
+        try:
+            release = find_release(client.get_mod_releases(failure.mod), failure.version)
+        except requests.RequestException as e:
+            failed(failure, e)
+            continue
+        if release is None:
+            failed(failure, ValueError(f"release {failure.version} is no longer on the portal"))
+            continue
+        mods.append((failure, ResolvedMod(failure.mod, failure.version, release)))
+
+    state = InstallState(output_path)
+    download_start = time.monotonic()
+    executor = ThreadPoolExecutor(max_workers=max_workers)
+    verifier = verification_pool(config.verify_workers)
+    with executor, verifier:
+        futures = {
+            _download_mod(mod, output_path, client, executor, verifier, extract, state): failure
+            for failure, mod in mods
+        }
+        for future in as_completed(futures):
+            failure = futures[future]
+            try:
+                retried[failure.mod] = future.result()
+            except (requests.RequestException, OSError, zipfile.BadZipFile, ValueError) as e:
+                failed(failure, e)
+                continue
+            log(f"Downloaded: {retried[failure.mod].file_name}.\n")
+    result.download_duration = time.monotonic() - download_start
+    if not config.dry_run:
+        state.save()
+
+    # Failures not attributed to a requested mod cannot be retried on their own
+    result.resolution_failures = [
+        failure for failure in previous.resolution_failures if not failure.requested_by
+    ]
+    entries = list(
+        dict.fromkeys(
+            entry for failure in previous.resolution_failures for entry in failure.requested_by
+        )
+    )
+    if entries:
+        log(f"Resolving {', '.join(entries)} again.\n")
+        # Recorded once, as part of the retry
+        resolved = batch_download_mods(
+            entries,
+            output_path,
+            dataclasses.replace(config, keep_history=False),
+            max_workers,
+            log,
+            extract=extract,
+            client=client,
+        )
+        # Mods already in place from the earlier run or a retried download keep their entry
+        known = {entry.name for entry in previous.mods} | set(retried)
+        retried.update({entry.name: entry for entry in resolved.mods if entry.name not in known})
+        result.resolution_failures.extend(resolved.resolution_failures)
+        result.download_failures.extend(resolved.download_failures)
+        result.planned_changes.extend(resolved.planned_changes)
+        result.skipped_mods = sorted(set(result.skipped_mods) | set(resolved.skipped_mods))
+        result.resolve_duration = resolved.resolve_duration
+        result.download_duration += resolved.download_duration
+        result.root_durations = resolved.root_durations
+
+    result.mods = [retried.pop(entry.name, entry) for entry in previous.mods]
+    result.mods.extend(retried.values())
+    return result
+
+
+def main():
+    """Download the mods given on the command line."""
+    parser = ArgumentParser(description="Download Factorio mods and their dependencies.")
+    parser.add_argument(
+        "mods", nargs="+", help="Mod portal URLs or names, optionally pinned with @version"
+    )
+    parser.add_argument(
+        "-o", "--output", help="Directory to save mods to, defaults to the configured destination"
+    )
+    parser.add_argument(
+        "--factorio-version",
+        help='Factorio version to select releases for, e.g. "2.0", or "auto" for the version '
+        "of the game the destination belongs to",
+    )
+    parser.add_argument(
+        "--dry-run", action="store_true", help="Only report what would be downloaded"
+    )
+    parser.add_argument("--report", help="File to write the JSON report of the download to")
+    parser.add_argument(
+        "--trace",
+        metavar="FILE",
+        help="File to write a Chrome trace of the resolution and downloads to, see tracing.py",
+    )
+    parser.add_argument(
+        "--as-of",
+        metavar="DATE",
+        help="Only consider releases published before this date, e.g. 2024-06-01, "
+        "to install mods as they were then",
+    )
+    parser.add_argument(
+        "--resume",
+        action="store_true",
+        help="Continue the run of the same mods interrupted in the destination",
+    )
+    parser.add_argument(
+        "--progress-json",
+        nargs="?",
+        const=STDOUT_TARGET,
+        metavar="SOCKET",
+        help="Emit progress as JSON lines on standard output, or on the given unix socket, "
+        "see progress.py",
+    )
+    args = parser.parse_args()
+    # Stopped like by Ctrl+C, so downloads clean up and the report is still written
+    signal.signal(signal.SIGTERM, signal.default_int_handler)
+
+    progress = None
+    try:
+        settings = load_settings(
+            dry_run=args.dry_run or None,
+            factorio_version=args.factorio_version,
+            trace_file=args.trace,
+            as_of=args.as_of,
+        )
+        output_path = args.output or settings.destination
+        if not output_path:
+            parser.error("no --output given and no destination configured")
+        if args.progress_json:
+            progress = JsonLinesProgress.open(args.progress_json)
+        result = batch_download_mods(
+            args.mods,
+            output_path,
+            settings.config,
+            settings.concurrency or DEFAULT_MAX_WORKERS,
+            # Standard output is kept for the JSON lines
+            log=sys.stderr.write if args.progress_json == STDOUT_TARGET else sys.stdout.write,
+            on_progress=progress,
+            resume=args.resume,
+        )
+    except KeyboardInterrupt:
+        sys.stderr.write("Interrupted.\n")
+        sys.exit(EXIT_INTERRUPTED)
+    except Exception as e:
+        sys.stderr.write(f"Error: {e}\n")
+        sys.exit(exit_code_for_error(e))
+    finally:
+        if progress:
+            progress.close()
+
+    for error in result.errors:
+        sys.stderr.write(f"{error}\n")
+    if args.report:
+        with open(args.report, "w", encoding="utf-8") as file:
+            json.dump(result.to_dict(), file, indent=2)
+    sys.exit(result.exit_code)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,250 @@
+"""
+Benchmarks of the resolver against recorded fixture graphs.
+
+A fixture is a directory of the portal's `/full` responses, one
+`<name>.json` per mod, as loaded by MockPortalClient.from_directory. The
+closure of a pack is recorded once from the portal, optional dependencies
+included so any configuration finds its mods:
+
+    python -m factorio_mod_downloader.downloader.benchmark record fixtures/pyanodons \\
+        pyalienlife pyhightech
+
+Resolving it again then involves no network at all, so timings only
+measure the resolver. Saving them as a baseline and comparing later runs
+catches regressions:
+
+    python -m factorio_mod_downloader.downloader.benchmark run fixtures/pyanodons \\
+        pyalienlife pyhightech --save baseline.json
+    python -m factorio_mod_downloader.downloader.benchmark run fixtures/pyanodons \\
+        pyalienlife pyhightech --baseline baseline.json --max-regression 0.2
+
+tests/fixtures/rails is a small fixture kept in the repository, covering
+every kind of dependency:
+
+    python -m factorio_mod_downloader.downloader.benchmark run tests/fixtures/rails rail-pack
+
+Nothing else imports this module, it is a development tool.
+"""
+
+import dataclasses
+import json
+import os
+import statistics
+import sys
+import threading
+import time
+from dataclasses import dataclass
+from dataclasses import field
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.exit_codes import EXIT_ERROR
+from factorio_mod_downloader.downloader.exit_codes import EXIT_SUCCESS
+from factorio_mod_downloader.downloader.exit_codes import ArgumentParser
+from factorio_mod_downloader.downloader.exit_codes import exit_code_for_error
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+from factorio_mod_downloader.game.installation import DEFAULT_FACTORIO_VERSION
+from factorio_mod_downloader.game.modpack import parse_mod_spec
+
+
+DEFAULT_REPEAT: Final = 5
+
+
+@dataclass
+class BenchmarkResult:
+    """Timings of repeated resolutions of the same mods."""
+
+    mods: List[str]
+    factorio_version: str
+    # Mods selected by each resolution
+    resolved: int = 0
+    # Seconds taken by each resolution
+    timings: List[float] = field(default_factory=list)
+
+    @property
+    def best(self) -> float:
+        """Fastest resolution, the least disturbed by the rest of the machine."""
+        return min(self.timings)
+
+    @property
+    def median(self) -> float:
+        """Median resolution time."""
+        return statistics.median(self.timings)
+
+    def regression(self, baseline: "BenchmarkResult") -> float:
+        """
+        Compare with an earlier run.
+
+        Args:
+            baseline: Earlier result for the same mods
+
+        Returns:
+            Relative slowdown of the best time, e.g. 0.25 for 25% slower,
+            negative if faster
+        """
+        return self.best / baseline.best - 1
+
+    def __str__(self) -> str:
+        return (
+            f"{self.resolved} mods resolved in {self.best * 1000:.1f} ms "
+            f"(median {self.median * 1000:.1f} ms over {len(self.timings)} runs)"
+        )
+
+
+class _RecordingClient(PortalClient):
+    """Client keeping every `/full` response it fetched."""
+
+    def __init__(self, *args, **kwargs):
+        super().__init__(*args, **kwargs)
+        self.responses: Dict[str, dict] = {}
+        self._lock = threading.Lock()
+
+    def _get_mod_details(self, mod_name: str) -> dict:
+        details = super()._get_mod_details(mod_name)
+        with self._lock:
+            self.responses[mod_name] = details
+        return details
+
+
+def _parse_mods(mods: List[str]) -> Tuple[List[str], Dict[str, str]]:
+    names: List[str] = []
+    pins: Dict[str, str] = {}
+    for mod in mods:
+        name, version = parse_mod_spec(mod)
+        names.append(name)
+        if version:
+            pins[name] = version
+    return names, pins
+
+
+def record_fixture(
+    mods: List[str],
+    fixture_dir: str,
+    factorio_version: str = DEFAULT_FACTORIO_VERSION,
+    config: Optional[Config] = None,
+) -> List[str]:
+    """
+    Record the metadata of mods and their dependencies from the portal.
+
+    Args:
+        mods: Mod names, optionally pinned with `@version`
+        fixture_dir: Directory to save the responses to
+        factorio_version: Factorio version to resolve for
+        config: Endpoints and resolution options, optional dependencies are
+            always followed
+
+    Returns:
+        Names of the recorded mods
+    """
+    config = dataclasses.replace(
+        config or Config(), include_optional=True, include_hidden_optional=True
+    )
+    client = _RecordingClient.from_config(config)
+    names, pins = _parse_mods(mods)
+    Resolver(factorio_version, config, client=client).resolve(names, pins)
+
+    os.makedirs(fixture_dir, exist_ok=True)
+    for name, details in client.responses.items():
+        with open(os.path.join(fixture_dir, f"{name}.json"), "w", encoding="utf-8") as file:
+            json.dump(details, file, sort_keys=True)
+    return sorted(client.responses)
+
+
+def benchmark_resolution(
+    fixture_dir: str,
+    mods: List[str],
+    factorio_version: str = DEFAULT_FACTORIO_VERSION,
+    config: Optional[Config] = None,
+    repeat: int = DEFAULT_REPEAT,
+) -> BenchmarkResult:
+    """
+    Time resolutions of mods against a recorded fixture.
+
+    Each resolution starts from a fresh resolver, so nothing is cached
+    between them. The fixture is loaded once, before timing.
+
+    Args:
+        fixture_dir: Directory recorded by record_fixture
+        mods: Mod names, optionally pinned with `@version`
+        factorio_version: Factorio version to resolve for
+        config: Resolution options, defaults to Config()
+        repeat: Number of resolutions
+
+    Returns:
+        Timings of the resolutions
+    """
+    config = config or Config()
+    client = MockPortalClient.from_directory(fixture_dir)
+    names, pins = _parse_mods(mods)
+
+    result = BenchmarkResult(list(mods), factorio_version)
+    for _ in range(repeat):
+        resolver = Resolver(factorio_version, config, client=client)
+        start = time.perf_counter()
+        resolution = resolver.resolve(names, pins)
+        result.timings.append(time.perf_counter() - start)
+        result.resolved = len(resolution.mods)
+    return result
+
+
+def main():
+    """Record a fixture or benchmark the resolver against one."""
+    parser = ArgumentParser(description="Benchmark the resolver against recorded fixtures.")
+    commands = parser.add_subparsers(dest="command", required=True)
+    for name, help_text in (
+        ("record", "Record the metadata of mods and their dependencies"),
+        ("run", "Time resolutions of mods against a fixture"),
+    ):
+        command = commands.add_parser(name, help=help_text)
+        command.add_argument("fixture_dir", help="Directory of recorded responses")
+        command.add_argument("mods", nargs="+", help="Mod names, optionally pinned with @version")
+        command.add_argument(
+            "--factorio-version", default=DEFAULT_FACTORIO_VERSION, help="Version to resolve for"
+        )
+    run = commands.choices["run"]
+    run.add_argument("--repeat", type=int, default=DEFAULT_REPEAT, help="Number of resolutions")
+    run.add_argument("--save", help="File to save the result to, as a baseline for later runs")
+    run.add_argument("--baseline", help="Result saved by an earlier run to compare with")
+    run.add_argument(
+        "--max-regression",
+        type=float,
+        default=0.2,
+        help="Slowdown relative to the baseline that fails the run, 0.2 for 20%%",
+    )
+    args = parser.parse_args()
+
+    try:
+        if args.command == "record":
+            recorded = record_fixture(args.mods, args.fixture_dir, args.factorio_version)
+            sys.stdout.write(f"Recorded {len(recorded)} mods in {args.fixture_dir}.\n")
+            sys.exit(EXIT_SUCCESS)
+
+        result = benchmark_resolution(
+            args.fixture_dir, args.mods, args.factorio_version, repeat=args.repeat
+        )
+    except Exception as e:
+        sys.stderr.write(f"Error: {e}\n")
+        sys.exit(exit_code_for_error(e))
+
+    sys.stdout.write(f"{result}\n")
+    if args.save:
+        with open(args.save, "w", encoding="utf-8") as file:
+            json.dump(dataclasses.asdict(result), file, indent=2)
+            file.write("\n")
+    if args.baseline:
+        with open(args.baseline, encoding="utf-8") as file:
+            baseline = BenchmarkResult(**json.load(file))
+        regression = result.regression(baseline)
+        sys.stdout.write(f"{regression:+.1%} compared to {baseline.best * 1000:.1f} ms.\n")
+        if regression > args.max_regression:
+            sys.exit(EXIT_ERROR)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,87 @@
+"""
+On-disk cache of mod portal metadata, revalidated with conditional requests.
+"""
+
+import hashlib
+import json
+import os
+import sys
+from pathlib import Path
+from typing import Optional
+
+
+def default_cache_dir() -> Path:
+    """
+    Locate the directory metadata is cached in.
+
+    Returns:
+        `factorio-mod-downloader/metadata` in the user's cache directory
+    """
+    if sys.platform.startswith("win"):
+        base = Path(os.environ.get("LOCALAPPDATA", Path.home() / "AppData" / "Local"))
+    else:
+        base = Path(os.environ.get("XDG_CACHE_HOME", Path.home() / ".cache"))
+    return base / "factorio-mod-downloader" / "metadata"
+
+
+class MetadataCache:
+    """Portal responses stored with the validators needed to revalidate them."""
+
+    def __init__(self, cache_dir: Optional[str] = None):
+        """
+        Initialize the cache.
+
+        Args:
+            cache_dir: Directory to store responses in, defaults to default_cache_dir()
+        """
+        self.cache_dir = Path(cache_dir) if cache_dir else default_cache_dir()
+
+    def _path(self, url: str) -> Path:
+        # Keyed by URL, so responses from different mirrors never mix
+        return self.cache_dir / f"{hashlib.sha1(url.encode()).hexdigest()}.json"
+
+    def load(self, url: str) -> Optional[dict]:
+        """
+        Look up a cached response.
+
+        Args:
+            url: URL the response was fetched from
+
+        Returns:
+            {"mod": {...}, "etag": ..., "last_modified": ...}, or None if
+            nothing usable is cached. Entries cached by earlier versions hold
+            "releases" instead of "mod".
+        """
+        try:
+            with open(self._path(url), "r", encoding="utf-8") as file:
+                return json.load(file)
+        except (OSError, ValueError):
+            return None
+
+    def store(
+        self,
+        url: str,
+        mod: dict,
+        etag: Optional[str] = None,
+        last_modified: Optional[str] = None,
+    ):
+        """
+        Cache a response, responses without validators are not cached.
+
+        Args:
+            url: URL the response was fetched from
+            mod: Body of the response
+            etag: ETag header of the response
+            last_modified: Last-Modified header of the response
+        """
+        if not etag and not last_modified:
+            return
+
+        path = self._path(url)
+        path.parent.mkdir(parents=True, exist_ok=True)
+
+        entry = {"url": url, "mod": mod, "etag": etag, "last_modified": last_modified}
+        temp_path = path.with_name(f"{path.name}.{os.getpid()}.tmp")
+        with open(temp_path, "w", encoding="utf-8") as file:
+            json.dump(entry, file)
+        os.replace(temp_path, path)
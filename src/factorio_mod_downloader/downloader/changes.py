@@ -0,0 +1,42 @@
+"""
+Changes an operation would make to a mods directory, reported by dry runs.
+"""
+
+from dataclasses import dataclass
+from enum import Enum
+from typing import Optional
+
+
+class ChangeKind(Enum):
+    """What a change does to the mods directory."""
+
+    # A release or modpack source is downloaded
+    DOWNLOAD = "download"
+    # A release is deleted
+    DELETE = "delete"
+    # An entry of mod-list.json is added, enabled, disabled or removed
+    MOD_LIST = "mod-list"
+    # mod-settings.dat is rewritten
+    SETTINGS = "settings"
+
+
+@dataclass
+class PlannedChange:
+    """A change left out by a dry run."""
+
+    kind: ChangeKind
+    # File or URL concerned
+    target: str
+    mod: Optional[str] = None
+    version: Optional[str] = None
+    # For MOD_LIST changes "enable", "disable" or "remove"
+    action: Optional[str] = None
+
+    def __str__(self) -> str:
+        if self.kind == ChangeKind.DOWNLOAD:
+            return f"Would download {self.target}"
+        if self.kind == ChangeKind.DELETE:
+            return f"Would delete {self.target}"
+        if self.kind == ChangeKind.SETTINGS:
+            return f"Would write {self.target}"
+        return f"Would {self.action} {self.mod} in {self.target}"
@@ -0,0 +1,135 @@
+"""
+Availability of a set of mods across Factorio versions, to tell when a
+modpack can migrate to a new game version.
+"""
+
+from concurrent.futures import ThreadPoolExecutor
+from dataclasses import dataclass
+from dataclasses import field
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Sequence
+
+import requests
+
+from factorio_mod_downloader.downloader.dependencies import BUILTIN_MODS
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.game.modpack import mod_name_from_url
+
+
+DEFAULT_FACTORIO_VERSIONS: Final = ("1.1", "2.0")
+
+
+@dataclass
+class CompatibilityMatrix:
+    """Newest release of each mod for each Factorio version."""
+
+    factorio_versions: List[str]
+    # Mod name to Factorio version to the newest release targeting it, None if there is none
+    releases: Dict[str, Dict[str, Optional[str]]] = field(default_factory=dict)
+    # Mods whose releases could not be loaded, with the reason
+    errors: Dict[str, str] = field(default_factory=dict)
+
+    def missing(self, factorio_version: str) -> List[str]:
+        """
+        List the mods holding a migration back.
+
+        Args:
+            factorio_version: Factorio version, one of factorio_versions
+
+        Returns:
+            Mods without a release targeting the version, or whose releases
+            could not be loaded
+        """
+        missing = [name for name, row in self.releases.items() if not row.get(factorio_version)]
+        return sorted(missing + list(self.errors))
+
+    def format(self) -> str:
+        """
+        Lay the matrix out as a table.
+
+        Returns:
+            One row per mod and one column per Factorio version, "-" where
+            there is no release
+        """
+        names = sorted(self.releases.keys() | self.errors.keys())
+        name_width = max([len("Mod")] + [len(name) for name in names])
+        widths = [
+            max([len(version)] + [len(row.get(version) or "-") for row in self.releases.values()])
+            for version in self.factorio_versions
+        ]
+
+        def row(cells: List[str]) -> str:
+            return "  ".join(cell.ljust(width) for cell, width in zip(cells, [name_width] + widths))
+
+        lines = [row(["Mod"] + self.factorio_versions)]
+        for name in names:
+            if name in self.errors:
+                lines.append(f"{name.ljust(name_width)}  {self.errors[name]}")
+                continue
+            versions = self.releases[name]
+            lines.append(
+                row([name] + [versions.get(version) or "-" for version in self.factorio_versions])
+            )
+        return "\n".join(line.rstrip() for line in lines)
+
+
+def _newest_releases(
+    mod_name: str, factorio_versions: Sequence[str], client: PortalClient
+) -> Dict[str, Optional[str]]:
+    newest: Dict[str, Optional[str]] = {version: None for version in factorio_versions}
+    for release in client.get_mod_releases(mod_name):
+        factorio_version = release.get("info_json", {}).get("factorio_version")
+        if factorio_version not in newest:
+            continue
+        current = newest[factorio_version]
+        if current is None or parse_version(release["version"]) > parse_version(current):
+            newest[factorio_version] = release["version"]
+    return newest
+
+
+def compatibility_matrix(
+    mods: Sequence[str],
+    factorio_versions: Sequence[str] = DEFAULT_FACTORIO_VERSIONS,
+    client: Optional[PortalClient] = None,
+    max_workers: int = 4,
+) -> CompatibilityMatrix:
+    """
+    Check which Factorio versions each mod of a set has a release for.
+
+    Only the given mods are checked, not their dependencies; pass a resolved
+    set, e.g. a snapshot of a mods directory, to cover those too.
+
+    Args:
+        mods: Mod portal URLs or mod names, optionally pinned with `@version`,
+            the pins are ignored
+        factorio_versions: Factorio versions as "major.minor"
+        client: Source of mod metadata, defaults to the default endpoints
+        max_workers: Number of concurrent lookups
+
+    Returns:
+        Newest release of each mod for each version
+    """
+    client = client or PortalClient(max_concurrency=max_workers)
+    factorio_versions = list(factorio_versions)
+    names = [
+        name for name in dict.fromkeys(map(mod_name_from_url, mods)) if name not in BUILTIN_MODS
+    ]
+
+    def lookup(mod_name: str):
+        try:
+            return _newest_releases(mod_name, factorio_versions, client), None
+        except requests.RequestException as e:
+            return None, f"Could not load releases: {e}"
+
+    matrix = CompatibilityMatrix(factorio_versions)
+    with ThreadPoolExecutor(max_workers=max_workers) as executor:
+        for name, (releases, error) in zip(names, executor.map(lookup, names)):
+            if error:
+                matrix.errors[name] = error
+            else:
+                matrix.releases[name] = releases
+    return matrix
@@ -0,0 +1,201 @@
+"""
+Options controlling dependency resolution.
+"""
+
+import re
+from dataclasses import dataclass
+from dataclasses import field
+from datetime import datetime
+from enum import Enum
+from fnmatch import fnmatchcase
+from typing import Dict
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+
+
+class ResolutionStrategy(Enum):
+    """How a release is picked among those satisfying every requirement."""
+
+    # Newest satisfying release
+    HIGHEST = "highest"
+    # Oldest satisfying release, to check the lower bounds a mod declares
+    LOWEST_COMPATIBLE = "lowest-compatible"
+    # Only the release pinned with "=", dependencies without a pin fail
+    PINNED_ONLY = "pinned-only"
+
+
+class Channel(Enum):
+    """Factorio release channel a download targets when the game version is not known."""
+
+    STABLE = "stable"
+    # Newest experimental version, mods may already have releases targeting it
+    EXPERIMENTAL = "experimental"
+
+
+class WebhookFormat(Enum):
+    """Payload posted to the webhook."""
+
+    # {"event": ..., "message": ..., plus the event's data}
+    JSON = "json"
+    # Message as Discord's "content"
+    DISCORD = "discord"
+    # Message as Slack's "text"
+    SLACK = "slack"
+
+
+@dataclass
+class Config:
+    """Resolution options shared by every mod of a download."""
+
+    # Factorio version releases are selected for, None targets the destination's game.
+    # "auto" targets it too, but fails when the destination does not belong to a game.
+    factorio_version: Optional[str] = None
+    # Mods shipped with the game, None to read them from the destination's game
+    # or use dependencies.BUILTIN_MODS
+    builtin_mods: Optional[List[str]] = None
+    # Whether the target game has the Space Age DLC, None to detect it from the
+    # destination's game and assume it otherwise
+    has_dlc: Optional[bool] = None
+    # Release channel whose newest version is targeted when neither factorio_version
+    # nor the destination's game tells the version
+    channel: Channel = Channel.STABLE
+    # Download optional ("?") dependencies
+    include_optional: bool = False
+    # Download hidden optional ("(?)") dependencies
+    include_hidden_optional: bool = False
+    # Optional dependencies downloaded whatever include_optional says, as glob
+    # patterns or regular expressions prefixed with "re:", e.g. "*graphics*"
+    optional_include: List[str] = field(default_factory=list)
+    # Optional dependencies never downloaded, taking precedence over optional_include
+    optional_exclude: List[str] = field(default_factory=list)
+    # Download dependencies that do not affect load order ("~")
+    include_load_order: bool = True
+    # Fall back to the newest release of mods with no release for the Factorio
+    # version instead of failing, the game may refuse to load them
+    allow_incompatible: bool = False
+    # Release selection for dependencies, requested mods always get their newest release
+    resolution_strategy: ResolutionStrategy = ResolutionStrategy.HIGHEST
+    # Keep a version of a dependency already in the destination when it satisfies every
+    # requirement, rather than replacing it with the one the strategy picks
+    prefer_installed: bool = True
+    # Resolve as the portal stood at this time, only releases published before it are
+    # considered, e.g. to reproduce the mods of a past event
+    as_of: Optional[datetime] = None
+    # Dependencies never downloaded, along with whatever only they depend on
+    exclude_mods: List[str] = field(default_factory=list)
+    # When set, the only dependencies that may be downloaded
+    only_mods: Optional[List[str]] = None
+    # Warn about mods whose newest release for the Factorio version is older
+    # than this many days, None to never warn
+    abandoned_after_days: Optional[int] = 730
+    # Portal categories and tags to warn about when a resolved mod has one, e.g. "cheats"
+    excluded_categories: List[str] = field(default_factory=list)
+    # Abort before downloading when the resolved mods add up to more bytes than this
+    max_total_size: Optional[int] = None
+    # Metadata API and storage to use instead of the defaults, see portal.py
+    metadata_base_url: Optional[str] = None
+    storage_base_url: Optional[str] = None
+    # Fallbacks for the metadata API and storage, the fastest available source of
+    # each kind is used for the run, see health.check_sources
+    metadata_mirrors: List[str] = field(default_factory=list)
+    storage_mirrors: List[str] = field(default_factory=list)
+    # Credentials of mirrors behind authentication by base URL, "user:password" for
+    # HTTP basic auth or "Bearer <token>", see portal.SourceAuth
+    source_credentials: Dict[str, str] = field(default_factory=dict)
+    # Check the newest release of each mod with the official portal when metadata
+    # comes from a mirror, and use the portal's metadata where the mirror lags behind
+    cross_check_portal: bool = False
+    # User-Agent sent to the portal and storage, see portal.DEFAULT_USER_AGENT
+    user_agent: Optional[str] = None
+    # Seconds to wait for a connection to the portal or storage
+    connect_timeout: float = 10
+    # Seconds to wait for a metadata response
+    metadata_timeout: float = 30
+    # Seconds a download may stall between two chunks, there is no limit on its total duration
+    download_idle_timeout: float = 60
+    # Resolve and report what would change, see changes.py, without touching the disk
+    dry_run: bool = False
+    # Append the report of every run to the history, see history.py
+    keep_history: bool = True
+    # Directory of the history, None for history.default_history_dir()
+    history_dir: Optional[str] = None
+    # Seconds to wait for another instance downloading to the same directory,
+    # 0 to fail at once, None to wait until it has finished
+    lock_timeout: Optional[float] = 0
+    # Connections kept alive per host, None to match the number of concurrent downloads
+    pool_size: Optional[int] = None
+    # Threads hashing and validating downloaded zips, None for one per core
+    verify_workers: Optional[int] = None
+    # Token for mods fetched from GitHub, defaults to the GITHUB_TOKEN environment variable
+    github_token: Optional[str] = None
+    # URL notified of completed downloads, failures and available updates
+    webhook_url: Optional[str] = None
+    webhook_format: WebhookFormat = WebhookFormat.JSON
+    # Keep portal metadata between runs and revalidate it instead of fetching it again
+    cache_metadata: bool = False
+    # Directory of the metadata cache, None for cache.default_cache_dir()
+    cache_dir: Optional[str] = None
+    # File to write a Chrome trace of the resolution and downloads to, see tracing.py
+    trace_file: Optional[str] = None
+
+    def wants(self, kind: DependencyKind) -> bool:
+        """
+        Check whether dependencies of a kind should be downloaded.
+
+        Args:
+            kind: Dependency kind
+
+        Returns:
+            True if dependencies of this kind are resolved and downloaded
+        """
+        if kind == DependencyKind.REQUIRED:
+            return True
+        if kind == DependencyKind.LOAD_ORDER:
+            return self.include_load_order
+        if kind == DependencyKind.OPTIONAL:
+            return self.include_optional
+        if kind == DependencyKind.HIDDEN_OPTIONAL:
+            return self.include_hidden_optional
+        return False
+
+    def wants_optional(self, mod_name: str, kind: DependencyKind) -> bool:
+        """
+        Check whether an optional dependency should be downloaded.
+
+        Args:
+            mod_name: Name of the dependency
+            kind: Dependency kind, optional or hidden optional
+
+        Returns:
+            True if the dependency matches optional_include, or its kind is
+            wanted, and it does not match optional_exclude
+        """
+        if _matches_any(mod_name, self.optional_exclude):
+            return False
+        return _matches_any(mod_name, self.optional_include) or self.wants(kind)
+
+    def allows(self, mod_name: str) -> bool:
+        """
+        Check whether a dependency passes the mod filters.
+
+        Args:
+            mod_name: Name of the dependency
+
+        Returns:
+            False if the mod is excluded or missing from only_mods
+        """
+        if mod_name in self.exclude_mods:
+            return False
+        return self.only_mods is None or mod_name in self.only_mods
+
+
+def _matches_any(mod_name: str, patterns: List[str]) -> bool:
+    for pattern in patterns:
+        if pattern.startswith("re:"):
+            if re.search(pattern[3:], mod_name):
+                return True
+        elif fnmatchcase(mod_name, pattern):
+            return True
+    return False
@@ -0,0 +1,151 @@
+"""
+Defaults read from TOML configuration files.
+
+The user's config.toml is read first, then factorio-mod-downloader.toml in
+the working directory, whose values take precedence:
+
+    destination = "~/factorio/mods"
+    factorio_version = "2.0"  # or "auto" for the destination's game
+    concurrency = 8
+    cache_metadata = true
+    cache_dir = "~/.cache/factorio-mods"
+    github_token = "ghp_..."
+    as_of = 2024-06-01  # only releases published before, see Config.as_of
+
+    [mirrors]
+    metadata = "https://mods.example.com/api/mods"
+    storage = "https://mods-storage.example.com"
+
+    # Mirrors behind authentication, the environment variables
+    # FACTORIO_MOD_METADATA_AUTH and FACTORIO_MOD_STORAGE_AUTH work too
+    [source_credentials]
+    "https://mods.example.com/api/mods" = "Bearer ..."
+    "https://mods-storage.example.com" = "user:password"
+
+Any other field of Config can be set by name as well. Arguments given
+explicitly always override the files.
+"""
+
+import dataclasses
+import tomllib
+from dataclasses import dataclass
+from dataclasses import field
+from datetime import datetime
+from enum import Enum
+from pathlib import Path
+from typing import Any
+from typing import Dict
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.helpers import parse_date
+from factorio_mod_downloader.game.profiles import default_profiles_path
+
+
+CONFIG_FILE = "config.toml"
+LOCAL_CONFIG_FILE = "factorio-mod-downloader.toml"
+MIRROR_KEYS = {"metadata": "metadata_base_url", "storage": "storage_base_url"}
+# Options holding paths, which may start with ~
+PATH_KEYS = ("destination", "cache_dir", "history_dir", "trace_file")
+
+
+class ConfigFileError(Exception):
+    """Raised when a configuration file is invalid."""
+
+
+@dataclass
+class Settings:
+    """Defaults of a run, None where neither a file nor an argument sets them."""
+
+    # Directory mods are downloaded to
+    destination: Optional[str] = None
+    # Number of concurrent downloads
+    concurrency: Optional[int] = None
+    config: Config = field(default_factory=Config)
+
+
+def default_config_path() -> Path:
+    """
+    Get the path of the configuration file in the user's configuration directory.
+
+    Returns:
+        Path to config.toml, next to the profiles file
+    """
+    return default_profiles_path().with_name(CONFIG_FILE)
+
+
+def config_paths() -> List[Path]:
+    """
+    List the configuration files read by default, lowest precedence first.
+
+    Returns:
+        The user's config.toml and the working directory's factorio-mod-downloader.toml
+    """
+    return [default_config_path(), Path.cwd() / LOCAL_CONFIG_FILE]
+
+
+def _read(path: Path) -> Dict[str, Any]:
+    try:
+        with open(path, "rb") as file:
+            data = tomllib.load(file)
+    except tomllib.TOMLDecodeError as e:
+        raise ConfigFileError(f"{path} is not valid TOML: {e}") from e
+
+    mirrors = data.pop("mirrors", {})
+    if not isinstance(mirrors, dict) or set(mirrors) - set(MIRROR_KEYS):
+        raise ConfigFileError(f"{path}: mirrors may only set {', '.join(MIRROR_KEYS)}")
+    data.update({MIRROR_KEYS[key]: url for key, url in mirrors.items()})
+    return data
+
+
+def load_settings(paths: Optional[List[str]] = None, **overrides) -> Settings:
+    """
+    Merge the configuration files with explicit arguments.
+
+    Args:
+        paths: Files to read, lowest precedence first, defaults to config_paths().
+            Missing files are skipped.
+        **overrides: Values of Settings or Config fields taking precedence over
+            the files, None values are ignored
+
+    Returns:
+        Merged settings
+
+    Raises:
+        ConfigFileError: If a file is invalid or sets an unknown option
+    """
+    values: Dict[str, Any] = {}
+    for path in map(Path, paths if paths is not None else config_paths()):
+        if path.is_file():
+            values.update(_read(path))
+    values.update({key: value for key, value in overrides.items() if value is not None})
+    for key in PATH_KEYS:
+        if values.get(key):
+            values[key] = str(Path(values[key]).expanduser())
+
+    config_fields = {config_field.name: config_field for config_field in dataclasses.fields(Config)}
+    settings = Settings(destination=values.pop("destination", None))
+    settings.concurrency = values.pop("concurrency", None)
+
+    unknown = sorted(set(values) - set(config_fields))
+    if unknown:
+        raise ConfigFileError(f"Unknown options: {', '.join(unknown)}")
+
+    for name, value in values.items():
+        # Enums are written as their value, e.g. resolution_strategy = "lowest-compatible"
+        field_type = config_fields[name].type
+        if isinstance(field_type, type) and issubclass(field_type, Enum):
+            try:
+                values[name] = field_type(value)
+            except ValueError as e:
+                raise ConfigFileError(f"Invalid {name}: {value!r}") from e
+        # Times are TOML dates or ISO strings, dates without a time zone are UTC
+        elif field_type == Optional[datetime]:
+            try:
+                values[name] = parse_date(str(value))
+            except ValueError as e:
+                raise ConfigFileError(f"Invalid {name}: {value!r}") from e
+
+    settings.config = Config(**values)
+    return settings
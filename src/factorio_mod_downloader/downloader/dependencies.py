@@ -0,0 +1,178 @@
+"""
+Parsing of Factorio dependency strings as found in a mod's info.json.
+
+A dependency string has the form `[prefix] name [operator version]`, e.g.
+"? flib >= 0.12.0". See https://wiki.factorio.com/Tutorial:Mod_structure#dependencies
+
+parse_dependency, parse_dependencies and format_dependency are the only
+implementation of this grammar, scripts and the GUI should use them rather
+than matching dependency strings themselves.
+"""
+
+import re
+from dataclasses import dataclass
+from enum import Enum
+from typing import Callable
+from typing import Final
+from typing import Iterable
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.helpers import parse_version
+
+
+# Mods shipped with the game, they are never downloaded. Used when the game's
+# own data is not available, see installation.target_builtin_mods
+BUILTIN_MODS: Final = ("base", "space-age", "quality", "elevated-rails")
+# Mods of the Space Age DLC, missing from games without it
+DLC_MODS: Final = ("space-age", "quality", "elevated-rails")
+
+DEPENDENCY_PATTERN: Final = re.compile(
+    r"^\s*(?P<prefix>!|\?|\(\?\)|~)?\s*"
+    r"(?P<name>[^<>=!?~()][^<>=]*?)\s*"
+    r"(?:(?P<operator><=|>=|<|>|=)\s*(?P<version>\d+(?:\.\d+){1,2}))?\s*$"
+)
+
+
+class DependencyKind(Enum):
+    """Kinds of dependencies, named after their effect on loading."""
+
+    REQUIRED = "required"
+    OPTIONAL = "optional"
+    HIDDEN_OPTIONAL = "hidden-optional"
+    INCOMPATIBLE = "incompatible"
+    LOAD_ORDER = "load-order"
+
+
+PREFIX_KINDS: Final = {
+    None: DependencyKind.REQUIRED,
+    "?": DependencyKind.OPTIONAL,
+    "(?)": DependencyKind.HIDDEN_OPTIONAL,
+    "!": DependencyKind.INCOMPATIBLE,
+    "~": DependencyKind.LOAD_ORDER,
+}
+
+
+@dataclass(frozen=True)
+class Dependency:
+    """A parsed dependency of a mod."""
+
+    name: str
+    kind: DependencyKind
+    operator: Optional[str] = None
+    version: Optional[str] = None
+
+    @property
+    def is_required(self) -> bool:
+        """Whether the dependency must be installed for the mod to load."""
+        return self.kind in (DependencyKind.REQUIRED, DependencyKind.LOAD_ORDER)
+
+    @property
+    def is_builtin(self) -> bool:
+        """Whether the dependency is a mod shipped with the game."""
+        return self.name in BUILTIN_MODS
+
+    def is_satisfied_by(self, version: str) -> bool:
+        """
+        Check whether a mod version satisfies the version constraint.
+
+        Args:
+            version: Version of the installed or candidate mod
+
+        Returns:
+            True if there is no constraint or the version matches it
+        """
+        if not self.operator:
+            return True
+
+        actual = parse_version(version)
+        expected = parse_version(self.version)
+        return {
+            "<": actual < expected,
+            "<=": actual <= expected,
+            "=": actual == expected,
+            ">=": actual >= expected,
+            ">": actual > expected,
+        }[self.operator]
+
+    def __str__(self) -> str:
+        return format_dependency(self.name, self.kind, self.operator, self.version)
+
+
+def parse_dependency(dependency: str) -> Dependency:
+    """
+    Parse a single dependency string.
+
+    Args:
+        dependency: Dependency string from info.json
+
+    Returns:
+        Parsed dependency
+
+    Raises:
+        ValueError: If the string is not a valid dependency
+    """
+    match = DEPENDENCY_PATTERN.match(dependency)
+    if not match:
+        raise ValueError(f"Invalid dependency: {dependency!r}")
+
+    return Dependency(
+        name=match.group("name"),
+        kind=PREFIX_KINDS[match.group("prefix")],
+        operator=match.group("operator"),
+        version=match.group("version"),
+    )
+
+
+def parse_dependencies(
+    dependency_strings: Iterable[str], on_invalid: Optional[Callable[[str], None]] = None
+) -> List[Dependency]:
+    """
+    Parse the dependency strings of a mod, leaving out invalid ones.
+
+    Args:
+        dependency_strings: Dependencies from info.json
+        on_invalid: Called with each string that is not a valid dependency
+
+    Returns:
+        The valid dependencies, in order
+    """
+    dependencies = []
+    for dependency_string in dependency_strings:
+        try:
+            dependencies.append(parse_dependency(dependency_string))
+        except ValueError:
+            if on_invalid:
+                on_invalid(dependency_string)
+    return dependencies
+
+
+def format_dependency(
+    name: str,
+    kind: DependencyKind = DependencyKind.REQUIRED,
+    operator: Optional[str] = None,
+    version: Optional[str] = None,
+) -> str:
+    """
+    Write a dependency string, e.g. for an info.json.
+
+    Args:
+        name: Name of the mod depended on
+        kind: Kind of dependency
+        operator: Version comparison, one of <, <=, =, >= and >, None for any version
+        version: Version compared to, required with an operator
+
+    Returns:
+        Dependency string parse_dependency reads back as the same dependency,
+        e.g. "? flib >= 0.12.0"
+
+    Raises:
+        ValueError: If the parts do not form a valid dependency
+    """
+    prefix = next(prefix for prefix, prefix_kind in PREFIX_KINDS.items() if prefix_kind == kind)
+    text = f"{prefix} {name}" if prefix else name
+    if operator or version:
+        text += f" {operator} {version}"
+    if parse_dependency(text) != Dependency(name, kind, operator, version):
+        raise ValueError(f"Invalid dependency: {text!r}")
+    return text
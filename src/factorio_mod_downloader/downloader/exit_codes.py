@@ -0,0 +1,66 @@
+"""
+Exit codes of the command line entry points, for scripts and CI pipelines.
+
+    0  Success, everything was downloaded or the check passed
+    1  Invalid arguments or configuration, or an unexpected error
+    2  Partial failure, some mods could not be downloaded
+    3  Resolution error, a mod or a release for it could not be found
+    4  Network error, the portal or storage could not be reached
+    5  Verification failure, e.g. a server would not load its mods
+    6  The game version needed to verify or to select releases could not be determined
+  130  Interrupted by Ctrl+C or SIGTERM, like shells report an interrupted command
+
+Every entry point uses these codes: `batch`, `watch` and `server`, each run
+with `python -m factorio_mod_downloader.<package>.<module>`.
+"""
+
+import argparse
+import sys
+from typing import Final
+from typing import NoReturn
+
+import requests
+
+from factorio_mod_downloader.downloader.resolver import DownloadSizeError
+from factorio_mod_downloader.downloader.resolver import ResolutionError
+from factorio_mod_downloader.game.installation import GameVersionError
+
+
+EXIT_SUCCESS: Final = 0
+EXIT_ERROR: Final = 1
+EXIT_PARTIAL_FAILURE: Final = 2
+EXIT_RESOLUTION_ERROR: Final = 3
+EXIT_NETWORK_ERROR: Final = 4
+EXIT_VERIFICATION_FAILURE: Final = 5
+EXIT_UNKNOWN_VERSION: Final = 6
+EXIT_INTERRUPTED: Final = 130
+
+
+class ArgumentParser(argparse.ArgumentParser):
+    """Parser exiting with EXIT_ERROR on invalid arguments, argparse's 2 means a partial failure."""
+
+    def error(self, message: str) -> NoReturn:
+        self.print_usage(sys.stderr)
+        self.exit(EXIT_ERROR, f"{self.prog}: error: {message}\n")
+
+
+def exit_code_for_error(error: BaseException) -> int:
+    """
+    Map an error that ended a run to its exit code.
+
+    Args:
+        error: Exception raised by the run
+
+    Returns:
+        EXIT_INTERRUPTED, EXIT_NETWORK_ERROR, EXIT_RESOLUTION_ERROR,
+        EXIT_UNKNOWN_VERSION or EXIT_ERROR
+    """
+    if isinstance(error, KeyboardInterrupt):
+        return EXIT_INTERRUPTED
+    if isinstance(error, requests.RequestException):
+        return EXIT_NETWORK_ERROR
+    if isinstance(error, (ResolutionError, DownloadSizeError)):
+        return EXIT_RESOLUTION_ERROR
+    if isinstance(error, GameVersionError):
+        return EXIT_UNKNOWN_VERSION
+    return EXIT_ERROR
@@ -0,0 +1,338 @@
+"""
+Dependency graph built during resolution, with DOT and Mermaid export.
+"""
+
+import heapq
+import itertools
+from collections import deque
+from dataclasses import dataclass
+from typing import Dict
+from typing import Final
+from typing import Iterable
+from typing import List
+from typing import Optional
+from typing import Set
+
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+
+
+GRAPH_FORMATS: Final = {"dot": ".dot", "mermaid": ".mmd"}
+# Partial chains explain() extends at most, bounding its work on graphs with many paths
+MAX_EXPLAIN_STEPS: Final = 10_000
+
+# Dependency string prefixes used to label edges that are not plain requirements
+EDGE_LABELS: Final = {
+    DependencyKind.OPTIONAL: "?",
+    DependencyKind.HIDDEN_OPTIONAL: "(?)",
+    DependencyKind.INCOMPATIBLE: "!",
+    DependencyKind.LOAD_ORDER: "~",
+}
+
+EXPLAIN_VERBS: Final = {
+    DependencyKind.REQUIRED: "requires",
+    DependencyKind.OPTIONAL: "optionally requires",
+    DependencyKind.HIDDEN_OPTIONAL: "optionally requires",
+    DependencyKind.INCOMPATIBLE: "is incompatible with",
+    DependencyKind.LOAD_ORDER: "requires (without load order)",
+}
+
+
+@dataclass(frozen=True)
+class DependencyEdge:
+    """A dependency of one mod on another."""
+
+    parent: str
+    child: str
+    kind: DependencyKind
+
+
+class DependencyGraph:
+    """Mods pulled in by a resolution and the dependencies connecting them."""
+
+    def __init__(self):
+        self.roots: List[str] = []
+        self.versions: Dict[str, Optional[str]] = {}
+        self.edges: List[DependencyEdge] = []
+
+    def add_root(self, mod_name: str):
+        """Record a mod that was requested directly."""
+        if mod_name not in self.roots:
+            self.roots.append(mod_name)
+        self.versions.setdefault(mod_name, None)
+
+    def add_mod(self, mod_name: str, version: Optional[str] = None):
+        """Record a resolved mod, optionally with its selected version."""
+        if version or mod_name not in self.versions:
+            self.versions[mod_name] = version
+
+    def add_edge(self, parent: str, child: str, kind: DependencyKind):
+        """Record that parent depends on child."""
+        edge = DependencyEdge(parent, child, kind)
+        if edge not in self.edges:
+            self.edges.append(edge)
+        self.versions.setdefault(parent, None)
+        self.versions.setdefault(child, None)
+
+    def required_mods(self, roots: Optional[Iterable[str]] = None) -> Set[str]:
+        """
+        Find the mods needed for the roots to load, ignoring optional dependencies.
+
+        Args:
+            roots: Only the mods needed for these roots, defaults to all of them
+
+        Returns:
+            Roots and every mod reachable from them through required dependencies
+        """
+        roots = list(self.roots if roots is None else roots)
+        required = set(roots)
+        queue = deque(roots)
+        while queue:
+            current = queue.popleft()
+            for edge in self.edges:
+                if (
+                    edge.parent == current
+                    and edge.kind in (DependencyKind.REQUIRED, DependencyKind.LOAD_ORDER)
+                    and edge.child not in required
+                ):
+                    required.add(edge.child)
+                    queue.append(edge.child)
+        return required
+
+    def depths(self, root: str) -> Dict[str, int]:
+        """
+        Find the mods a mod pulls into the graph, optional dependencies included.
+
+        Args:
+            root: Mod to start from
+
+        Returns:
+            Every mod reachable from root, itself included, with the number of
+            dependencies on the shortest chain leading to it
+        """
+        children: Dict[str, List[str]] = {}
+        for edge in self.edges:
+            if edge.kind != DependencyKind.INCOMPATIBLE:
+                children.setdefault(edge.parent, []).append(edge.child)
+
+        depths = {root: 0}
+        queue = deque([root])
+        while queue:
+            current = queue.popleft()
+            for child in children.get(current, []):
+                if child not in depths:
+                    depths[child] = depths[current] + 1
+                    queue.append(child)
+        return depths
+
+    def roots_of(self, mod_name: str) -> List[str]:
+        """
+        Find the requested mods that pulled a mod into the graph.
+
+        Args:
+            mod_name: Mod to look up
+
+        Returns:
+            Roots depending on the mod directly or indirectly, including the mod
+            itself if it is a root, in the order they were requested
+        """
+        parents: Dict[str, List[str]] = {}
+        for edge in self.edges:
+            parents.setdefault(edge.child, []).append(edge.parent)
+
+        reached = {mod_name}
+        queue = deque([mod_name])
+        while queue:
+            for parent in parents.get(queue.popleft(), []):
+                if parent not in reached:
+                    reached.add(parent)
+                    queue.append(parent)
+        return [root for root in self.roots if root in reached]
+
+    def explain(self, mod_name: str, max_chains: int = 10) -> List[List[DependencyEdge]]:
+        """
+        Find the chains of dependencies that pulled a mod into the graph.
+
+        Args:
+            mod_name: Mod to explain
+            max_chains: Maximum number of chains to return
+
+        Returns:
+            Chains of edges leading from a root to the mod, shortest first.
+            Empty if the mod is a root itself or not part of the graph. Fewer
+            chains than exist if finding them takes over MAX_EXPLAIN_STEPS steps.
+        """
+        children: Dict[str, List[DependencyEdge]] = {}
+        parents: Dict[str, List[str]] = {}
+        for edge in self.edges:
+            children.setdefault(edge.parent, []).append(edge)
+            parents.setdefault(edge.child, []).append(edge.parent)
+
+        # Fewest dependencies from each mod leading to mod_name, no other mod is on a chain
+        distances = {mod_name: 0}
+        queue = deque([mod_name])
+        while queue:
+            current = queue.popleft()
+            for parent in parents.get(current, []):
+                if parent not in distances:
+                    distances[parent] = distances[current] + 1
+                    queue.append(parent)
+
+        chains: List[List[DependencyEdge]] = []
+        if len(distances) == 1:
+            return chains
+
+        # Partial chains are extended by their shortest possible length, so chains come out
+        # shortest first. Among equally short ones the longest is extended first, which
+        # completes a chain before starting others, then the count keeps the order of the
+        # roots and edges.
+        count = itertools.count()
+        heap = [
+            (distances[root], 0, next(count), root, [], {root})
+            for root in self.roots
+            if root != mod_name and root in distances
+        ]
+        expanded = 0
+        while heap and len(chains) < max_chains and expanded < MAX_EXPLAIN_STEPS:
+            _, _, _, current, chain, visited = heapq.heappop(heap)
+            expanded += 1
+            for edge in children.get(current, []):
+                if edge.child == mod_name:
+                    chains.append(chain + [edge])
+                elif edge.child in distances and edge.child not in visited:
+                    length = len(chain) + 1 + distances[edge.child]
+                    extended = (edge.child, chain + [edge], visited | {edge.child})
+                    heapq.heappush(heap, (length, -len(chain) - 1, next(count)) + extended)
+
+        return chains[:max_chains]
+
+    def format_explanation(self, mod_name: str, max_chains: int = 10) -> str:
+        """
+        Describe why a mod is part of the graph in human readable form.
+
+        Args:
+            mod_name: Mod to explain
+            max_chains: Maximum number of chains to describe
+
+        Returns:
+            One line per chain, e.g. "A → requires B → optionally requires C"
+        """
+        if mod_name in self.roots:
+            return f"{mod_name} was requested directly."
+
+        chains = self.explain(mod_name, max_chains)
+        if not chains:
+            return f"{mod_name} is not part of the dependency graph."
+
+        lines = []
+        for chain in chains:
+            parts = [chain[0].parent]
+            parts.extend(f"{EXPLAIN_VERBS[edge.kind]} {edge.child}" for edge in chain)
+            lines.append(" → ".join(parts))
+        return "\n".join(lines)
+
+    def _label(self, mod_name: str) -> str:
+        version = self.versions.get(mod_name)
+        return f"{mod_name} {version}" if version else mod_name
+
+    def to_dot(self) -> str:
+        """
+        Render the graph in Graphviz DOT format.
+
+        Returns:
+            DOT source, roots are drawn bold, optional dependencies dashed and
+            dependencies other than plain requirements labelled with their prefix
+        """
+        lines = ["digraph dependencies {", "    rankdir=LR;", "    node [shape=box];"]
+
+        for mod_name in self.versions:
+            label = self._label(mod_name).replace('"', '\\"')
+            style = ", style=bold" if mod_name in self.roots else ""
+            lines.append(f'    "{mod_name}" [label="{label}"{style}];')
+
+        for edge in self.edges:
+            attributes = []
+            if edge.kind in EDGE_LABELS:
+                attributes.append(f'label="{EDGE_LABELS[edge.kind]}"')
+            if edge.kind in (DependencyKind.OPTIONAL, DependencyKind.HIDDEN_OPTIONAL):
+                attributes.append("style=dashed")
+
+            style = f" [{', '.join(attributes)}]" if attributes else ""
+            lines.append(f'    "{edge.parent}" -> "{edge.child}"{style};')
+
+        lines.append("}")
+        return "\n".join(lines) + "\n"
+
+    def to_mermaid(self) -> str:
+        """
+        Render the graph as a Mermaid flowchart.
+
+        Returns:
+            Mermaid source, optional dependencies are drawn dotted and
+            dependencies other than plain requirements labelled with their prefix
+        """
+        ids = {mod_name: f"mod{index}" for index, mod_name in enumerate(self.versions)}
+        lines = ["graph LR"]
+
+        for mod_name, node_id in ids.items():
+            label = self._label(mod_name).replace('"', "#quot;")
+            lines.append(f'    {node_id}["{label}"]')
+
+        for edge in self.edges:
+            if edge.kind in (DependencyKind.OPTIONAL, DependencyKind.HIDDEN_OPTIONAL):
+                arrow = f'-. "{EDGE_LABELS[edge.kind]}" .->'
+            elif edge.kind in EDGE_LABELS:
+                arrow = f'-- "{EDGE_LABELS[edge.kind]}" -->'
+            else:
+                arrow = "-->"
+            lines.append(f"    {ids[edge.parent]} {arrow} {ids[edge.child]}")
+
+        return "\n".join(lines) + "\n"
+
+    def render(self, graph_format: str) -> str:
+        """
+        Render the graph in one of GRAPH_FORMATS.
+
+        Args:
+            graph_format: "dot" or "mermaid"
+
+        Returns:
+            Rendered graph
+        """
+        if graph_format == "dot":
+            return self.to_dot()
+        if graph_format == "mermaid":
+            return self.to_mermaid()
+        raise ValueError(f"Unknown graph format {graph_format}")
+
+    def to_dict(self) -> dict:
+        """
+        The graph in a form serializable as JSON.
+
+        Returns:
+            Roots, versions and edges as [parent, child, kind]
+        """
+        return {
+            "roots": list(self.roots),
+            "versions": dict(self.versions),
+            "edges": [[edge.parent, edge.child, edge.kind.value] for edge in self.edges],
+        }
+
+    @classmethod
+    def from_dict(cls, data: dict) -> "DependencyGraph":
+        """
+        Rebuild a graph from to_dict's output.
+
+        Args:
+            data: Graph returned by to_dict
+
+        Returns:
+            The graph
+        """
+        graph = cls()
+        graph.roots = list(data["roots"])
+        graph.versions = dict(data["versions"])
+        graph.edges = [
+            DependencyEdge(parent, child, DependencyKind(kind))
+            for parent, child, kind in data["edges"]
+        ]
+        return graph
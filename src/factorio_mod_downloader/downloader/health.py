@@ -0,0 +1,159 @@
+"""
+Health checks of the metadata APIs and storages a session can use.
+
+Besides the configured endpoints, Config.metadata_mirrors and
+Config.storage_mirrors list fallbacks. Each source is probed by looking up
+a well-known mod: metadata sources must answer with its releases, storages
+must serve its newest release. Sources that pass are ranked by latency and
+the client is pointed at the fastest of each kind.
+"""
+
+import time
+from concurrent.futures import ThreadPoolExecutor
+from dataclasses import dataclass
+from dataclasses import field
+from typing import Callable
+from typing import Final
+from typing import List
+from typing import Optional
+
+import requests
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import get_latest_version
+from factorio_mod_downloader.downloader.portal import get_release_size
+from factorio_mod_downloader.downloader.portal import metadata_base_url
+from factorio_mod_downloader.downloader.portal import storage_base_url
+
+
+# Mod looked up to probe sources, a dependency of many popular mods that every mirror carries
+PROBE_MOD: Final = "flib"
+
+
+@dataclass
+class SourceStatus:
+    """Outcome of probing a metadata API or a storage."""
+
+    url: str
+    available: bool
+    # Seconds the probe took, None if the source could not be reached
+    latency: Optional[float] = None
+    # Why the source is unavailable
+    error: Optional[str] = None
+
+    def __str__(self) -> str:
+        if not self.available:
+            return f"{self.url}: unavailable, {self.error}"
+        return f"{self.url}: {self.latency * 1000:.0f} ms"
+
+
+@dataclass
+class SourceReport:
+    """Probed sources of each kind, the available ones first, fastest first."""
+
+    metadata: List[SourceStatus] = field(default_factory=list)
+    storage: List[SourceStatus] = field(default_factory=list)
+
+    @property
+    def best_metadata(self) -> Optional[str]:
+        """Fastest available metadata API, None if none is available."""
+        return next((status.url for status in self.metadata if status.available), None)
+
+    @property
+    def best_storage(self) -> Optional[str]:
+        """Fastest available storage, None if none is available."""
+        return next((status.url for status in self.storage if status.available), None)
+
+    def format(self) -> str:
+        """
+        Lay the report out for display.
+
+        Returns:
+            One line per source under a heading per kind
+        """
+        lines = ["Metadata:"] + [f"  {status}" for status in self.metadata]
+        lines += ["Storage:"] + [f"  {status}" for status in self.storage]
+        return "\n".join(lines)
+
+
+def _ranked(statuses: List[SourceStatus]) -> List[SourceStatus]:
+    # Stable, so sources equally fast keep the configured order
+    return sorted(statuses, key=lambda status: (not status.available, status.latency or 0.0))
+
+
+def _probe(url: str, request: Callable[[], object]) -> SourceStatus:
+    start = time.monotonic()
+    try:
+        request()
+    except (requests.RequestException, ValueError) as e:
+        return SourceStatus(url, False, error=str(e))
+    return SourceStatus(url, True, time.monotonic() - start)
+
+
+def check_sources(
+    config: Optional[Config] = None,
+    client: Optional[PortalClient] = None,
+    probe_mod: str = PROBE_MOD,
+    timeout: float = 10,
+    log: Callable[[str], None] = lambda info: None,
+) -> SourceReport:
+    """
+    Probe the configured metadata APIs and storages and rank them.
+
+    Args:
+        config: Endpoints and their mirrors, defaults to Config()
+        client: Client to point at the fastest available sources, if any
+        probe_mod: Mod looked up on every source
+        timeout: Seconds each probe may take
+        log: Callback receiving the sources picked for the client
+
+    Returns:
+        Status of every source, for display e.g. by the GUI
+    """
+    config = config or Config()
+    session = client.session if client else None
+    metadata_urls = list(
+        dict.fromkeys(map(metadata_base_url, [config.metadata_base_url] + config.metadata_mirrors))
+    )
+    storage_urls = list(
+        dict.fromkeys(map(storage_base_url, [config.storage_base_url] + config.storage_mirrors))
+    )
+
+    versions: List[str] = []
+
+    def probe_metadata(url: str) -> SourceStatus:
+        def request():
+            version = get_latest_version(probe_mod, timeout, url, session)
+            if not version:
+                raise ValueError(f"{probe_mod} has no releases")
+            versions.append(version)
+
+        return _probe(url, request)
+
+    def probe_storage(url: str) -> SourceStatus:
+        return _probe(url, lambda: get_release_size(probe_mod, version, timeout, url, session))
+
+    with ThreadPoolExecutor(max_workers=len(metadata_urls) + len(storage_urls)) as executor:
+        report = SourceReport(metadata=_ranked(list(executor.map(probe_metadata, metadata_urls))))
+
+        if not versions:
+            error = f"no metadata source knows a release of {probe_mod} to probe with"
+            report.storage = [SourceStatus(url, False, error=error) for url in storage_urls]
+            return report
+
+        # Mirrors can lag behind, the oldest newest release is the likeliest to be everywhere
+        version = min(versions, key=parse_version)
+        report.storage = _ranked(list(executor.map(probe_storage, storage_urls)))
+
+    if client:
+        if report.best_metadata:
+            client.metadata_base_url = report.best_metadata
+        if report.best_storage:
+            client.storage_base_url = report.best_storage
+        log(
+            f"Using {metadata_base_url(client.metadata_base_url)} for metadata "
+            f"and {client.source} for releases.\n"
+        )
+    return report
@@ -3,98 +3,48 @@ Downloader helper functions and utilities.
 """
 
 import random
-import socket
-import time
-from typing import Optional
+from datetime import datetime
+from datetime import timezone
+from typing import Tuple
 
-import requests
-from selenium.webdriver.support import expected_conditions as EC
-from selenium.webdriver.support.ui import WebDriverWait
 
-
-def is_website_up(url: str, timeout: int = 5) -> bool:
+def generate_anticache() -> str:
     """
-    Check if a website is accessible.
-
-    Args:
-        url: Website URL to check
-        timeout: Request timeout in seconds
+    Generate a random anti-cache parameter.
 
     Returns:
-        True if website is accessible, False otherwise
+        16-digit random number as string with '0.' prefix
     """
-    try:
-        response = requests.get(url, timeout=timeout)
-        if response.status_code == 200:
-            return True
-        else:
-            print(f"Website responded with status code: {response.status_code}")
-            return False
-    except requests.exceptions.RequestException as e:
-        print(f"Error connecting to {url}: {e}")
-        return False
+    random_number = random.randint(1_000_000_000_000_000, 9_999_999_999_999_999)
+    return f"0.{random_number}"
 
 
-def is_port_free(port: int) -> bool:
+def parse_version(version: str) -> Tuple[int, ...]:
     """
-    Check if a port is available.
+    Convert a dotted version string into a comparable tuple.
 
     Args:
-        port: Port number to check
+        version: Version string (e.g. "1.10.2")
 
     Returns:
-        True if port is free, False otherwise
+        Tuple of version components (e.g. (1, 10, 2))
     """
-    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as sock:
-        result = sock.connect_ex(("localhost", port))
-        return result != 0
+    return tuple(int(part) for part in version.split(".") if part.isdigit())
 
 
-def find_free_port(start_port: int = 9222, step: int = 10) -> int:
+def parse_date(text: str) -> datetime:
     """
-    Find the first available port starting from start_port.
+    Parse an ISO 8601 date or time, e.g. "2024-01-01" or the portal's release times.
 
     Args:
-        start_port: Starting port number
-        step: Increment step between attempts
+        text: Date or time, in UTC unless it gives an offset
 
     Returns:
-        First available port number
-    """
-    port = start_port
-    while port:
-        if is_port_free(port):
-            return port
-        port += step
-    return start_port
-
+        Timezone aware time, midnight for a date
 
-def generate_anticache() -> str:
+    Raises:
+        ValueError: If the text is not an ISO 8601 date or time
     """
-    Generate a random anti-cache parameter.
+    parsed = datetime.fromisoformat(text.replace("Z", "+00:00"))
+    return parsed if parsed.tzinfo else parsed.replace(tzinfo=timezone.utc)
 
-    Returns:
-        16-digit random number as string with '0.' prefix
-    """
-    random_number = random.randint(1_000_000_000_000_000, 9_999_999_999_999_999)
-    return f"0.{random_number}"
-
-
-def wait_for_element(driver, by, value, timeout: int = 15) -> bool:
-    """
-    Wait for an element to be present on the page.
-
-    Args:
-        driver: Selenium WebDriver instance
-        by: Locator strategy (e.g., By.CSS_SELECTOR)
-        value: Locator value
-        timeout: Maximum wait time in seconds
-
-    Returns:
-        True if element found, False on timeout
-    """
-    try:
-        WebDriverWait(driver, timeout).until(EC.presence_of_element_located((by, value)))
-        return True
-    except Exception:
-        return False
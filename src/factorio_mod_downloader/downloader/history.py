@@ -0,0 +1,145 @@
+"""
+History of the downloads and updates run, one JSON report per line.
+
+Every run appends its report to history.jsonl in the history directory, so
+what an unattended update changed can be looked up afterwards:
+
+    record = last_run()
+    print(record.operation, record.finished_at, record.report["downloaded"])
+"""
+
+import json
+from dataclasses import asdict
+from dataclasses import dataclass
+from datetime import datetime
+from datetime import timezone
+from pathlib import Path
+from typing import Final
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.game.profiles import default_profiles_path
+
+
+HISTORY_FILE: Final = "history.jsonl"
+
+
+def default_history_dir() -> Path:
+    """
+    Locate the directory the run history is kept in.
+
+    Returns:
+        `factorio-mod-downloader/history` in the user's configuration directory
+    """
+    return default_profiles_path().with_name("history")
+
+
+@dataclass
+class RunRecord:
+    """A run as kept in the history."""
+
+    # What was run, e.g. "download", "update" or "deploy"
+    operation: str
+    output_path: str
+    # Start and end of the run, ISO 8601 in UTC
+    started_at: str
+    finished_at: str
+    exit_code: int
+    dry_run: bool
+    # Report of the run, see DownloadResult.to_dict and DownloadResult.from_dict
+    report: dict
+
+
+def record_run(
+    operation: str,
+    output_path: str,
+    report: dict,
+    exit_code: int,
+    started_at: datetime,
+    dry_run: bool = False,
+    history_dir: Optional[str] = None,
+) -> RunRecord:
+    """
+    Append a run to the history.
+
+    Args:
+        operation: What was run, e.g. "download"
+        output_path: Directory the run worked on
+        report: Report of the run
+        exit_code: Exit code of the run, see exit_codes.py
+        started_at: When the run started, timezone aware
+        dry_run: Whether the run was a dry run
+        history_dir: Directory of the history, defaults to default_history_dir()
+
+    Returns:
+        The record appended
+    """
+    record = RunRecord(
+        operation=operation,
+        output_path=str(Path(output_path).resolve()),
+        started_at=started_at.astimezone(timezone.utc).isoformat(),
+        finished_at=datetime.now(timezone.utc).isoformat(),
+        exit_code=exit_code,
+        dry_run=dry_run,
+        report=report,
+    )
+
+    history_path = Path(history_dir) if history_dir else default_history_dir()
+    history_path.mkdir(parents=True, exist_ok=True)
+    # A single write per line, so runs of several instances never interleave
+    with open(history_path / HISTORY_FILE, "a", encoding="utf-8") as file:
+        file.write(json.dumps(asdict(record)) + "\n")
+    return record
+
+
+def history(
+    n: int = 10, output_path: Optional[str] = None, history_dir: Optional[str] = None
+) -> List[RunRecord]:
+    """
+    Look up the latest runs.
+
+    Args:
+        n: Number of runs to return
+        output_path: Only runs on this directory
+        history_dir: Directory of the history, defaults to default_history_dir()
+
+    Returns:
+        Up to n runs, the latest first
+    """
+    history_path = Path(history_dir) if history_dir else default_history_dir()
+    try:
+        with open(history_path / HISTORY_FILE, "r", encoding="utf-8") as file:
+            lines = file.readlines()
+    except OSError:
+        return []
+
+    wanted = str(Path(output_path).resolve()) if output_path else None
+    records = []
+    for line in reversed(lines):
+        if len(records) == n:
+            break
+        try:
+            record = RunRecord(**json.loads(line))
+        except (ValueError, TypeError):
+            # A line cut short by a crash
+            continue
+        if wanted is None or record.output_path == wanted:
+            records.append(record)
+    return records
+
+
+def last_run(
+    output_path: Optional[str] = None, history_dir: Optional[str] = None
+) -> Optional[RunRecord]:
+    """
+    Look up the latest run.
+
+    Args:
+        output_path: Only runs on this directory
+        history_dir: Directory of the history, defaults to default_history_dir()
+
+    Returns:
+        The latest run, None if there is none
+    """
+    records = history(1, output_path, history_dir)
+    return records[0] if records else None
@@ -0,0 +1,126 @@
+"""
+Advisory locking of an output directory against other running instances.
+
+The lock is held on a `.fmd-lock` file in the directory through the
+operating system, so it is released whenever the process holding it exits,
+crashes included, and never goes stale. Within a process the lock is
+reentrant, nested operations on the same directory share it.
+"""
+
+import os
+import threading
+import time
+from pathlib import Path
+from typing import Dict
+from typing import Final
+from typing import Optional
+from typing import TextIO
+
+
+LOCK_FILE: Final = ".fmd-lock"
+POLL_INTERVAL: Final = 0.5  # seconds
+
+_held: Dict[Path, list] = {}
+_held_lock = threading.Lock()
+
+
+class DirectoryLockedError(Exception):
+    """Raised when another instance holds the lock of a directory."""
+
+
+def _try_lock(file: TextIO) -> bool:
+    try:
+        if os.name == "nt":
+            import msvcrt
+
+            # Locks bytes from the current position, which is the end of a file opened
+            # for appending, the first byte is the same whatever the holder wrote
+            file.seek(0)
+            msvcrt.locking(file.fileno(), msvcrt.LK_NBLCK, 1)
+        else:
+            import fcntl
+
+            fcntl.flock(file.fileno(), fcntl.LOCK_EX | fcntl.LOCK_NB)
+    except OSError:
+        return False
+    return True
+
+
+def _holder(path: Path) -> str:
+    try:
+        pid = path.read_text(encoding="utf-8").strip()
+    except OSError:
+        return ""
+    return f" (pid {pid})" if pid else ""
+
+
+class DirectoryLock:
+    """Lock of a directory, to be used as a context manager."""
+
+    def __init__(self, directory: str, timeout: Optional[float] = 0):
+        """
+        Initialize the lock.
+
+        Args:
+            directory: Directory to lock, created if missing
+            timeout: Seconds to wait for another instance to release the
+                lock, 0 to fail at once, None to wait indefinitely
+        """
+        self.path = Path(directory).resolve() / LOCK_FILE
+        self.timeout = timeout
+
+    def acquire(self):
+        """
+        Take the lock.
+
+        Raises:
+            DirectoryLockedError: If another instance still holds it after the timeout
+        """
+        deadline = None if self.timeout is None else time.monotonic() + self.timeout
+        file: Optional[TextIO] = None
+        try:
+            while True:
+                # Released while waiting, so a directory waited for never blocks the others
+                with _held_lock:
+                    if self.path in _held:
+                        _held[self.path][1] += 1
+                        return
+
+                    if file is None:
+                        self.path.parent.mkdir(parents=True, exist_ok=True)
+                        # Opened without truncating, the holder's pid must survive a failed attempt
+                        file = open(self.path, "a+", encoding="utf-8")
+                    if _try_lock(file):
+                        file.seek(0)
+                        file.truncate()
+                        file.write(str(os.getpid()))
+                        file.flush()
+                        _held[self.path] = [file, 1]
+                        file = None
+                        return
+
+                if deadline is not None and time.monotonic() >= deadline:
+                    raise DirectoryLockedError(
+                        f"Another instance is running on {self.path.parent}"
+                        f"{_holder(self.path)}, try again once it has finished"
+                    )
+                time.sleep(POLL_INTERVAL)
+        finally:
+            if file is not None:
+                file.close()
+
+    def release(self):
+        """Release the lock once every nested acquire has been released."""
+        with _held_lock:
+            held = _held[self.path]
+            held[1] -= 1
+            if held[1] == 0:
+                del _held[self.path]
+                held[0].close()
+
+    def __enter__(self) -> "DirectoryLock":
+        self.acquire()
+        return self
+
+    def __exit__(self, *exc_info):
+        self.release()
@@ -0,0 +1,234 @@
+"""
+Metrics of a long-running updater, in Prometheus' text format.
+
+watch.py records every check and update run, and exposes the metrics over
+HTTP for Prometheus to scrape, or writes them to a file for node_exporter's
+textfile collector:
+
+    python -m factorio_mod_downloader.game.watch /opt/factorio/mods --apply \\
+        --metrics-port 9464
+
+Failing auto-updates show as fmd_update_failures_total increasing, a stuck
+updater as fmd_last_check_timestamp_seconds falling behind.
+"""
+
+import os
+import threading
+import time
+from dataclasses import dataclass
+from dataclasses import field
+from http.server import BaseHTTPRequestHandler
+from http.server import ThreadingHTTPServer
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+
+
+CONTENT_TYPE: Final = "text/plain; version=0.0.4; charset=utf-8"
+# Upper bounds in seconds of the duration histograms
+DURATION_BUCKETS: Final = (0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0)
+
+
+@dataclass
+class Histogram:
+    """Distribution of observed values over fixed buckets."""
+
+    buckets: Tuple[float, ...] = DURATION_BUCKETS
+    # Observations per bucket, not cumulative, the last one above every bound
+    counts: List[int] = field(default_factory=list)
+    total: float = 0.0
+
+    def __post_init__(self):
+        self.counts = self.counts or [0] * (len(self.buckets) + 1)
+
+    def observe(self, value: float):
+        """
+        Count a value in the first bucket it fits in.
+
+        Args:
+            value: Observed value
+        """
+        index = next((i for i, bound in enumerate(self.buckets) if value <= bound), -1)
+        self.counts[index] += 1
+        self.total += value
+
+    def lines(self, name: str) -> List[str]:
+        """
+        Samples of the histogram.
+
+        Args:
+            name: Metric name
+
+        Returns:
+            Cumulative bucket, sum and count samples
+        """
+        lines = []
+        cumulative = 0
+        for bound, count in zip(self.buckets + (float("inf"),), self.counts):
+            cumulative += count
+            upper = "+Inf" if bound == float("inf") else repr(bound)
+            lines.append(f'{name}_bucket{{le="{upper}"}} {cumulative}')
+        lines.append(f"{name}_sum {self.total!r}")
+        lines.append(f"{name}_count {cumulative}")
+        return lines
+
+
+class Metrics:
+    """Counters and histograms of an updater, safe to share between threads."""
+
+    def __init__(self):
+        self.update_checks = 0
+        self.updates_available = 0
+        self.last_check: Optional[float] = None
+        self.update_runs = 0
+        # Update runs where a mod could not be resolved or downloaded
+        self.update_failures = 0
+        self.downloads = 0
+        self.downloaded_bytes = 0
+        self.download_failures = 0
+        self.resolve_duration = Histogram()
+        self.download_duration = Histogram()
+        self._lock = threading.Lock()
+
+    def record_check(self, updates: int):
+        """
+        Count a check for updates.
+
+        Args:
+            updates: Number of updates found
+        """
+        with self._lock:
+            self.update_checks += 1
+            self.updates_available = updates
+            self.last_check = time.time()
+
+    def record_run(self, result: DownloadResult):
+        """
+        Count an update run.
+
+        Args:
+            result: Outcome of the run
+        """
+        downloaded = [entry for entry in result.mods if not entry.was_cached]
+        with self._lock:
+            self.update_runs += 1
+            self.update_failures += bool(result.errors)
+            self.downloads += len(downloaded)
+            self.downloaded_bytes += sum(entry.size_bytes for entry in downloaded)
+            self.download_failures += len(result.errors)
+            self.resolve_duration.observe(result.resolve_duration)
+            self.download_duration.observe(result.download_duration)
+
+    def render(self) -> str:
+        """
+        Lay the metrics out in Prometheus' text format.
+
+        Returns:
+            HELP, TYPE and sample lines of every metric
+        """
+        with self._lock:
+            families = [
+                ("fmd_update_checks_total", "counter", "Checks for updates", [self.update_checks]),
+                (
+                    "fmd_updates_available",
+                    "gauge",
+                    "Updates found by the last check",
+                    [self.updates_available],
+                ),
+                (
+                    "fmd_last_check_timestamp_seconds",
+                    "gauge",
+                    "Unix time of the last check",
+                    [self.last_check or 0],
+                ),
+                ("fmd_update_runs_total", "counter", "Update runs", [self.update_runs]),
+                (
+                    "fmd_update_failures_total",
+                    "counter",
+                    "Update runs where a mod failed",
+                    [self.update_failures],
+                ),
+                ("fmd_downloads_total", "counter", "Releases downloaded", [self.downloads]),
+                (
+                    "fmd_downloaded_bytes_total",
+                    "counter",
+                    "Bytes of the downloaded releases",
+                    [self.downloaded_bytes],
+                ),
+                (
+                    "fmd_download_failures_total",
+                    "counter",
+                    "Mods that could not be resolved or downloaded",
+                    [self.download_failures],
+                ),
+                (
+                    "fmd_resolve_duration_seconds",
+                    "histogram",
+                    "Seconds spent resolving an update run",
+                    self.resolve_duration.lines("fmd_resolve_duration_seconds"),
+                ),
+                (
+                    "fmd_download_duration_seconds",
+                    "histogram",
+                    "Seconds spent downloading an update run",
+                    self.download_duration.lines("fmd_download_duration_seconds"),
+                ),
+            ]
+
+        lines = []
+        for name, kind, help_text, samples in families:
+            lines.append(f"# HELP {name} {help_text}.")
+            lines.append(f"# TYPE {name} {kind}")
+            if kind == "histogram":
+                lines.extend(samples)
+            else:
+                lines.extend(f"{name} {value}" for value in samples)
+        return "\n".join(lines) + "\n"
+
+    def write_textfile(self, path: str):
+        """
+        Write the metrics for node_exporter's textfile collector.
+
+        The file is replaced at once, so the collector never reads half of it.
+
+        Args:
+            path: File to write, ending in .prom
+        """
+        temp_path = f"{path}.tmp"
+        with open(temp_path, "w", encoding="utf-8") as file:
+            file.write(self.render())
+        os.replace(temp_path, path)
+
+
+def serve_metrics(metrics: Metrics, port: int, host: str = "") -> ThreadingHTTPServer:
+    """
+    Serve the metrics over HTTP from a background thread.
+
+    Args:
+        metrics: Metrics to serve on every path
+        port: Port to listen on, 0 for any free port
+        host: Address to listen on, all of them by default
+
+    Returns:
+        The running server, to stop with shutdown()
+    """
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            body = metrics.render().encode("utf-8")
+            self.send_response(200)
+            self.send_header("Content-Type", CONTENT_TYPE)
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            # Scrapes every few seconds would flood the updater's output
+            pass
+
+    server = ThreadingHTTPServer((host, port), Handler)
+    threading.Thread(target=server.serve_forever, name="metrics", daemon=True).start()
+    return server
@@ -0,0 +1,89 @@
+"""
+Export of downloaded mods in the layout of the mod storage, to be served locally.
+
+A mirror directory holds `<mod>/<version>.zip` for every release, the same
+paths the storage serves, and an index.json describing them:
+
+    {
+        "mods": {
+            "flib": {
+                "0.14.0": {"file": "flib/0.14.0.zip", "sha1": "...", "size": 123456}
+            }
+        }
+    }
+"""
+
+import json
+import os
+import shutil
+from pathlib import Path
+from typing import Final
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+
+
+MIRROR_INDEX_FILE: Final = "index.json"
+
+
+def read_mirror_index(mirror_dir: str) -> dict:
+    """
+    Read the index of a mirror directory.
+
+    Args:
+        mirror_dir: Mirror directory
+
+    Returns:
+        Parsed index, an empty one if the mirror has none yet
+    """
+    path = Path(mirror_dir) / MIRROR_INDEX_FILE
+    if not path.exists():
+        return {"mods": {}}
+
+    with open(path, "r", encoding="utf-8") as file:
+        return json.load(file)
+
+
+def export_mirror(result: DownloadResult, mirror_dir: str) -> str:
+    """
+    Copy downloaded mods into a mirror directory and update its index.
+
+    Releases already in the mirror are kept, so a mirror can be filled by
+    several runs.
+
+    Args:
+        result: Downloads to mirror, they must not have been extracted
+        mirror_dir: Mirror directory, created if missing
+
+    Returns:
+        Path of the updated index
+
+    Raises:
+        ValueError: If a mod was extracted instead of kept as a zip
+    """
+    index = read_mirror_index(mirror_dir)
+
+    for entry in result.mods:
+        if not os.path.isfile(entry.path):
+            raise ValueError(f"{entry.name} was extracted, only zips can be mirrored")
+
+        relative_path = f"{entry.name}/{entry.version}.zip"
+        target = Path(mirror_dir) / relative_path
+        target.parent.mkdir(parents=True, exist_ok=True)
+        shutil.copyfile(entry.path, target)
+
+        index["mods"].setdefault(entry.name, {})[entry.version] = {
+            "file": relative_path,
+            "sha1": entry.sha1,
+            "size": entry.size_bytes,
+        }
+
+    path = Path(mirror_dir) / MIRROR_INDEX_FILE
+    path.parent.mkdir(parents=True, exist_ok=True)
+
+    temp_path = path.with_name(f"{MIRROR_INDEX_FILE}.tmp")
+    with open(temp_path, "w", encoding="utf-8") as file:
+        json.dump(index, file, indent=2, sort_keys=True)
+        file.write("\n")
+    os.replace(temp_path, path)
+
+    return str(path)
@@ -4,78 +4,161 @@ Main downloader module for Factorio mods with dependency resolution.
 
 import os
 import time
+from threading import Event
+from threading import Lock
 from threading import Thread
-from typing import Final
+from threading import current_thread
 from typing import List
+from typing import Optional
 from typing import Set
-from typing import Tuple
 
-import chromedriver_autoinstaller
-import requests
-from bs4 import BeautifulSoup
 from CTkMessagebox import CTkMessagebox
-from selenium import webdriver
-from selenium.webdriver.chrome.options import Options
-from selenium.webdriver.common.by import By
-from selenium.webdriver.support import expected_conditions as EC
-from selenium.webdriver.support.ui import WebDriverWait
 
-from factorio_mod_downloader.downloader.helpers import find_free_port
-from factorio_mod_downloader.downloader.helpers import generate_anticache
-from factorio_mod_downloader.downloader.helpers import is_port_free
-from factorio_mod_downloader.downloader.helpers import is_website_up
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.graph import GRAPH_FORMATS
+from factorio_mod_downloader.downloader.graph import DependencyGraph
+from factorio_mod_downloader.downloader.lock import DirectoryLock
+from factorio_mod_downloader.downloader.portal import DownloadCancelledError
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import check_content_length
+from factorio_mod_downloader.downloader.portal import release_download_url
+from factorio_mod_downloader.downloader.resolver import ResolutionResult
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.resolver import check_download_size
+from factorio_mod_downloader.downloader.resolver import fetch_release_sizes
+from factorio_mod_downloader.game.installation import target_builtin_mods
+from factorio_mod_downloader.game.installation import target_factorio_version
+from factorio_mod_downloader.game.installation import target_has_dlc
+from factorio_mod_downloader.game.installed import installed_versions
+from factorio_mod_downloader.game.modpack import mod_name_from_url
+from factorio_mod_downloader.game.modpack import parse_mod_spec
 
 
-# API Constants
-BASE_FACTORIO_MOD_URL: Final = "https://mods.factorio.com/mod"
-BASE_MOD_URL: Final = "https://re146.dev/factorio/mods/en#"
-BASE_DOWNLOAD_URL: Final = "https://mods-storage.re146.dev"
+class ModDownloader(Thread):
+    """
+    Thread-based mod downloader with dependency resolution.
 
+    Used as a context manager, leaving the block stops the downloads still
+    running and cleans up after them, see close():
 
-class ModDownloader(Thread):
-    """Thread-based mod downloader with dependency resolution."""
+        with ModDownloader(mod_url, output_path, app) as downloader:
+            downloader.start()
+            downloader.join()
+    """
 
-    def __init__(self, mod_url: str, output_path: str, app):
+    def __init__(self, mod_url: str, output_path: str, app, config: Optional[Config] = None):
         """
         Initialize the mod downloader.
 
         Args:
-            mod_url: URL of the mod to download
+            mod_url: URL of the mod to download, optionally pinned with `@version`
             output_path: Directory to save downloaded mods
             app: Reference to the GUI application
+            config: Resolution options, defaults to Config()
         """
         super().__init__()
         self.daemon = True
         self.output_path = output_path
-        self.mod = mod_url.split("/")[-1]  # Extract mod name from URL
-        self.mod_url = BASE_MOD_URL + mod_url
+        self.mod_url = mod_url
+        self.mod = mod_name_from_url(mod_url)
         self.app = app
-        self.downloaded_mods: Set[str] = set()
-        self.analyzed_mods: Set[str] = set()
-        self.chrome_options: Options = None
         self.download_threads = []
-        self.include_optional = self.app.optional_deps.get()
+        self.config = config or Config()
+        self.client = PortalClient.from_config(self.config, log=self.log_info)
+        self.errors: List[str] = []
+        self.graph_format = self.app.graph_format.get().lower()
+        self.graph = DependencyGraph()
+        self.factorio_version = target_factorio_version(
+            output_path, self.config.factorio_version, self.config.channel
+        )
+        self._cancelled = Event()
+        # Files being written, removed again if their download does not complete
+        self._partial_files: Set[str] = set()
+        self._partial_lock = Lock()
+
+    def __enter__(self) -> "ModDownloader":
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self._cancelled.set()
+        if self.is_alive() and self is not current_thread():
+            self.join()
+        self.close()
+
+    def close(self):
+        """
+        Stop the downloads still running and release what they hold.
+
+        Partial files are removed and the HTTP session is closed. Called when
+        the download ends, safe to call again.
+        """
+        self._cancelled.set()
+        for thread in self.download_threads:
+            if thread is not current_thread():
+                thread.join()
+
+        with self._partial_lock:
+            for file_path in self._partial_files:
+                if os.path.exists(file_path):
+                    os.remove(file_path)
+            self._partial_files.clear()
+        self.client.session.close()
 
     def run(self):
         """Execute the download process."""
         try:
             self.log_info(f"Loading mod {self.mod}.\n")
+            self.log_info(f"Selecting releases for Factorio {self.factorio_version}.\n")
 
-            if not is_website_up(BASE_MOD_URL):
-                raise Exception("Website down. Please check your connection.")
+            result = self.resolve_mods()
+            self.graph = result.graph
+            self.errors.extend(result.errors)
 
-            self.chrome_options = self._init_selenium()
-            self.download_mod_with_dependencies(self.mod_url, self.output_path)
+            if self.config.max_total_size is not None:
+                self.log_info("Checking download size.\n")
+                check_download_size(result, self.config.max_total_size, self.client)
 
-            active_threads = [t for t in self.download_threads if t.is_alive()]
-            if active_threads:
-                self.log_info("Waiting for all downloads to finish...\n")
+            if not self.confirm_download(result):
+                self.log_info("Download cancelled.\n")
+                self.app.progressbar.stop()
                 self.app.progress_file.after(
-                    0, lambda: self.app.progress_file.configure(text="Finalizing downloads...")
+                    0, lambda: self.app.progress_file.configure(text="Download cancelled.")
                 )
+                return
+
+            # Held until every download is done, another instance must not touch the files
+            with DirectoryLock(self.output_path, self.config.lock_timeout):
+                self.download_mods(result)
+
+                active_threads = [t for t in self.download_threads if t.is_alive()]
+                if active_threads:
+                    self.log_info("Waiting for all downloads to finish...\n")
+                    self.app.progress_file.after(
+                        0, lambda: self.app.progress_file.configure(text="Finalizing downloads...")
+                    )
 
-                for t in active_threads:
-                    t.join()
+                    for t in active_threads:
+                        t.join()
+
+            if self.graph_format in GRAPH_FORMATS:
+                self.export_graph(self.graph_format)
+
+            if self.errors:
+                self.log_info(f"Finished with {len(self.errors)} error(s).\n")
+                self.app.progress_file.after(
+                    0,
+                    lambda: self.app.progress_file.configure(text="Finished with errors."),
+                )
+
+                CTkMessagebox(
+                    title="Download Completed",
+                    width=500,
+                    wraplength=500,
+                    message="Mods downloaded with errors:\n" + "\n".join(self.errors),
+                    icon="warning",
+                    option_1="Ok",
+                )
+                return
 
             self.log_info("All mods downloaded successfully.\n")
             self.app.progress_file.after(
@@ -93,7 +176,8 @@ class ModDownloader(Thread):
             )
 
         except Exception as e:
-            self.log_info(f"Error: {str(e).split("\n")[0]}\n")
+            error_msg = str(e).split("\n")[0]
+            self.log_info(f"Error: {error_msg}\n")
 
             CTkMessagebox(
                 title="Error",
@@ -109,191 +193,71 @@ class ModDownloader(Thread):
             )
 
         finally:
+            self.close()
             self.app.download_button.configure(state="normal", text="Start Download")
             self.app.path_button.configure(state="normal")
 
-    def _init_selenium(self) -> Options:
-        """
-        Initialize Selenium WebDriver options.
-
-        Returns:
-            Chrome Options object
-
-        Raises:
-            Exception: If chromedriver installation fails
-        """
-        try:
-            self.app.progress_file.after(
-                0,
-                lambda: self.app.progress_file.configure(
-                    text="Downloading and loading dependencies."
-                ),
-            )
-
-            self.log_info("Downloading application dependencies.\n")
-            chromedriver_autoinstaller.install()
-            self.log_info("Finished downloading application dependencies.\n")
-
-            chrome_options = Options()
-            chrome_options.add_argument("--headless")
-            chrome_options.add_argument("--window-position=-2400,-2400")
-            chrome_options.add_argument("--disable-gpu")
-
-            # Find and set a free debugging port
-            port = find_free_port()
-            chrome_options.add_argument(f"--remote-debugging-port={port}")
-
-            self.log_info("Configured application dependencies.\n")
-            return chrome_options
-
-        except Exception as e:
-            self.log_info(f"Error initializing Selenium: {str(e).split("\n")[0]}\n")
-            raise
-
-    def init_driver(self) -> webdriver.Chrome:
-        """
-        Initialize a Chrome WebDriver instance.
-
-        Returns:
-            Chrome WebDriver instance
-        """
-        return webdriver.Chrome(options=self.chrome_options)
-
-    def close_driver(self, driver: webdriver.Chrome):
-        """
-        Close and cleanup a WebDriver instance.
-
-        Args:
-            driver: WebDriver instance to close
-        """
-        try:
-            driver.stop_client()
-            driver.close()
-            driver.quit()
-        except Exception as e:
-            print(f"Error closing driver: {str(e).split("\n")[0]}")
-
-    def get_page_source(self, url: str, is_dependency_check: bool = False) -> BeautifulSoup:
-        """
-        Fetch and parse a webpage.
-
-        Args:
-            url: URL to fetch
-            is_dependency_check: Whether to wait for dependency table to load
-
-        Returns:
-            BeautifulSoup object of the parsed HTML
-
-        Raises:
-            Exception: If page loading fails
-        """
-        driver = self.init_driver()
-
-        try:
-            driver.get(url)
-
-            if is_dependency_check:
-                # Wait for dependency table to load
-                WebDriverWait(driver, 15).until(
-                    EC.presence_of_element_located((By.CSS_SELECTOR, "table.panel-hole"))
-                )
-            else:
-                time.sleep(2)
-
-            html = driver.page_source
-            return BeautifulSoup(html, "html.parser")
-        except Exception as e:
-            self.log_info(f"Error loading {url}: {str(e).split("\n")[0]}\n")
-            return None
-        finally:
-            self.close_driver(driver)
-
-    def get_mod_name(self, soup: BeautifulSoup) -> str:
+    def resolve_mods(self) -> ResolutionResult:
         """
-        Extract mod name from parsed HTML.
-
-        Args:
-            soup: BeautifulSoup object of mod page
+        Select releases for the mod and all its dependencies.
 
         Returns:
-            Mod name
-
-        Raises:
-            ValueError: If mod name cannot be found
+            Resolved mods and the dependency graph connecting them
         """
-        dd_element = soup.find("dd", id="mod-info-name")
-        if not dd_element:
-            raise ValueError("Could not find mod name in page")
-        return dd_element.get_text(strip=True).strip()
-
-    def get_latest_version(self, soup: BeautifulSoup) -> str:
-        """
-        Extract the latest mod version from parsed HTML.
-
-        Args:
-            soup: BeautifulSoup object of mod page
-
-        Returns:
-            Latest version identifier
+        self.app.progressbar.stop()
+        self.app.progress_file.after(
+            0,
+            lambda: self.app.progress_file.configure(text=f"Resolving dependencies of {self.mod}"),
+        )
+        self.app.progressbar.configure(mode="indeterminate")
+        self.app.progressbar.start()
 
-        Raises:
-            ValueError: If version cannot be found
-        """
-        select = soup.find("select", {"id": "mod-version"})
-        if not select:
-            raise ValueError("No version select element found")
+        builtin_mods = target_builtin_mods(self.output_path, self.config.builtin_mods)
+        resolver = Resolver(
+            self.factorio_version,
+            self.config,
+            self.log_info,
+            self.client,
+            builtin_mods=builtin_mods,
+            has_dlc=target_has_dlc(self.output_path, self.config.has_dlc),
+            installed=installed_versions(self.output_path),
+        )
+        _, version = parse_mod_spec(self.mod_url)
+        result = resolver.resolve([self.mod], {self.mod: version} if version else None)
 
-        # Find the latest version (marked with 'last')
-        for option in select.find_all("option"):
-            if "(last)" in option.text:
-                return option["value"]
+        for mod in result.mods.values():
+            self.log_info(f"Loaded mod {mod.name} with version {mod.version}.\n")
+            if mod.dependencies:
+                dep_names = ", ".join([str(dep) for dep in mod.dependencies])
+                self.log_info(f"Dependencies found for {mod.name}: {dep_names}\n")
 
-        # Fallback to first version
-        first_option = select.find("option")
-        if not first_option:
-            raise ValueError("No version options found")
+        for error in result.errors:
+            self.log_info(f"Error resolving {error}\n")
 
-        return first_option["value"]
+        return result
 
-    def get_required_dependencies(self, mod_name: str) -> List[Tuple[str, str]]:
+    def confirm_download(self, result: ResolutionResult) -> bool:
         """
-        Fetch required dependencies for a mod.
+        Ask the user whether to download the resolved mods.
 
         Args:
-            mod_name: Name of the mod
+            result: Resolution about to be downloaded
 
         Returns:
-            List of (dependency_name, dependency_url) tuples
+            True if the user chose to continue
         """
-        dependency_url = (
-            f"{BASE_FACTORIO_MOD_URL}/{mod_name}/dependencies?direction=out&sort=idx&filter=all"
+        self.log_info("Checking download size.\n")
+        fetch_release_sizes(result, self.client)
+        response = CTkMessagebox(
+            title="Continue?",
+            width=500,
+            wraplength=500,
+            message=f"This will download {result.summary()}. Continue?",
+            icon="question",
+            option_1="Cancel",
+            option_2="Yes",
         )
-
-        try:
-            soup = self.get_page_source(dependency_url, is_dependency_check=True)
-            if not soup:
-                self.log_info(f"Could not fetch dependencies for {mod_name}\n")
-                return []
-
-            required_mods = []
-
-            links = soup.find_all("a", class_="mod-dependencies-required")
-            for link in links:
-                dep_name = link.get_text(strip=True)
-                mod_url = f"{BASE_MOD_URL}{BASE_FACTORIO_MOD_URL}/{dep_name}"
-                required_mods.append((dep_name, mod_url))
-
-            if self.include_optional:
-                for link in soup.find_all("a", class_="mod-dependencies-optional"):
-                    dep_name = link.get_text(strip=True)
-                    mod_url = f"{BASE_MOD_URL}{BASE_FACTORIO_MOD_URL}/{dep_name}"
-                    required_mods.append((dep_name, mod_url))
-
-            return required_mods
-
-        except Exception as e:
-            self.log_info(f"Could not fetch dependencies for {mod_name}: {e}\n")
-            return []
+        return bool(response) and response.get() == "Yes"
 
     def download_file(self, url: str, file_path: str, file_name: str):
         """
@@ -306,168 +270,147 @@ class ModDownloader(Thread):
         """
         entry = self.app.downloader_frame.add_download(file_name)
         entry.progress_bar.set(0)
+        # Written next to the final file and renamed once complete
+        temp_path = f"{file_path}.part"
 
         def _download():
             max_retries = 3
             retry_delay = 2  # seconds
 
-            for attempt in range(1, max_retries + 1):
-                try:
-                    response = requests.get(url, stream=True, timeout=30)
-                    response.raise_for_status()
-
-                    total_size = int(response.headers.get("content-length", 0))
-                    min_chunk = 64 * 1024  # 64 KB
-                    max_chunk = 4 * 1024 * 1024  # 4 MB
-                    block_size = max(min_chunk, min(total_size // 100, max_chunk))
-                    progress = 0
-
-                    # Indeterminate progress if no total size
-                    if not total_size:
-                        entry.progress_bar.after(
-                            0, entry.progress_bar.configure, {"mode": "indeterminate"}
-                        )
+            with self._partial_lock:
+                self._partial_files.add(temp_path)
 
-                    with open(file_path, "wb") as file:
-                        start_time = time.time()
-                        last_update = start_time
-
-                        for chunk in response.iter_content(chunk_size=block_size):
-                            if not chunk:
-                                continue
-
-                            file.write(chunk)
-                            progress += len(chunk)
-
-                            percentage = progress / total_size if total_size else 0
-                            now = time.time()
-
-                            # Update UI every ~0.2s for smoother visuals
-                            if now - last_update >= 0.2:
-                                elapsed = now - start_time
-                                speed = (
-                                    (progress / 1024 / 1024) / elapsed if elapsed > 0 else 0.0
-                                )  # MB/s
-
-                                downloaded_mb = progress / 1024 / 1024
-                                total_mb = total_size / 1024 / 1024 if total_size else 0
-
-                                # Thread-safe update using DownloadEntry.update_progress
-                                entry.progress_bar.after(
-                                    0,
-                                    lambda p=percentage, d=downloaded_mb, t=total_mb, s=speed: entry.update_progress(
-                                        p, d, t, s
-                                    ),
-                                )
-
-                                last_update = now
-
-                    # ✅ Mark complete
-                    entry.text_label.after(0, entry.mark_complete)
-                    self.log_info(f"Downloaded: {file_path.replace("\\", "/")}.\n")
-                    break  # success, exit retry loop
-
-                except Exception as e:
-                    # Delete partial file
-                    if os.path.exists(file_path):
-                        os.remove(file_path)
-
-                    if attempt < max_retries:
-                        entry.text_label.after(
-                            0, lambda x=attempt: entry.mark_retrying(x, max_retries)
-                        )
-                        self.log_info(
-                            f"Error downloading {file_path} (attempt {attempt}): {e}\nRetrying..."
-                        )
-                        time.sleep(retry_delay)
-                    else:
-                        entry.text_label.after(0, lambda: entry.mark_failed(str(e)))
-                        self.log_info(
-                            f"Failed to download {file_path} after {max_retries} attempts: {e}\n"
+            # Bounds the downloads running at once, each holding a buffer
+            with self.client.throttle:
+                for attempt in range(1, max_retries + 1):
+                    try:
+                        response = self.client.session.get(
+                            url, stream=True, timeout=self.client.download_timeout
                         )
+                        response.raise_for_status()
+
+                        total_size = int(response.headers.get("content-length", 0))
+                        min_chunk = 64 * 1024  # 64 KB
+                        max_chunk = 1024 * 1024  # 1 MB
+                        block_size = max(min_chunk, min(total_size // 100, max_chunk))
+                        progress = 0
+
+                        # Indeterminate progress if no total size
+                        if not total_size:
+                            entry.progress_bar.after(
+                                0, entry.progress_bar.configure, {"mode": "indeterminate"}
+                            )
+
+                        with open(temp_path, "wb") as file:
+                            start_time = time.time()
+                            last_update = start_time
+
+                            for chunk in response.iter_content(chunk_size=block_size):
+                                if self._cancelled.is_set():
+                                    raise DownloadCancelledError("Download cancelled")
+                                if not chunk:
+                                    continue
+
+                                file.write(chunk)
+                                progress += len(chunk)
+
+                                percentage = progress / total_size if total_size else 0
+                                now = time.time()
+
+                                # Update UI every ~0.2s for smoother visuals
+                                if now - last_update >= 0.2:
+                                    elapsed = now - start_time
+                                    speed = (
+                                        (progress / 1024 / 1024) / elapsed if elapsed > 0 else 0.0
+                                    )  # MB/s
+
+                                    downloaded_mb = progress / 1024 / 1024
+                                    total_mb = total_size / 1024 / 1024 if total_size else 0
+
+                                    # Thread-safe update using DownloadEntry.update_progress
+                                    entry.progress_bar.after(
+                                        0,
+                                        lambda p=percentage, d=downloaded_mb, t=total_mb, s=speed: entry.update_progress(
+                                            p, d, t, s
+                                        ),
+                                    )
+
+                                    last_update = now
+
+                        check_content_length(response, progress)
+                        os.replace(temp_path, file_path)
+                        with self._partial_lock:
+                            self._partial_files.discard(temp_path)
+
+                        # ✅ Mark complete
+                        entry.text_label.after(0, entry.mark_complete)
+                        self.log_info(f"Downloaded: {file_path.replace("\\", "/")}.\n")
+                        break  # success, exit retry loop
+
+                    except Exception as e:
+                        # Delete partial file
+                        if os.path.exists(temp_path):
+                            os.remove(temp_path)
+
+                        if isinstance(e, DownloadCancelledError):
+                            entry.text_label.after(0, lambda: entry.mark_failed("Cancelled"))
+                            self.errors.append(f"{file_name}: {e}")
+                            break
+
+                        if attempt < max_retries:
+                            entry.text_label.after(
+                                0, lambda x=attempt: entry.mark_retrying(x, max_retries)
+                            )
+                            self.log_info(
+                                f"Error downloading {file_path} (attempt {attempt}): {e}\n"
+                                "Retrying..."
+                            )
+                            time.sleep(retry_delay)
+                        else:
+                            entry.text_label.after(0, lambda: entry.mark_failed(str(e)))
+                            self.errors.append(f"{file_name}: {e}")
+                            self.log_info(
+                                f"Failed to download {file_path} after {max_retries} "
+                                f"attempts: {e}\n"
+                            )
 
         # Run download in background thread to prevent GUI freeze
         t = Thread(target=_download, daemon=True)
         t.start()
         self.download_threads.append(t)
 
-    def download_mod_with_dependencies(self, mod_url: str, download_path: str):
+    def download_mods(self, result: ResolutionResult):
         """
-        Recursively download a mod and all its dependencies.
+        Download every resolved mod in the background.
 
         Args:
-            mod_url: URL of the mod to download
-            download_path: Directory to save downloads
+            result: Resolution to download
         """
-        # Update UI with current mod being analyzed
-        mod_name_display = mod_url.split("/")[-1]
-        self.app.progressbar.stop()
-        self.app.progress_file.after(
-            0,
-            lambda: self.app.progress_file.configure(text=f"Analyzing mod {mod_name_display}"),
-        )
+        os.makedirs(self.output_path, exist_ok=True)
 
-        self.app.progressbar.configure(mode="indeterminate")
-        self.app.progressbar.start()
-
-        try:
-            # Fetch mod information
-            soup = self.get_page_source(mod_url)
-            mod_name = self.get_mod_name(soup)
-            latest_version = self.get_latest_version(soup)
+        for mod in result.mods.values():
+            if self._cancelled.is_set():
+                break
+            download_url = release_download_url(mod.name, mod.version, self.client.storage_base_url)
+            file_path = os.path.join(self.output_path, mod.file_name)
 
-            if not mod_name or not latest_version:
-                self.log_info(f"Error: Could not get mod info for {mod_url}. Skipping!\n")
-                return
-
-            # Skip reserved dependencies
-            if mod_name in ("space-age",):
-                self.log_info(
-                    f"Skipping reserved dependency {mod_name}. Download manually if needed.\n"
-                )
-                return
+            self.log_info(f"Downloading {mod.file_name}.\n")
+            self.download_file(download_url, file_path, mod.file_name)
 
-            self.log_info(f"Loaded mod {mod_name} with version {latest_version}.\n")
-            self.analyzed_mods.add(mod_url)
-
-            # Construct download URL
-            download_url = (
-                f"{BASE_DOWNLOAD_URL}/{mod_name}/{latest_version}.zip"
-                f"?anticache={generate_anticache()}"
-            )
-            file_name = f"{mod_name}_{latest_version}.zip"
-            file_path = os.path.join(download_path, file_name)
-
-            # Download the mod
-            os.makedirs(download_path, exist_ok=True)
-
-            if file_name not in self.downloaded_mods:
-                self.log_info(f"Downloading {file_name}.\n")
-                self.downloaded_mods.add(file_name)
-                self.download_file(download_url, file_path, file_name)
-            else:
-                self.log_info(f"Mod already downloaded {file_name}. Skipping!\n")
-
-            # Fetch and recursively download dependencies
-            self.log_info(f"Loading dependencies for {mod_name}.\n")
-            dependencies = self.get_required_dependencies(mod_name)
-
-            if not dependencies:
-                self.log_info(f"No dependencies found for {mod_name}.\n")
-                return
-
-            dep_names = ", ".join([dep_name for dep_name, _ in dependencies])
-            self.log_info(f"Dependencies found for {mod_name}: {dep_names}\n")
-
-            for dep_name, dep_url in dependencies:
-                if dep_name in self.downloaded_mods or dep_url in self.analyzed_mods:
-                    continue
+    def export_graph(self, graph_format: str):
+        """
+        Save the dependency graph next to the downloaded mods.
 
-                self.log_info(f"Analyzing dependency {dep_name} of {mod_name}\n")
-                self.download_mod_with_dependencies(dep_url, download_path)
+        Args:
+            graph_format: One of GRAPH_FORMATS
+        """
+        file_path = os.path.join(
+            self.output_path, f"dependency-graph{GRAPH_FORMATS[graph_format]}"
+        )
+        with open(file_path, "w", encoding="utf-8") as file:
+            file.write(self.graph.render(graph_format))
 
-        except Exception as e:
-            self.log_info(f"Error processing mod: {str(e).split("\n")[0]}\n")
+        self.log_info(f"Saved dependency graph: {file_path.replace("\\", "/")}.\n")
 
     def log_info(self, info: str):
         """
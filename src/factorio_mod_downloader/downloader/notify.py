@@ -0,0 +1,139 @@
+"""
+Webhook notifications of download and update events.
+"""
+
+import sys
+from typing import Any
+from typing import Callable
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+
+import requests
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.config import WebhookFormat
+from factorio_mod_downloader.downloader.portal import create_session
+
+
+# Discord rejects messages longer than this
+MAX_MESSAGE_LENGTH: Final = 2000
+WEBHOOK_TIMEOUT: Final = 10  # seconds
+
+
+class Notifier:
+    """
+    Posts events to a webhook.
+
+    Failing to notify never fails a download, errors are only logged.
+    """
+
+    def __init__(
+        self,
+        url: str,
+        webhook_format: WebhookFormat = WebhookFormat.JSON,
+        user_agent: Optional[str] = None,
+        log: Optional[Callable[[str], None]] = None,
+    ):
+        """
+        Initialize the notifier.
+
+        Args:
+            url: Webhook URL
+            webhook_format: Payload to post
+            user_agent: User-Agent to send instead of portal.DEFAULT_USER_AGENT
+            log: Callback receiving notification errors, defaults to standard output
+        """
+        self.url = url
+        self.webhook_format = webhook_format
+        self.session = create_session(user_agent)
+        self.log = log or sys.stdout.write
+
+    @classmethod
+    def from_config(
+        cls, config: Config, log: Optional[Callable[[str], None]] = None
+    ) -> Optional["Notifier"]:
+        """
+        Create the notifier configured by config.webhook_url.
+
+        Args:
+            config: Options holding the webhook
+            log: Callback receiving notification errors, defaults to standard output
+
+        Returns:
+            Notifier, None if no webhook is configured
+        """
+        if not config.webhook_url:
+            return None
+        return cls(config.webhook_url, config.webhook_format, config.user_agent, log)
+
+    def _payload(self, event: str, message: str, data: Dict[str, Any]) -> dict:
+        if len(message) > MAX_MESSAGE_LENGTH:
+            message = message[: MAX_MESSAGE_LENGTH - 3] + "..."
+
+        if self.webhook_format == WebhookFormat.DISCORD:
+            return {"content": message}
+        if self.webhook_format == WebhookFormat.SLACK:
+            return {"text": message}
+        return {"event": event, "message": message, **data}
+
+    def notify(self, event: str, message: str, data: Optional[Dict[str, Any]] = None) -> bool:
+        """
+        Post an event to the webhook.
+
+        Args:
+            event: Event name, e.g. "downloads_completed"
+            message: Human readable description of the event
+            data: Structured details, only sent with WebhookFormat.JSON
+
+        Returns:
+            Whether the webhook accepted the event
+        """
+        try:
+            response = self.session.post(
+                self.url, json=self._payload(event, message, data or {}), timeout=WEBHOOK_TIMEOUT
+            )
+            response.raise_for_status()
+        except requests.RequestException as e:
+            self.log(f"Failed to notify webhook of {event}: {e}\n")
+            return False
+        return True
+
+    def downloads_completed(self, mods: List[dict], errors: List[str]) -> bool:
+        """
+        Notify the outcome of a download, as "downloads_failed" if anything failed.
+
+        Args:
+            mods: Downloaded mods as {"name", "version", "file_name", "was_cached"}
+            errors: Resolution and download errors
+
+        Returns:
+            Whether the webhook accepted the event
+        """
+        downloaded = [mod for mod in mods if not mod["was_cached"]]
+        lines = [f"Downloaded {len(downloaded)} mods, {len(mods) - len(downloaded)} up to date."]
+        lines += [f"- {mod['file_name']}" for mod in downloaded]
+        if errors:
+            lines.append(f"{len(errors)} failed:")
+            lines += [f"- {error}" for error in errors]
+
+        event = "downloads_failed" if errors else "downloads_completed"
+        return self.notify(event, "\n".join(lines), {"mods": mods, "errors": errors})
+
+    def updates_available(self, updates: List[dict]) -> bool:
+        """
+        Notify that newer releases of installed mods are available.
+
+        Args:
+            updates: Updates as {"name", "installed_version", "latest_version"}
+
+        Returns:
+            Whether the webhook accepted the event
+        """
+        lines = [f"{len(updates)} mod updates available:"]
+        lines += [
+            f"- {update['name']} {update['installed_version']} -> {update['latest_version']}"
+            for update in updates
+        ]
+        return self.notify("updates_available", "\n".join(lines), {"updates": updates})
@@ -0,0 +1,832 @@
+"""
+Clients for the official Factorio mod portal API and the re146 mod storage.
+"""
+
+import os
+import threading
+from contextlib import contextmanager
+from dataclasses import dataclass
+from dataclasses import field
+from importlib import metadata
+from typing import Callable
+from typing import Dict
+from typing import Final
+from typing import Iterator
+from typing import List
+from typing import Optional
+from typing import Tuple
+from typing import Union
+
+import requests
+from requests.adapters import HTTPAdapter
+from requests.auth import AuthBase
+from requests.auth import HTTPBasicAuth
+
+from factorio_mod_downloader.downloader.cache import MetadataCache
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.dependencies import Dependency
+from factorio_mod_downloader.downloader.dependencies import parse_dependencies
+from factorio_mod_downloader.downloader.helpers import generate_anticache
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.throttle import THROTTLE_STATUS_CODES
+from factorio_mod_downloader.downloader.throttle import Throttle
+from factorio_mod_downloader.downloader.throttle import retry_after
+
+
+BASE_PORTAL_API_URL: Final = "https://mods.factorio.com/api/mods"
+BASE_DOWNLOAD_URL: Final = "https://mods-storage.re146.dev"
+
+# Environment variables overriding the endpoints, e.g. for a self-hosted mirror
+METADATA_URL_ENV: Final = "FACTORIO_MOD_METADATA_URL"
+STORAGE_URL_ENV: Final = "FACTORIO_MOD_STORAGE_URL"
+# Environment variables holding credentials of the endpoints, see SourceAuth
+METADATA_AUTH_ENV: Final = "FACTORIO_MOD_METADATA_AUTH"
+STORAGE_AUTH_ENV: Final = "FACTORIO_MOD_STORAGE_AUTH"
+
+# Overload responses of a single request retried before giving up
+MAX_THROTTLED_ATTEMPTS: Final = 8
+
+# Seconds, either for connecting and reading alike or as (connect, read). The
+# read timeout bounds the wait for each chunk, not the whole request.
+Timeout = Union[float, Tuple[float, float]]
+
+
+def _package_version() -> str:
+    try:
+        return metadata.version("factorio-mod-downloader")
+    except metadata.PackageNotFoundError:
+        return "dev"
+
+
+DEFAULT_USER_AGENT: Final = f"factorio-mod-downloader/{_package_version()}"
+
+_default_session: Optional[requests.Session] = None
+
+
+class DownloadCancelledError(Exception):
+    """A download was cancelled while it was running."""
+
+
+class IncompleteDownloadError(requests.RequestException):
+    """A download ended before delivering the size announced by Content-Length."""
+
+
+def check_content_length(response: requests.Response, bytes_written: int):
+    """
+    Make sure a download received as many bytes as the server announced.
+
+    Args:
+        response: Response the file was streamed from
+        bytes_written: Number of bytes written to disk
+
+    Raises:
+        IncompleteDownloadError: If Content-Length is present and does not match
+    """
+    # Content-Length counts encoded bytes, which are not comparable once decoded
+    if response.headers.get("content-encoding"):
+        return
+
+    content_length = response.headers.get("content-length")
+    if content_length and int(content_length) != bytes_written:
+        raise IncompleteDownloadError(
+            f"Received {bytes_written} of {content_length} bytes from {response.url}"
+        )
+
+
+def create_session(user_agent: Optional[str] = None, pool_size: int = 10) -> requests.Session:
+    """
+    Create an HTTP session identifying the downloader to the servers.
+
+    Connections are kept alive and reused, so concurrent downloads from the
+    same host don't each pay for a new TCP and TLS handshake. A pool smaller
+    than the number of concurrent requests closes the surplus connections
+    after every request.
+
+    Args:
+        user_agent: User-Agent to send instead of DEFAULT_USER_AGENT
+        pool_size: Number of connections kept alive per host
+
+    Returns:
+        Session sending the User-Agent with every request
+    """
+    session = requests.Session()
+    session.headers["User-Agent"] = user_agent or DEFAULT_USER_AGENT
+    adapter = HTTPAdapter(pool_connections=pool_size, pool_maxsize=pool_size)
+    session.mount("https://", adapter)
+    session.mount("http://", adapter)
+    return session
+
+
+class SourceAuth(AuthBase):
+    """
+    Credentials of mirrors behind authentication, sent to their own source only.
+
+    A credential is either "user:password" for HTTP basic auth or
+    "Bearer <token>". It applies to every URL under its base URL; requests
+    redirected to another host, e.g. a CDN, do not carry it.
+    """
+
+    def __init__(self, credentials: Dict[str, str]):
+        """
+        Initialize the credentials.
+
+        Args:
+            credentials: Credential of each source, by base URL
+
+        Raises:
+            ValueError: If a credential is in neither form
+        """
+        self.credentials: Dict[str, AuthBase] = {}
+        for base_url, credential in credentials.items():
+            if credential.lower().startswith("bearer "):
+                self.credentials[base_url.rstrip("/")] = _BearerAuth(credential[7:].strip())
+            elif ":" in credential:
+                user, password = credential.split(":", 1)
+                self.credentials[base_url.rstrip("/")] = HTTPBasicAuth(user, password)
+            else:
+                raise ValueError(
+                    f'Credentials of {base_url} must be "user:password" or "Bearer <token>"'
+                )
+
+    def __call__(self, request: requests.PreparedRequest) -> requests.PreparedRequest:
+        # The most specific base URL wins
+        for base_url in sorted(self.credentials, key=len, reverse=True):
+            if request.url == base_url or request.url.startswith(f"{base_url}/"):
+                return self.credentials[base_url](request)
+        return request
+
+
+class _BearerAuth(AuthBase):
+    def __init__(self, token: str):
+        self.token = token
+
+    def __call__(self, request: requests.PreparedRequest) -> requests.PreparedRequest:
+        request.headers["Authorization"] = f"Bearer {self.token}"
+        return request
+
+
+def source_credentials(
+    credentials: Optional[Dict[str, str]] = None,
+    metadata_url: Optional[str] = None,
+    storage_url: Optional[str] = None,
+) -> Dict[str, str]:
+    """
+    Collect the credentials of the sources of a session.
+
+    Args:
+        credentials: Explicit credentials by base URL, take precedence over the environment
+        metadata_url: Metadata API given FACTORIO_MOD_METADATA_AUTH, see metadata_base_url
+        storage_url: Storage given FACTORIO_MOD_STORAGE_AUTH, see storage_base_url
+
+    Returns:
+        Credential of each source by base URL, see SourceAuth
+    """
+    collected = {}
+    # Never sent to the official endpoints, which take no credentials
+    if os.environ.get(METADATA_AUTH_ENV) and metadata_base_url(metadata_url) != BASE_PORTAL_API_URL:
+        collected[metadata_base_url(metadata_url)] = os.environ[METADATA_AUTH_ENV]
+    if os.environ.get(STORAGE_AUTH_ENV) and storage_base_url(storage_url) != BASE_DOWNLOAD_URL:
+        collected[storage_base_url(storage_url)] = os.environ[STORAGE_AUTH_ENV]
+    collected.update(credentials or {})
+    return collected
+
+
+def default_session() -> requests.Session:
+    """Session shared by requests made without an explicit one."""
+    global _default_session
+    if _default_session is None:
+        _default_session = create_session()
+    return _default_session
+
+
+def metadata_base_url(base_url: Optional[str] = None) -> str:
+    """
+    Pick the base URL of the mod metadata API.
+
+    Args:
+        base_url: Explicit base URL, takes precedence over the environment
+
+    Returns:
+        base_url, FACTORIO_MOD_METADATA_URL or the official portal API
+    """
+    return (base_url or os.environ.get(METADATA_URL_ENV) or BASE_PORTAL_API_URL).rstrip("/")
+
+
+def storage_base_url(base_url: Optional[str] = None) -> str:
+    """
+    Pick the base URL of the mod storage.
+
+    Args:
+        base_url: Explicit base URL, takes precedence over the environment
+
+    Returns:
+        base_url, FACTORIO_MOD_STORAGE_URL or the re146 storage
+    """
+    return (base_url or os.environ.get(STORAGE_URL_ENV) or BASE_DOWNLOAD_URL).rstrip("/")
+
+
+def get_mod_details(
+    mod_name: str,
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+    cache: Optional[MetadataCache] = None,
+) -> dict:
+    """
+    Fetch the portal's details of a mod, e.g. its downloads_count, along
+    with its releases and the dependencies declared in each release's info.json.
+
+    With a cache, a cached response is revalidated with If-None-Match and
+    If-Modified-Since and reused when the portal answers 304 Not Modified.
+
+    Args:
+        mod_name: Name of the mod
+        timeout: Request timeout, see Timeout
+        base_url: Metadata API to use instead of the default, see metadata_base_url
+        session: Session to send the request with, defaults to default_session()
+        cache: Cache to revalidate and store the response in
+
+    Returns:
+        The portal's `/full` response
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    url = f"{metadata_base_url(base_url)}/{mod_name}/full"
+
+    cached = cache.load(url) if cache else None
+    headers = {}
+    if cached and cached.get("etag"):
+        headers["If-None-Match"] = cached["etag"]
+    if cached and cached.get("last_modified"):
+        headers["If-Modified-Since"] = cached["last_modified"]
+
+    response = (session or default_session()).get(url, headers=headers, timeout=timeout)
+    if cached and response.status_code == 304:
+        return cached.get("mod") or {"name": mod_name, "releases": cached.get("releases", [])}
+
+    response.raise_for_status()
+    mod = response.json()
+    if cache:
+        cache.store(url, mod, response.headers.get("ETag"), response.headers.get("Last-Modified"))
+    return mod
+
+
+def get_mod_releases(
+    mod_name: str,
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+    cache: Optional[MetadataCache] = None,
+) -> List[dict]:
+    """
+    Fetch the list of releases of a mod from the portal, including the
+    dependencies declared in each release's info.json.
+
+    Args:
+        mod_name: Name of the mod
+        timeout: Request timeout, see Timeout
+        base_url: Metadata API to use instead of the default, see metadata_base_url
+        session: Session to send the request with, defaults to default_session()
+        cache: Cache to revalidate and store the response in, see get_mod_details
+
+    Returns:
+        List of release dictionaries as returned by the portal
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    return get_mod_details(mod_name, timeout, base_url, session, cache).get("releases", [])
+
+
+def get_latest_version(
+    mod_name: str,
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+) -> Optional[str]:
+    """
+    Fetch the version of a mod's newest release from the portal's short API,
+    which is much lighter than the `/full` response.
+
+    Args:
+        mod_name: Name of the mod
+        timeout: Request timeout, see Timeout
+        base_url: Metadata API to use instead of the default, see metadata_base_url
+        session: Session to send the request with, defaults to default_session()
+
+    Returns:
+        Version of the newest release, or None if the mod has none
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    url = f"{metadata_base_url(base_url)}/{mod_name}"
+    response = (session or default_session()).get(url, timeout=timeout)
+    response.raise_for_status()
+    mod = response.json()
+    latest = mod.get("latest_release") or {}
+    return latest.get("version") or newest_version(mod.get("releases", []))
+
+
+def list_mods(
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+    factorio_version: Optional[str] = None,
+) -> List[dict]:
+    """
+    Fetch the portal's list of every mod, a large response.
+
+    Args:
+        timeout: Request timeout, see Timeout
+        base_url: Metadata API to use instead of the default, see metadata_base_url
+        session: Session to send the request with, defaults to default_session()
+        factorio_version: Only mods with a release for this version
+
+    Returns:
+        Entries of the mod list, with the name, title, owner, summary and
+        downloads count of each mod but not its releases
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    params = {"page_size": "max"}
+    if factorio_version:
+        params["version"] = factorio_version
+    response = (session or default_session()).get(
+        metadata_base_url(base_url), params=params, timeout=timeout
+    )
+    response.raise_for_status()
+    return response.json().get("results", [])
+
+
+def newest_version(releases: List[dict]) -> Optional[str]:
+    """
+    Get the version of the newest release, whatever Factorio version it targets.
+
+    Args:
+        releases: Releases as returned by get_mod_releases
+
+    Returns:
+        Newest version, or None if there are no releases
+    """
+    versions = [release["version"] for release in releases if release.get("version")]
+    return max(versions, key=parse_version) if versions else None
+
+
+@dataclass
+class Release:
+    """A release of a mod as listed by the portal, see get_mod_releases for the raw form."""
+
+    version: str
+    # Factorio version the release targets as "major.minor", None if not declared
+    factorio_version: Optional[str] = None
+    file_name: str = ""
+    sha1: Optional[str] = None
+    # ISO 8601, e.g. "2024-10-21T12:00:00.000000Z"
+    released_at: Optional[str] = None
+    # Dependency strings of the release's info.json
+    dependency_strings: List[str] = field(default_factory=list)
+
+    @classmethod
+    def from_portal(cls, data: dict) -> "Release":
+        """
+        Build a release from a portal response.
+
+        Args:
+            data: Entry of a mod's `releases`
+
+        Returns:
+            The release
+        """
+        info_json = data.get("info_json") or {}
+        return cls(
+            version=data["version"],
+            factorio_version=info_json.get("factorio_version"),
+            file_name=data.get("file_name") or "",
+            sha1=data.get("sha1"),
+            released_at=data.get("released_at"),
+            dependency_strings=list(info_json.get("dependencies") or []),
+        )
+
+    @property
+    def dependencies(self) -> List[Dependency]:
+        """Parsed dependencies, invalid dependency strings are left out."""
+        return parse_dependencies(self.dependency_strings)
+
+
+def find_release(releases: List[dict], version: str) -> Optional[dict]:
+    """
+    Find the release with an exact version.
+
+    Args:
+        releases: Releases as returned by get_mod_releases
+        version: Version to look for (e.g. "1.4.2")
+
+    Returns:
+        The matching release, or None if the version was never released
+    """
+    return next(
+        (
+            release
+            for release in releases
+            if parse_version(release["version"]) == parse_version(version)
+        ),
+        None,
+    )
+
+
+def find_latest_release(releases: List[dict], factorio_version: str) -> Optional[dict]:
+    """
+    Find the newest release targeting a Factorio version.
+
+    Args:
+        releases: Releases as returned by get_mod_releases
+        factorio_version: Factorio version in "major.minor" form
+
+    Returns:
+        The newest compatible release, or None if no release targets the version
+    """
+    compatible = [
+        release
+        for release in releases
+        if release.get("info_json", {}).get("factorio_version") == factorio_version
+    ]
+    if not compatible:
+        return None
+
+    return max(compatible, key=lambda release: parse_version(release["version"]))
+
+
+def release_download_url(mod_name: str, version: str, base_url: Optional[str] = None) -> str:
+    """
+    Build the storage URL of a mod release.
+
+    Args:
+        mod_name: Name of the mod
+        version: Release version
+        base_url: Storage to use instead of the default, see storage_base_url
+
+    Returns:
+        Download URL of the release zip
+    """
+    storage = storage_base_url(base_url)
+    return f"{storage}/{mod_name}/{version}.zip?anticache={generate_anticache()}"
+
+
+def get_release_size(
+    mod_name: str,
+    version: str,
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+) -> Optional[int]:
+    """
+    Fetch the size of a release zip without downloading it.
+
+    The portal does not report file sizes, so they are read from the storage.
+
+    Args:
+        mod_name: Name of the mod
+        version: Release version
+        timeout: Request timeout, see Timeout
+        base_url: Storage to use instead of the default, see storage_base_url
+        session: Session to send the request with, defaults to default_session()
+
+    Returns:
+        Size in bytes, or None if the storage does not report it
+
+    Raises:
+        requests.RequestException: If the storage could not be reached
+    """
+    url = release_download_url(mod_name, version, base_url)
+    response = (session or default_session()).head(url, allow_redirects=True, timeout=timeout)
+    response.raise_for_status()
+
+    content_length = response.headers.get("content-length")
+    return int(content_length) if content_length else None
+
+
+def download_release(
+    mod_name: str,
+    version: str,
+    output_path: str,
+    timeout: Timeout = 30,
+    base_url: Optional[str] = None,
+    session: Optional[requests.Session] = None,
+    cancelled: Optional[threading.Event] = None,
+) -> str:
+    """
+    Download a single mod release without resolving its dependencies.
+
+    The release is written to a `.part` file renamed once complete, nothing
+    is left behind when the download fails or is cancelled.
+
+    Args:
+        mod_name: Name of the mod
+        version: Release version
+        output_path: Directory to save the release in
+        timeout: Request timeout, see Timeout
+        base_url: Storage to use instead of the default, see storage_base_url
+        session: Session to send the request with, defaults to default_session()
+        cancelled: Event stopping the download when set
+
+    Returns:
+        Path of the downloaded file
+
+    Raises:
+        requests.RequestException: If the download fails or is incomplete
+        DownloadCancelledError: If cancelled was set during the download
+    """
+    os.makedirs(output_path, exist_ok=True)
+    file_path = os.path.join(output_path, f"{mod_name}_{version}.zip")
+    temp_path = f"{file_path}.part"
+
+    try:
+        url = release_download_url(mod_name, version, base_url)
+        with (session or default_session()).get(url, stream=True, timeout=timeout) as response:
+            response.raise_for_status()
+            bytes_written = 0
+            with open(temp_path, "wb") as file:
+                for chunk in response.iter_content(chunk_size=64 * 1024):
+                    if cancelled and cancelled.is_set():
+                        raise DownloadCancelledError(f"Download of {mod_name} {version} cancelled")
+                    file.write(chunk)
+                    bytes_written += len(chunk)
+            check_content_length(response, bytes_written)
+        os.replace(temp_path, file_path)
+    finally:
+        if os.path.exists(temp_path):
+            os.remove(temp_path)
+
+    return file_path
+
+
+class PortalClient:
+    """
+    Access to mod metadata and release zips over HTTP.
+
+    Resolution and downloads go through a client, so they can be pointed at
+    a mirror or, in tests, at MockPortalClient. Requests share a Throttle,
+    overload responses are retried after backing off rather than failing.
+    """
+
+    def __init__(
+        self,
+        metadata_base_url: Optional[str] = None,
+        storage_base_url: Optional[str] = None,
+        connect_timeout: float = 10,
+        metadata_timeout: float = 30,
+        download_idle_timeout: float = 60,
+        user_agent: Optional[str] = None,
+        cache: Optional[MetadataCache] = None,
+        max_concurrency: int = 4,
+        log: Callable[[str], None] = lambda info: None,
+        github_token: Optional[str] = None,
+        pool_size: Optional[int] = None,
+        cross_check: bool = False,
+        credentials: Optional[Dict[str, str]] = None,
+    ):
+        """
+        Initialize the client.
+
+        Args:
+            metadata_base_url: Metadata API to use instead of the default
+            storage_base_url: Storage to use instead of the default
+            connect_timeout: Seconds to wait for a connection
+            metadata_timeout: Seconds to wait for a metadata response
+            download_idle_timeout: Seconds a download may stall between two chunks
+            user_agent: User-Agent to send instead of DEFAULT_USER_AGENT
+            cache: Cache for mod metadata, None to always fetch it
+            max_concurrency: Highest number of concurrent requests
+            log: Callback receiving throttling events
+            github_token: Token for mods fetched from GitHub, see sources.py
+            pool_size: Number of connections kept alive per host, defaults
+                to max_concurrency
+            cross_check: When metadata comes from a mirror, check the newest
+                version of each mod with the official portal and use the
+                portal's metadata instead if the mirror lacks it
+            credentials: Credentials of mirrors behind authentication by base
+                URL, see SourceAuth, along with those of the environment
+
+        Raises:
+            ValueError: If a credential is malformed
+        """
+        self.metadata_base_url = metadata_base_url
+        self.storage_base_url = storage_base_url
+        self.connect_timeout = connect_timeout
+        self.metadata_timeout = metadata_timeout
+        self.download_idle_timeout = download_idle_timeout
+        self.session = create_session(user_agent, pool_size or max(max_concurrency, 1))
+        self.session.auth = SourceAuth(
+            source_credentials(credentials, metadata_base_url, storage_base_url)
+        )
+        self.cache = cache
+        self.throttle = Throttle(max_concurrency, log)
+        self.github_token = github_token
+        self.cross_check = cross_check
+        self.log = log
+        self.cancelled = threading.Event()
+        # Details fetched inside memoized(), by mod name
+        self._memo: Optional[Dict[str, dict]] = None
+
+    @classmethod
+    def from_config(
+        cls,
+        config: Config,
+        max_concurrency: int = 4,
+        log: Callable[[str], None] = lambda info: None,
+    ) -> "PortalClient":
+        """
+        Create a client for the endpoints and options of a config.
+
+        Args:
+            config: Resolution options
+            max_concurrency: Highest number of concurrent requests
+            log: Callback receiving throttling events
+
+        Returns:
+            Configured client
+        """
+        cache = MetadataCache(config.cache_dir) if config.cache_metadata else None
+        return cls(
+            config.metadata_base_url,
+            config.storage_base_url,
+            connect_timeout=config.connect_timeout,
+            metadata_timeout=config.metadata_timeout,
+            download_idle_timeout=config.download_idle_timeout,
+            user_agent=config.user_agent,
+            cache=cache,
+            max_concurrency=max_concurrency,
+            log=log,
+            github_token=config.github_token,
+            pool_size=config.pool_size,
+            cross_check=config.cross_check_portal,
+            credentials=config.source_credentials,
+        )
+
+    @property
+    def source(self) -> str:
+        """Storage releases are downloaded from."""
+        return storage_base_url(self.storage_base_url)
+
+    @property
+    def download_timeout(self) -> Tuple[float, float]:
+        """Timeout of requests streaming release zips."""
+        return (self.connect_timeout, self.download_idle_timeout)
+
+    def cancel(self):
+        """Stop the downloads in progress and refuse new requests, see DownloadCancelledError."""
+        self.cancelled.set()
+
+    @contextmanager
+    def memoized(self) -> Iterator[None]:
+        """
+        Fetch the details of each mod once for the duration of a block.
+
+        A resolver already fetches each mod once, this shares the details
+        between the steps of a run that resolve separately, e.g. checking
+        for updates then installing them. Blocks nest, the outermost one
+        drops the details. Failed requests are not kept.
+        """
+        if self._memo is not None:
+            yield
+            return
+        self._memo = {}
+        try:
+            yield
+        finally:
+            self._memo = None
+
+    def _throttled(self, request: Callable, *args):
+        for attempt in range(1, MAX_THROTTLED_ATTEMPTS + 1):
+            if self.cancelled.is_set():
+                raise DownloadCancelledError("The client was cancelled")
+            with self.throttle:
+                try:
+                    result = request(*args)
+                except requests.HTTPError as e:
+                    status_code = e.response.status_code if e.response is not None else None
+                    if status_code not in THROTTLE_STATUS_CODES:
+                        raise
+                    if attempt == MAX_THROTTLED_ATTEMPTS:
+                        raise
+                    self.throttle.overloaded(status_code, retry_after(e.response))
+                    continue
+
+            self.throttle.succeeded()
+            return result
+
+    def get_mod_details(self, mod_name: str) -> dict:
+        """See get_mod_details, cross_check in __init__ and memoized."""
+        memo = self._memo
+        if memo is not None and mod_name in memo:
+            return memo[mod_name]
+
+        if not self.cross_check or metadata_base_url(self.metadata_base_url) == BASE_PORTAL_API_URL:
+            details = self._throttled(self._get_mod_details, mod_name)
+        else:
+            details = self._cross_checked_details(mod_name)
+        if memo is not None:
+            memo[mod_name] = details
+        return details
+
+    def _cross_checked_details(self, mod_name: str) -> dict:
+        mirror = metadata_base_url(self.metadata_base_url)
+        try:
+            details = self._throttled(self._get_mod_details, mod_name)
+        except requests.HTTPError as e:
+            # Mods published since the mirror last synced are missing altogether
+            if e.response is None or e.response.status_code != 404:
+                raise
+            details = None
+
+        try:
+            latest = self._throttled(self._get_official_latest_version, mod_name)
+        except requests.RequestException as e:
+            if details is None:
+                raise
+            self.log(f"Could not check {mod_name} with the official portal, using {mirror}: {e}\n")
+            return details
+
+        mirrored = newest_version(details.get("releases", [])) if details else None
+        if latest and (not mirrored or parse_version(latest) > parse_version(mirrored)):
+            self.log(f"{mirror} lacks {mod_name} {latest}, using the official portal.\n")
+            return self._throttled(self._get_official_details, mod_name)
+        if details is None:
+            # Known to the official portal, which lists no release of it either
+            return {"name": mod_name, "releases": []}
+
+        self.log(f"Using {mirror} for {mod_name}.\n")
+        return details
+
+    def get_mod_releases(self, mod_name: str) -> List[dict]:
+        """See get_mod_releases."""
+        return self.get_mod_details(mod_name).get("releases", [])
+
+    def list_mods(self, factorio_version: Optional[str] = None) -> List[dict]:
+        """See list_mods."""
+        return self._throttled(self._list_mods, factorio_version)
+
+    def get_release_size(self, mod_name: str, version: str) -> Optional[int]:
+        """See get_release_size."""
+        return self._throttled(self._get_release_size, mod_name, version)
+
+    def download_release(self, mod_name: str, version: str, output_path: str) -> str:
+        """See download_release."""
+        return self._throttled(self._download_release, mod_name, version, output_path)
+
+    def _get_mod_details(self, mod_name: str) -> dict:
+        return get_mod_details(
+            mod_name,
+            (self.connect_timeout, self.metadata_timeout),
+            self.metadata_base_url,
+            self.session,
+            self.cache,
+        )
+
+    def _get_official_details(self, mod_name: str) -> dict:
+        return get_mod_details(
+            mod_name,
+            (self.connect_timeout, self.metadata_timeout),
+            BASE_PORTAL_API_URL,
+            self.session,
+            self.cache,
+        )
+
+    def _get_official_latest_version(self, mod_name: str) -> Optional[str]:
+        return get_latest_version(
+            mod_name,
+            (self.connect_timeout, self.metadata_timeout),
+            BASE_PORTAL_API_URL,
+            self.session,
+        )
+
+    def _list_mods(self, factorio_version: Optional[str]) -> List[dict]:
+        return list_mods(
+            (self.connect_timeout, self.metadata_timeout),
+            self.metadata_base_url,
+            self.session,
+            factorio_version,
+        )
+
+    def _get_release_size(self, mod_name: str, version: str) -> Optional[int]:
+        return get_release_size(
+            mod_name,
+            version,
+            (self.connect_timeout, self.metadata_timeout),
+            self.storage_base_url,
+            self.session,
+        )
+
+    def _download_release(self, mod_name: str, version: str, output_path: str) -> str:
+        return download_release(
+            mod_name,
+            version,
+            output_path,
+            self.download_timeout,
+            self.storage_base_url,
+            self.session,
+            self.cancelled,
+        )
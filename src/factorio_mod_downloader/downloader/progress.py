@@ -0,0 +1,128 @@
+"""
+Progress of a download as newline-delimited JSON, for frontends not written in Python.
+
+Each line is an object with the protocol version, the event and its status,
+plus the mod and version it concerns where there is one:
+
+    {"protocol": 1, "event": "resolve", "status": "started", "mods": ["Krastorio2"]}
+    {"protocol": 1, "event": "resolve", "status": "resolved", "mod": "flib", "version": "0.14.0"}
+    {"protocol": 1, "event": "resolve", "status": "finished", "mods": 12, "failures": 0}
+    {"protocol": 1, "event": "download", "status": "started", "mod": "flib", "version": "0.14.0"}
+    {"protocol": 1, "event": "download", "status": "finished", "mod": "flib", ...}
+    {"protocol": 1, "event": "verify", "status": "passed", "mod": "flib", "version": "0.14.0"}
+    {"protocol": 1, "event": "complete", "status": "finished", "exit_code": 0, ...}
+
+Download statuses are "started", "finished", "cached" and "failed", verify
+statuses "passed" and "failed", failures carry an "error" message. A mod is
+resolved again if a later resolution pass selects another version. Fields
+are only ever added within a protocol version; renaming or removing one
+bumps PROTOCOL_VERSION.
+"""
+
+import json
+import socket
+import sys
+import threading
+from dataclasses import dataclass
+from dataclasses import field
+from enum import Enum
+from typing import Final
+from typing import Optional
+from typing import TextIO
+
+
+PROTOCOL_VERSION: Final = 1
+# Target of JsonLinesProgress.open writing to standard output
+STDOUT_TARGET: Final = "-"
+
+
+class EventKind(Enum):
+    """Stage of a download an event reports on."""
+
+    RESOLVE = "resolve"
+    DOWNLOAD = "download"
+    VERIFY = "verify"
+    COMPLETE = "complete"
+
+
+@dataclass
+class ProgressEvent:
+    """A step of a download, see the module documentation for the statuses."""
+
+    kind: EventKind
+    status: str
+    mod: Optional[str] = None
+    version: Optional[str] = None
+    # Fields specific to the event, e.g. "error" or "size_bytes"
+    data: dict = field(default_factory=dict)
+
+    def to_dict(self) -> dict:
+        """The event as a line of the protocol."""
+        line = {"protocol": PROTOCOL_VERSION, "event": self.kind.value, "status": self.status}
+        if self.mod is not None:
+            line["mod"] = self.mod
+            line["version"] = self.version
+        line.update(self.data)
+        return line
+
+
+class JsonLinesProgress:
+    """Callback writing progress events as JSON lines, safe to call from several threads."""
+
+    def __init__(self, stream: TextIO, connection: Optional[socket.socket] = None):
+        """
+        Initialize the writer.
+
+        Args:
+            stream: Text stream the lines are written to
+            connection: Socket the stream belongs to, closed along with it
+        """
+        self.stream = stream
+        self.connection = connection
+        self._lock = threading.Lock()
+
+    @classmethod
+    def open(cls, target: str = STDOUT_TARGET) -> "JsonLinesProgress":
+        """
+        Open a progress stream.
+
+        Args:
+            target: "-" for standard output, otherwise the path of a unix
+                socket a frontend listens on
+
+        Returns:
+            Writer for the target
+
+        Raises:
+            OSError: If the socket cannot be connected to
+        """
+        if target == STDOUT_TARGET:
+            return cls(sys.stdout)
+
+        connection = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        try:
+            connection.connect(target)
+        except OSError:
+            connection.close()
+            raise
+        return cls(connection.makefile("w", encoding="utf-8"), connection)
+
+    def __call__(self, event: ProgressEvent):
+        line = json.dumps(event.to_dict()) + "\n"
+        with self._lock:
+            try:
+                self.stream.write(line)
+                self.stream.flush()
+            except OSError:
+                # A frontend going away must not fail the download
+                pass
+
+    def close(self):
+        """Close the stream, unless it is standard output."""
+        if self.connection is None:
+            return
+        try:
+            self.stream.close()
+        except OSError:
+            pass
+        self.connection.close()
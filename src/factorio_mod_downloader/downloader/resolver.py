@@ -0,0 +1,1058 @@
+"""
+Resolution of mods and their dependencies against the mod portal.
+
+Versions are selected once every requirement placed on a mod is known, so two
+mods asking for different versions of a shared dependency get the highest
+version satisfying both, or a conflict naming every requester.
+"""
+
+import difflib
+import heapq
+import time
+from collections import deque
+from dataclasses import dataclass
+from dataclasses import field
+from datetime import datetime
+from datetime import timedelta
+from datetime import timezone
+from enum import Enum
+from typing import Callable
+from typing import Collection
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Set
+from typing import Tuple
+
+import requests
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.config import ResolutionStrategy
+from factorio_mod_downloader.downloader.dependencies import BUILTIN_MODS
+from factorio_mod_downloader.downloader.dependencies import DLC_MODS
+from factorio_mod_downloader.downloader.dependencies import Dependency
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+from factorio_mod_downloader.downloader.dependencies import parse_dependencies
+from factorio_mod_downloader.downloader.graph import DependencyGraph
+from factorio_mod_downloader.downloader.helpers import parse_date
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import Release
+from factorio_mod_downloader.downloader.portal import find_release
+from factorio_mod_downloader.downloader.portal import metadata_base_url
+from factorio_mod_downloader.downloader.tracing import span
+
+
+# Selecting a version changes the requirements its dependencies see, give up
+# if the selection has not settled after this many passes
+MAX_RESOLUTION_PASSES: Final = 10
+# Dependencies decided one by one, see Config.wants_optional
+OPTIONAL_KINDS: Final = (DependencyKind.OPTIONAL, DependencyKind.HIDDEN_OPTIONAL)
+# Dependencies loaded before their dependents
+LOAD_ORDER_KINDS: Final = (DependencyKind.REQUIRED,) + OPTIONAL_KINDS
+# Fields of a mod's details kept for the warnings, descriptions and changelogs
+# are dropped so large packs stay small in memory
+SUMMARY_FIELDS: Final = ("category", "tags", "downloads_count", "deprecated")
+# Names suggested for a mod the portal does not know
+MAX_SUGGESTIONS: Final = 3
+# Similarity below which a name is not worth suggesting, see difflib.SequenceMatcher.ratio
+SUGGESTION_CUTOFF: Final = 0.6
+
+
+class ResolutionError(Exception):
+    """No release of a mod can be selected."""
+
+
+class ModRemovedError(ResolutionError):
+    """The mod portal does not know a mod, it was removed or never existed."""
+
+
+class DownloadSizeError(Exception):
+    """The mods of a resolution add up to more than the size limit."""
+
+    def __init__(self, result: "ResolutionResult", total_size: int, max_total_size: int):
+        super().__init__(
+            f"Downloads total {total_size / 1024 / 1024:.1f} MB, "
+            f"over the limit of {max_total_size / 1024 / 1024:.1f} MB"
+        )
+        self.result = result
+        self.total_size = total_size
+        self.max_total_size = max_total_size
+
+
+class WarningKind(Enum):
+    """Problems of a resolved mod that do not prevent downloading it."""
+
+    # The selected release targets another Factorio version than the one resolved for
+    INCOMPATIBLE_RELEASE = "incompatible-release"
+    # The newest release for the Factorio version is older than Config.abandoned_after_days
+    ABANDONED = "abandoned"
+    # The mod's category or one of its tags is in Config.excluded_categories
+    EXCLUDED_CATEGORY = "excluded-category"
+    # The author marked the mod deprecated on the portal
+    DEPRECATED = "deprecated"
+    # The mod was requested with another case than its name on the portal, which was used instead
+    CASE_MISMATCH = "case-mismatch"
+    # The mod is in a cycle of dependencies affecting load order, see ResolutionResult.cycles
+    DEPENDENCY_CYCLE = "dependency-cycle"
+
+
+@dataclass
+class ResolutionWarning:
+    """A problem of a resolved mod, see WarningKind."""
+
+    mod: str
+    kind: WarningKind
+    message: str
+    # Downloads of the mod reported by the portal, None if unknown
+    downloads_count: Optional[int] = None
+
+    def __str__(self) -> str:
+        return f"{self.mod}: {self.message}"
+
+
+@dataclass
+class Requirement:
+    """A constraint placed on a mod by a dependent mod or by the user."""
+
+    dependency: Dependency
+    # "name version" of the mod declaring the dependency, None if requested directly
+    requester: Optional[str] = None
+
+    def __str__(self) -> str:
+        source = f"required by {self.requester}" if self.requester else "requested directly"
+        return f"{self.dependency} ({source})"
+
+
+@dataclass
+class ResolvedMod:
+    """A mod and the release selected for it."""
+
+    name: str
+    version: str
+    release: dict
+    dependencies: List[Dependency] = field(default_factory=list)
+    # Size of the release zip in bytes, filled in by check_download_size
+    size: Optional[int] = None
+
+    @property
+    def file_name(self) -> str:
+        """Name of the release zip in a mods directory."""
+        return f"{self.name}_{self.version}.zip"
+
+    @property
+    def info(self) -> Release:
+        """The selected release as a typed object."""
+        return Release.from_portal({"version": self.version, **self.release})
+
+
+@dataclass(frozen=True)
+class ReleaseChoice:
+    """Which satisfying release of a requested mod to select, rather than the newest."""
+
+    # Only releases published before this time, e.g. to recreate an earlier state of a pack
+    released_before: Optional[datetime] = None
+    # Newer satisfying releases to pass over, 1 for the release before the newest,
+    # e.g. to roll back a bad update
+    skip_newest: int = 0
+
+
+@dataclass
+class ResolutionResult:
+    """Mods selected by a resolution, dependencies before their dependents."""
+
+    mods: Dict[str, ResolvedMod] = field(default_factory=dict)
+    graph: DependencyGraph = field(default_factory=DependencyGraph)
+    errors: List[str] = field(default_factory=list)
+    # Reason of the errors concerning a single mod, by mod
+    failures: Dict[str, str] = field(default_factory=dict)
+    # Dependencies left out by the mod filters, with the mods requiring them
+    filtered: Dict[str, List[str]] = field(default_factory=dict)
+    # Resolved mods that are likely to cause trouble, see WarningKind
+    warnings: List[ResolutionWarning] = field(default_factory=list)
+    # Seconds spent loading the releases of each mod, 0 when they were already loaded
+    metadata_durations: Dict[str, float] = field(default_factory=dict)
+    # Whether each optional dependency considered was downloaded, by mod, so
+    # a run can be reproduced with the same choices
+    optional_decisions: Dict[str, bool] = field(default_factory=dict)
+    # Requested mods resolved under their name on the portal, by requested name
+    renamed: Dict[str, str] = field(default_factory=dict)
+    # Similarly named mods on the portal for the failed mods it does not know, by mod
+    suggestions: Dict[str, List[str]] = field(default_factory=dict)
+    # Cycles of dependencies affecting load order, which Factorio refuses to load, each a
+    # path from its alphabetically first mod back to it, e.g. ["a", "b", "a"]
+    cycles: List[List[str]] = field(default_factory=list)
+
+    @property
+    def total_size(self) -> int:
+        """Size of all release zips in bytes, unknown sizes count as empty."""
+        return sum(mod.size or 0 for mod in self.mods.values())
+
+    def summary(self) -> str:
+        """Number of mods and their total size, e.g. "143 mods (2.1 GB)"."""
+        return f"{len(self.mods)} mods ({format_size(self.total_size)})"
+
+
+def format_size(size: int) -> str:
+    """
+    Format a size in bytes for display.
+
+    Args:
+        size: Size in bytes
+
+    Returns:
+        Size in the largest unit keeping it at least 1, e.g. "2.1 GB"
+    """
+    value = float(size)
+    for unit in ("B", "KB", "MB"):
+        if value < 1024:
+            return f"{value:.0f} {unit}" if unit == "B" else f"{value:.1f} {unit}"
+        value /= 1024
+    return f"{value:.1f} GB"
+
+
+class Resolver:
+    """Selects releases for a set of mods and everything they depend on."""
+
+    def __init__(
+        self,
+        factorio_version: str,
+        config: Optional[Config] = None,
+        log: Callable[[str], None] = lambda info: None,
+        client: Optional[PortalClient] = None,
+        optional_filter: Optional[Callable[[str, Dependency], bool]] = None,
+        builtin_mods: Optional[Collection[str]] = None,
+        has_dlc: Optional[bool] = None,
+        include_optional: Optional[Dict[str, bool]] = None,
+        installed: Optional[Dict[str, Collection[str]]] = None,
+        release_choices: Optional[Dict[str, ReleaseChoice]] = None,
+    ):
+        """
+        Initialize the resolver.
+
+        Args:
+            factorio_version: Factorio version releases are selected for
+            config: Resolution options, defaults to Config()
+            log: Callback receiving progress messages
+            client: Source of mod metadata, defaults to the endpoints in config
+            optional_filter: Called with the requesting mod and an optional
+                dependency, returns whether to download it. It decides in place
+                of the config's optional options and is asked once per dependency.
+            builtin_mods: Mods shipped with the game, defaults to the config's
+                builtin_mods or BUILTIN_MODS
+            has_dlc: Whether the game has the Space Age DLC, releases requiring
+                it are not selected otherwise. Defaults to the config's has_dlc,
+                or True.
+            include_optional: Whether to download the optional dependencies of
+                a mod, by mod, overriding optional_filter and the config. A
+                dependency left out by one mod is still downloaded if another
+                wants it.
+            installed: Versions of each mod already in the destination, kept
+                for dependencies they satisfy, see Config.prefer_installed
+            release_choices: Releases to select for some requested mods
+                instead of their newest, by mod
+        """
+        self.factorio_version = factorio_version
+        self.config = config or Config()
+        self.log = log
+        self.client = client or PortalClient.from_config(self.config)
+        self._releases: Dict[str, List[dict]] = {}
+        self._details: Dict[str, dict] = {}
+        self._metadata_durations: Dict[str, float] = {}
+        self._release_errors: Dict[str, str] = {}
+        self._warned: Set[str] = set()
+        self._removed: Set[str] = set()
+        self.optional_filter = optional_filter
+        if builtin_mods is None:
+            builtin_mods = self.config.builtin_mods
+        self.builtin_mods = frozenset(BUILTIN_MODS if builtin_mods is None else builtin_mods)
+        if has_dlc is None:
+            has_dlc = self.config.has_dlc
+        self.has_dlc = has_dlc is not False
+        self._optional_decisions: Dict[str, bool] = {}
+        self.include_optional = dict(include_optional or {})
+        self.installed = installed or {}
+        self.release_choices = dict(release_choices or {})
+        # Downloads of the portal's mods by name, loaded when a mod is not found
+        self._portal_mods: Optional[Dict[str, int]] = None
+        self._suggestions: Dict[str, List[str]] = {}
+
+    def get_releases(self, mod_name: str) -> List[dict]:
+        """
+        Fetch the releases of a mod, once per resolver.
+
+        Args:
+            mod_name: Name of the mod
+
+        Returns:
+            Releases as returned by the portal
+
+        Raises:
+            ResolutionError: If the portal could not be reached
+            ModRemovedError: If the portal does not know the mod
+        """
+        if mod_name in self._release_errors:
+            if mod_name in self._removed:
+                raise ModRemovedError(self._release_errors[mod_name])
+            raise ResolutionError(self._release_errors[mod_name])
+
+        if mod_name not in self._releases:
+            self.log(f"Loading releases for {mod_name}.\n")
+            start = time.monotonic()
+            source = metadata_base_url(self.client.metadata_base_url)
+            try:
+                with span("metadata", mod=mod_name, source=source):
+                    details = self.client.get_mod_details(mod_name)
+                self._details[mod_name] = {
+                    key: details[key] for key in SUMMARY_FIELDS if key in details
+                }
+                self._releases[mod_name] = details.get("releases", [])
+            except requests.RequestException as e:
+                response = getattr(e, "response", None)
+                if response is not None and response.status_code == 404:
+                    # The portal answers 404 for deleted mods as for unknown ones.
+                    # Official mods, e.g. a future DLC, are not on the portal either
+                    self._release_errors[mod_name] = (
+                        "Not found on the mod portal, it was removed by its author or "
+                        "never existed. If it is shipped with the game, add it to builtin_mods"
+                    )
+                    suggestions = self.suggest_names(mod_name)
+                    self._suggestions[mod_name] = suggestions
+                    if suggestions and suggestions[0].casefold() == mod_name.casefold():
+                        self._release_errors[mod_name] = (
+                            f"Not found on the mod portal, did you mean {suggestions[0]}? "
+                            "Mod names are case sensitive"
+                        )
+                    elif suggestions:
+                        names = ", ".join(suggestions[:-1])
+                        names = f"{names} or {suggestions[-1]}" if names else suggestions[-1]
+                        self._release_errors[mod_name] = (
+                            f"Not found on the mod portal, did you mean {names}?"
+                        )
+                    self._removed.add(mod_name)
+                    raise ModRemovedError(self._release_errors[mod_name]) from e
+                self._release_errors[mod_name] = f"Could not load releases: {e}"
+                raise ResolutionError(self._release_errors[mod_name]) from e
+            finally:
+                self._metadata_durations[mod_name] = time.monotonic() - start
+
+        return self._releases[mod_name]
+
+    def _load_portal_mods(self) -> Dict[str, int]:
+        if self._portal_mods is None:
+            try:
+                mods = self.client.list_mods()
+            except requests.RequestException as e:
+                self.log(f"Could not load the mod list to suggest mod names: {e}\n")
+                mods = []
+            self._portal_mods = {mod["name"]: mod.get("downloads_count") or 0 for mod in mods}
+        return self._portal_mods
+
+    def portal_spelling(self, mod_name: str) -> Optional[str]:
+        """
+        Find the mod on the portal whose name only differs in case, e.g. for
+        Krastorio2 typed as krastorio2.
+
+        The portal's mod list is fetched the first time, which is large, so
+        only mods the portal does not know are looked up.
+
+        Args:
+            mod_name: Name the portal does not know
+
+        Returns:
+            Name of the matching mod, None if there is none or the mod list
+            could not be loaded
+        """
+        suggestions = self.suggest_names(mod_name, 1)
+        if suggestions and suggestions[0].casefold() == mod_name.casefold():
+            return suggestions[0]
+        return None
+
+    def suggest_names(self, mod_name: str, limit: int = MAX_SUGGESTIONS) -> List[str]:
+        """
+        Find mods on the portal named like one it does not know, see portal_spelling.
+
+        Args:
+            mod_name: Name the portal does not know
+            limit: Highest number of names returned
+
+        Returns:
+            A name only differing in case first, then the most similar names,
+            the most downloaded first among equally similar ones. Empty if the
+            mod list could not be loaded.
+        """
+        folded = mod_name.casefold()
+        matcher = difflib.SequenceMatcher(b=folded)
+        scored = []
+        for name, downloads_count in self._load_portal_mods().items():
+            if name == mod_name:
+                continue
+            matcher.set_seq1(name.casefold())
+            # Cheap upper bounds first, the mod list has thousands of names
+            if matcher.real_quick_ratio() < SUGGESTION_CUTOFF:
+                continue
+            if matcher.quick_ratio() < SUGGESTION_CUTOFF:
+                continue
+            ratio = 2.0 if name.casefold() == folded else matcher.ratio()
+            if ratio >= SUGGESTION_CUTOFF:
+                scored.append((-ratio, -downloads_count, name))
+        return [name for _, _, name in heapq.nsmallest(limit, scored)]
+
+    def _correct_names(self, mod_names: List[str], provided: Dict[str, dict]) -> Dict[str, str]:
+        """Portal names of the requested mods only known with another case."""
+        renamed = {}
+        for mod_name in mod_names:
+            if mod_name in self.builtin_mods or mod_name in provided:
+                continue
+            try:
+                self.get_releases(mod_name)
+            except ModRemovedError:
+                spelling = self.portal_spelling(mod_name)
+                if spelling:
+                    renamed[mod_name] = spelling
+            except ResolutionError:
+                continue
+        return renamed
+
+    def clear_cache(self):
+        """
+        Drop the releases fetched so far, e.g. once a resolution is complete.
+
+        A later resolution fetches them again, revalidating the metadata cache.
+        Errors are kept, mods the portal does not know are not asked for again.
+        """
+        self._releases.clear()
+        self._details.clear()
+
+    def select_release(self, mod_name: str, requirements: List[Requirement]) -> dict:
+        """
+        Select a release of a mod satisfying every requirement.
+
+        Only releases targeting the Factorio version are considered, unless a
+        requirement pins an exact version, or no release targets it at all and
+        the config allows incompatible releases.
+        Among the satisfying releases the config's resolution strategy picks
+        one, mods requested directly always get the newest satisfying one.
+        Dependencies keep an installed version instead if one satisfies every
+        requirement, unless the config's prefer_installed is off. Mods with a
+        release choice get the release it describes, see ReleaseChoice. With
+        the config's as_of, releases published later do not exist.
+
+        Args:
+            mod_name: Name of the mod
+            requirements: Every requirement placed on the mod
+
+        Returns:
+            Selected release
+
+        Raises:
+            ResolutionError: If no release satisfies all requirements
+        """
+        releases = self.get_releases(mod_name)
+        if not releases:
+            raise ResolutionError(f"{mod_name} has no releases on the mod portal")
+        for requirement in requirements:
+            pin = requirement.dependency
+            if pin.operator == "=" and not find_release(releases, pin.version):
+                raise ResolutionError(
+                    f"Pinned version {pin.version} of {mod_name} is not available "
+                    f"on the portal: {requirement}"
+                )
+        if self.config.as_of:
+            releases = [
+                release for release in releases if _released_before(release, self.config.as_of)
+            ]
+            if not releases:
+                raise ResolutionError(
+                    f"{mod_name} had no releases on the mod portal "
+                    f"before {self.config.as_of.isoformat()}"
+                )
+
+        pinned = any(requirement.dependency.operator == "=" for requirement in requirements)
+        constraints = ", ".join(str(requirement) for requirement in requirements)
+
+        strategy = self.config.resolution_strategy
+        requested = any(requirement.requester is None for requirement in requirements)
+        if requested:
+            strategy = ResolutionStrategy.HIGHEST
+
+        if strategy == ResolutionStrategy.PINNED_ONLY and not pinned:
+            raise ResolutionError(
+                f"{mod_name} must be pinned to an exact version, got {constraints}"
+            )
+
+        if pinned:
+            candidates = releases
+        else:
+            candidates = [
+                release
+                for release in releases
+                if release.get("info_json", {}).get("factorio_version") == self.factorio_version
+            ]
+            if not candidates and not self.config.allow_incompatible:
+                ordered = sorted(releases, key=lambda release: parse_version(release["version"]))
+                targets = ", ".join(
+                    f"{release['version']} (Factorio "
+                    f"{release.get('info_json', {}).get('factorio_version', '?')})"
+                    for release in ordered
+                )
+                raise ResolutionError(
+                    f"No release of {mod_name} targets Factorio {self.factorio_version}. "
+                    f"Available: {targets}"
+                )
+            if not candidates:
+                if mod_name not in self._warned:
+                    self._warned.add(mod_name)
+                    self.log(
+                        f"No release of {mod_name} targets Factorio {self.factorio_version}. "
+                        "Using latest version.\n"
+                    )
+                candidates = releases
+
+        if not self.has_dlc:
+            without_dlc = [release for release in candidates if not _required_dlc(release)]
+            if not without_dlc:
+                dlc_mods = ", ".join(
+                    sorted({name for release in candidates for name in _required_dlc(release)})
+                )
+                raise ResolutionError(
+                    f"Every release of {mod_name} for Factorio {self.factorio_version} "
+                    f"requires the Space Age DLC ({dlc_mods}), which the target game does not have"
+                )
+            candidates = without_dlc
+
+        satisfying = [
+            release
+            for release in candidates
+            if all(
+                requirement.dependency.is_satisfied_by(release["version"])
+                for requirement in requirements
+            )
+        ]
+        if satisfying and mod_name in self.release_choices:
+            satisfying = self._chosen_releases(
+                mod_name, satisfying, self.release_choices[mod_name], constraints
+            )
+        if satisfying and self.config.prefer_installed and not requested:
+            # Keeps the mods folder unchanged where nothing requires another version
+            installed = self.installed.get(mod_name, ())
+            satisfying = [
+                release for release in satisfying if release["version"] in installed
+            ] or satisfying
+        if satisfying:
+            select = min if strategy == ResolutionStrategy.LOWEST_COMPATIBLE else max
+            return select(satisfying, key=lambda release: parse_version(release["version"]))
+
+        available = ", ".join(
+            sorted((release["version"] for release in candidates), key=parse_version)
+        )
+        raise ResolutionError(
+            f"No release of {mod_name} satisfies {constraints}. Available: {available}"
+        )
+
+    def _chosen_releases(
+        self, mod_name: str, satisfying: List[dict], choice: ReleaseChoice, constraints: str
+    ) -> List[dict]:
+        """The satisfying releases a release choice leaves, the one to select if it skips any."""
+        if choice.released_before:
+            satisfying = [
+                release
+                for release in satisfying
+                if _released_before(release, choice.released_before)
+            ]
+            if not satisfying:
+                raise ResolutionError(
+                    f"No release of {mod_name} satisfying {constraints} was published "
+                    f"before {choice.released_before.isoformat()}"
+                )
+
+        if choice.skip_newest:
+            ordered = sorted(
+                satisfying, key=lambda release: parse_version(release["version"]), reverse=True
+            )
+            if choice.skip_newest >= len(ordered):
+                raise ResolutionError(
+                    f"Cannot skip the {choice.skip_newest} newest releases of {mod_name}, "
+                    f"only {len(ordered)} satisfy {constraints}"
+                )
+            satisfying = [ordered[choice.skip_newest]]
+        return satisfying
+
+    def _warnings(self, mod: ResolvedMod) -> List[ResolutionWarning]:
+        """Warnings about a resolved mod, see WarningKind."""
+        details = self._details.get(mod.name, {})
+        downloads_count = details.get("downloads_count")
+        warnings = []
+
+        if details.get("deprecated"):
+            warnings.append(
+                ResolutionWarning(
+                    mod.name,
+                    WarningKind.DEPRECATED,
+                    "Deprecated by its author on the mod portal",
+                    downloads_count,
+                )
+            )
+
+        excluded = [
+            category
+            for category in (details.get("category"), *details.get("tags", []))
+            if category and category in self.config.excluded_categories
+        ]
+        if excluded:
+            message = f"In excluded category {', '.join(excluded)}"
+            warnings.append(
+                ResolutionWarning(
+                    mod.name, WarningKind.EXCLUDED_CATEGORY, message, downloads_count
+                )
+            )
+
+        target = mod.release.get("info_json", {}).get("factorio_version")
+        if target and target != self.factorio_version:
+            message = f"{mod.version} targets Factorio {target}, not {self.factorio_version}"
+            warnings.append(
+                ResolutionWarning(
+                    mod.name, WarningKind.INCOMPATIBLE_RELEASE, message, downloads_count
+                )
+            )
+            return warnings
+
+        if self.config.abandoned_after_days is None:
+            return warnings
+        release_dates = [
+            _parse_release_date(release.get("released_at"))
+            for release in self._releases.get(mod.name, [])
+            if release.get("info_json", {}).get("factorio_version") == self.factorio_version
+        ]
+        release_dates = [released for released in release_dates if released]
+        if not release_dates:
+            return warnings
+
+        latest = max(release_dates)
+        if datetime.now(timezone.utc) - latest <= timedelta(days=self.config.abandoned_after_days):
+            return warnings
+        message = f"No release for Factorio {self.factorio_version} since {latest.date()}"
+        if downloads_count is not None:
+            message += f", {downloads_count} downloads"
+        warnings.append(
+            ResolutionWarning(mod.name, WarningKind.ABANDONED, message, downloads_count)
+        )
+        return warnings
+
+    def _release_dependencies(self, mod_name: str, release: dict) -> List[Dependency]:
+        dependencies = []
+
+        for dependency in parse_dependencies(
+            release.get("info_json", {}).get("dependencies", []),
+            lambda invalid: self.log(f"Ignoring invalid dependency of {mod_name}: {invalid}\n"),
+        ):
+            if dependency.name in self.builtin_mods:
+                continue
+            if dependency.kind in OPTIONAL_KINDS:
+                if not self._wants_optional(mod_name, dependency):
+                    continue
+            elif not self.config.wants(dependency.kind):
+                continue
+
+            dependencies.append(dependency)
+
+        return dependencies
+
+    def _wants_optional(self, mod_name: str, dependency: Dependency) -> bool:
+        if mod_name in self.include_optional:
+            if self.include_optional[mod_name]:
+                self._optional_decisions[dependency.name] = True
+            return self.include_optional[mod_name]
+        if dependency.name not in self._optional_decisions:
+            if self.optional_filter:
+                wanted = bool(self.optional_filter(mod_name, dependency))
+            else:
+                wanted = self.config.wants_optional(dependency.name, dependency.kind)
+            self._optional_decisions[dependency.name] = wanted
+        return self._optional_decisions[dependency.name]
+
+    def _try_select(
+        self, mod_name: str, requirements: List[Requirement], failures: Dict[str, str]
+    ) -> Optional[dict]:
+        try:
+            release = self.select_release(mod_name, requirements)
+        except ResolutionError as e:
+            failures[mod_name] = str(e)
+            return None
+
+        failures.pop(mod_name, None)
+        return release
+
+    def _walk(
+        self,
+        mod_names: List[str],
+        pins: Dict[str, str],
+        preferred: Dict[str, Optional[dict]],
+        failures: Dict[str, str],
+        on_resolved: Callable[[ResolvedMod], None],
+    ) -> Tuple[ResolutionResult, Dict[str, List[Requirement]]]:
+        """
+        Walk the dependency graph from the requested mods.
+
+        Mods selected by an earlier pass keep their release, new ones are
+        selected from the requirements seen so far. There is no depth limit,
+        dependencies are followed however deep they are, each mod once.
+
+        Returns:
+            The mods reached and every requirement placed on each of them
+        """
+        result = ResolutionResult()
+        requirements: Dict[str, List[Requirement]] = {}
+        # Shortest distance of each mod from a requested one
+        depths: Dict[str, int] = {}
+        queue = deque()
+
+        for mod_name in mod_names:
+            if mod_name in self.builtin_mods:
+                self.log(f"Skipping {mod_name}, it is shipped with the game.\n")
+                continue
+
+            result.graph.add_root(mod_name)
+            if mod_name not in requirements:
+                operator = "=" if mod_name in pins else None
+                root = Dependency(mod_name, DependencyKind.REQUIRED, operator, pins.get(mod_name))
+                requirements[mod_name] = [Requirement(root)]
+                depths[mod_name] = 0
+                queue.append(mod_name)
+
+        while queue:
+            mod_name = queue.popleft()
+
+            if mod_name in preferred:
+                release = preferred[mod_name]
+            else:
+                with span("select", mod=mod_name, depth=depths[mod_name]) as attributes:
+                    release = self._try_select(mod_name, requirements[mod_name], failures)
+                    attributes["version"] = release["version"] if release else None
+            if not release:
+                continue
+
+            version = release["version"]
+            dependencies = self._release_dependencies(mod_name, release)
+            result.mods[mod_name] = ResolvedMod(mod_name, version, release, dependencies)
+            result.graph.add_mod(mod_name, version)
+            on_resolved(result.mods[mod_name])
+
+            for dependency in dependencies:
+                if not self.config.allows(dependency.name):
+                    result.filtered.setdefault(dependency.name, []).append(mod_name)
+                    continue
+
+                result.graph.add_edge(mod_name, dependency.name, dependency.kind)
+                if dependency.name not in requirements:
+                    requirements[dependency.name] = []
+                    depths[dependency.name] = depths[mod_name] + 1
+                    queue.append(dependency.name)
+                requirements[dependency.name].append(
+                    Requirement(dependency, f"{mod_name} {version}")
+                )
+
+        return result, requirements
+
+    def resolve(
+        self,
+        mod_names: List[str],
+        pins: Optional[Dict[str, str]] = None,
+        provided: Optional[Dict[str, dict]] = None,
+        on_resolved: Optional[Callable[[ResolvedMod], None]] = None,
+    ) -> ResolutionResult:
+        """
+        Resolve mods and their dependencies.
+
+        Every mod gets a release satisfying the requirements of all mods
+        depending on it, the highest one unless configured otherwise. Mods for
+        which no such release exists are left out and reported in the result's
+        errors together with their requesters.
+
+        Dependencies rejected by the config's mod filters are not followed, so
+        mods only they depend on are left out as well.
+
+        Requested mods the portal only knows with another case, e.g.
+        krastorio2 for Krastorio2, are resolved under the portal's name with
+        a warning, see the result's renamed. Dependencies are named by their
+        authors, a dependency with the wrong case fails with a suggestion.
+
+        Args:
+            mod_names: Mods requested directly
+            pins: Exact versions some of the requested mods are pinned to,
+                instead of their newest release
+            provided: Releases of requested mods obtained elsewhere than the
+                portal, they are used as is and only their dependencies resolved
+            on_resolved: Called with each mod as soon as a release is selected
+                for it, while the rest of the graph is still being walked. A
+                mod is passed again if a later pass selects another release for
+                it; the returned result has the final selections.
+
+        Returns:
+            Resolved mods, their dependency graph, filtered mods and resolution errors
+        """
+        pins = pins or {}
+        provided = provided or {}
+        renamed = self._correct_names(mod_names, provided)
+        mod_names = [renamed.get(mod_name, mod_name) for mod_name in mod_names]
+        pins = {renamed.get(mod_name, mod_name): version for mod_name, version in pins.items()}
+        for mod_name, spelling in renamed.items():
+            if mod_name in self.include_optional:
+                self.include_optional[spelling] = self.include_optional.pop(mod_name)
+            if mod_name in self.release_choices:
+                self.release_choices[spelling] = self.release_choices.pop(mod_name)
+        preferred: Dict[str, Optional[dict]] = dict(provided)
+        failures: Dict[str, str] = {}
+        streamed: Dict[str, str] = {}
+
+        def stream(mod: ResolvedMod):
+            if on_resolved and streamed.get(mod.name) != mod.version:
+                streamed[mod.name] = mod.version
+                on_resolved(mod)
+
+        for _ in range(MAX_RESOLUTION_PASSES):
+            result, requirements = self._walk(mod_names, pins, preferred, failures, stream)
+
+            # Reselect with the complete requirements, another pass is needed
+            # whenever this changes a selection
+            settled = True
+            for mod_name, mod_requirements in requirements.items():
+                if mod_name in provided:
+                    continue
+                release = self._try_select(mod_name, mod_requirements, failures)
+                current = result.mods.get(mod_name)
+                if (release and release["version"]) != (current and current.version):
+                    settled = False
+                preferred[mod_name] = release
+
+            if settled:
+                break
+        else:
+            result.errors.append(
+                f"Dependency resolution did not settle after {MAX_RESOLUTION_PASSES} passes"
+            )
+
+        for mod_name in sorted(requirements):
+            if mod_name not in result.mods and mod_name in failures:
+                result.failures[mod_name] = failures[mod_name]
+                result.errors.append(f"{mod_name}: {failures[mod_name]}")
+                if self._suggestions.get(mod_name):
+                    result.suggestions[mod_name] = self._suggestions[mod_name]
+
+        for mod_name, release in sorted(provided.items()):
+            unsatisfied = ", ".join(
+                str(requirement)
+                for requirement in requirements.get(mod_name, [])
+                if not requirement.dependency.is_satisfied_by(release["version"])
+            )
+            if unsatisfied:
+                reason = f"Provided version {release['version']} does not satisfy {unsatisfied}"
+                result.failures[mod_name] = reason
+                result.errors.append(f"{mod_name}: {reason}")
+
+        result.mods = {mod_name: result.mods[mod_name] for mod_name in _dependency_order(result)}
+        result.optional_decisions = dict(sorted(self._optional_decisions.items()))
+        result.metadata_durations = {
+            mod_name: self._metadata_durations.get(mod_name, 0.0) for mod_name in requirements
+        }
+
+        result.renamed = renamed
+        result.warnings.extend(
+            ResolutionWarning(
+                spelling,
+                WarningKind.CASE_MISMATCH,
+                f"requested as {mod_name}, mod names are case sensitive",
+            )
+            for mod_name, spelling in renamed.items()
+        )
+        for mod in result.mods.values():
+            result.warnings.extend(self._warnings(mod))
+        result.cycles = _dependency_cycles(result)
+        result.warnings.extend(
+            ResolutionWarning(
+                cycle[0],
+                WarningKind.DEPENDENCY_CYCLE,
+                f"depends on itself through {' -> '.join(cycle)}",
+            )
+            for cycle in result.cycles
+        )
+        for warning in result.warnings:
+            if warning.kind == WarningKind.ABANDONED:
+                self.log(f"Warning: {warning.mod} may be abandoned. {warning.message}.\n")
+            elif warning.kind == WarningKind.EXCLUDED_CATEGORY:
+                self.log(f"Warning: {warning.mod} is {warning.message.lower()}.\n")
+            elif warning.kind == WarningKind.DEPRECATED:
+                self.log(f"Warning: {warning.mod} is deprecated, look for a replacement.\n")
+            elif warning.kind == WarningKind.CASE_MISMATCH:
+                self.log(f"Warning: using {warning.mod}, {warning.message}.\n")
+            elif warning.kind == WarningKind.DEPENDENCY_CYCLE:
+                self.log(
+                    f"Warning: {warning.mod} {warning.message}, Factorio will refuse to load "
+                    "it. Report the cycle to the authors of these mods.\n"
+                )
+
+        for mod_name, requesters in result.filtered.items():
+            required_by = ", ".join(requesters)
+            self.log(f"Skipping filtered mod {mod_name} required by {required_by}.\n")
+
+        return result
+
+
+def _required_dlc(release: dict) -> List[str]:
+    """DLC mods a release cannot load without."""
+    return [
+        dependency.name
+        for dependency in parse_dependencies(release.get("info_json", {}).get("dependencies", []))
+        if dependency.is_required and dependency.name in DLC_MODS
+    ]
+
+
+def _parse_release_date(released_at: Optional[str]) -> Optional[datetime]:
+    """Parse the portal's released_at, e.g. "2024-10-21T12:00:00.000000Z", None if invalid."""
+    try:
+        return parse_date(released_at or "")
+    except ValueError:
+        return None
+
+
+def _released_before(release: dict, cutoff: datetime) -> bool:
+    """Whether a release was published before a time, releases of unknown date never were."""
+    released = _parse_release_date(release.get("released_at"))
+    return released is not None and released < cutoff
+
+
+def _dependency_order(result: ResolutionResult) -> List[str]:
+    """
+    Order resolved mods so that dependencies come before their dependents.
+
+    Ties are broken alphabetically, so the order only depends on the mods and
+    their dependencies, not on the order they were reached in.
+    """
+    dependents: Dict[str, List[str]] = {mod_name: [] for mod_name in result.mods}
+    waiting_for: Dict[str, int] = {mod_name: 0 for mod_name in result.mods}
+
+    for mod in result.mods.values():
+        for dependency_name in {dependency.name for dependency in mod.dependencies}:
+            if dependency_name in result.mods and dependency_name != mod.name:
+                dependents[dependency_name].append(mod.name)
+                waiting_for[mod.name] += 1
+
+    ready = [mod_name for mod_name, count in waiting_for.items() if not count]
+    heapq.heapify(ready)
+    order = []
+    while ready:
+        mod_name = heapq.heappop(ready)
+        order.append(mod_name)
+        for dependent in dependents[mod_name]:
+            waiting_for[dependent] -= 1
+            if not waiting_for[dependent]:
+                heapq.heappush(ready, dependent)
+
+    # Mods depending on each other in a cycle follow alphabetically
+    order.extend(sorted(mod_name for mod_name in result.mods if mod_name not in order))
+    return order
+
+
+def _dependency_cycles(result: ResolutionResult) -> List[List[str]]:
+    """
+    Find the cycles of dependencies affecting load order among resolved mods.
+
+    "~" dependencies do not affect load order, cycles through them are fine.
+    Each group of mods depending on each other is reported once, by the
+    shortest cycle from its alphabetically first mod.
+    """
+    children: Dict[str, List[str]] = {
+        mod.name: sorted(
+            {
+                dependency.name
+                for dependency in mod.dependencies
+                if dependency.name in result.mods and dependency.kind in LOAD_ORDER_KINDS
+            }
+        )
+        for mod in result.mods.values()
+    }
+
+    def reachable(start: str) -> Set[str]:
+        seen = {start}
+        stack = [start]
+        while stack:
+            for child in children[stack.pop()]:
+                if child not in seen:
+                    seen.add(child)
+                    stack.append(child)
+        return seen
+
+    cycles = []
+    in_cycle: Set[str] = set()
+    for mod_name in sorted(children):
+        if mod_name in in_cycle:
+            continue
+        # Shortest path back to the mod, through the mods it reaches
+        previous: Dict[str, str] = {}
+        queue = deque([mod_name])
+        while queue and mod_name not in previous:
+            current = queue.popleft()
+            for child in children[current]:
+                if child not in previous:
+                    previous[child] = current
+                    queue.append(child)
+        if mod_name not in previous:
+            continue
+
+        cycle = [mod_name]
+        while len(cycle) == 1 or cycle[-1] != mod_name:
+            cycle.append(previous[cycle[-1]])
+        cycles.append(cycle[::-1])
+        # Mods reaching each other belong to the same group
+        in_cycle.update(other for other in reachable(mod_name) if mod_name in reachable(other))
+    return cycles
+
+
+def fetch_release_sizes(result: ResolutionResult, client: Optional[PortalClient] = None) -> int:
+    """
+    Fill in the size of every resolved mod whose size is not known yet.
+
+    Args:
+        result: Resolution to complete
+        client: Source of release sizes, defaults to the default endpoints
+
+    Returns:
+        Total size of the downloads in bytes, releases whose size is unknown count as empty
+
+    Raises:
+        requests.RequestException: If the storage could not be reached
+    """
+    client = client or PortalClient()
+    for mod in result.mods.values():
+        if mod.size is None:
+            mod.size = client.get_release_size(mod.name, mod.version)
+
+    return result.total_size
+
+
+def check_download_size(
+    result: ResolutionResult, max_total_size: int, client: Optional[PortalClient] = None
+) -> int:
+    """
+    Make sure the resolved mods fit within a size limit before downloading.
+
+    Fills in the size of every resolved mod, see fetch_release_sizes.
+
+    Args:
+        result: Resolution to check
+        max_total_size: Limit in bytes
+        client: Source of release sizes, defaults to the default endpoints
+
+    Returns:
+        Total size of the downloads in bytes
+
+    Raises:
+        DownloadSizeError: If the downloads exceed the limit, carrying the resolution
+        requests.RequestException: If the storage could not be reached
+    """
+    total_size = fetch_release_sizes(result, client)
+    if total_size > max_total_size:
+        raise DownloadSizeError(result, total_size, max_total_size)
+
+    return total_size
@@ -0,0 +1,142 @@
+"""
+Progress of a batch download kept in its destination, so an interrupted run can resume.
+
+The first line of `.fmd-resume.jsonl` holds the requested mods and their
+resolution, every following line the entry of a mod whose download
+completed. Lines are appended as downloads complete, so a run stopped at
+any point, by Ctrl+C, a crash or a reboot, leaves the earlier lines intact.
+Resuming skips the resolution, keeping the versions selected back then, and
+the mods already downloaded.
+"""
+
+import json
+import os
+import threading
+from pathlib import Path
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.graph import DependencyGraph
+from factorio_mod_downloader.downloader.resolver import ResolutionResult
+from factorio_mod_downloader.downloader.resolver import ResolvedMod
+
+
+RESUME_FILE: Final = ".fmd-resume.jsonl"
+
+
+def _resolution_to_dict(resolution: ResolutionResult) -> dict:
+    return {
+        "mods": [
+            {"name": mod.name, "version": mod.version, "release": mod.release, "size": mod.size}
+            for mod in resolution.mods.values()
+        ],
+        "graph": resolution.graph.to_dict(),
+        "errors": list(resolution.errors),
+        "failures": dict(resolution.failures),
+        "filtered": dict(resolution.filtered),
+    }
+
+
+def _resolution_from_dict(data: dict) -> ResolutionResult:
+    mods = {}
+    for entry in data["mods"]:
+        mod = ResolvedMod(entry["name"], entry["version"], entry["release"], size=entry["size"])
+        mod.dependencies = mod.info.dependencies
+        mods[mod.name] = mod
+    return ResolutionResult(
+        mods=mods,
+        graph=DependencyGraph.from_dict(data["graph"]),
+        errors=list(data["errors"]),
+        failures=dict(data["failures"]),
+        filtered=dict(data["filtered"]),
+    )
+
+
+class ResumeState:
+    """Plan and completed downloads of a batch run, safe to share between threads."""
+
+    def __init__(
+        self,
+        output_path: str,
+        requested: List[str],
+        resolution: ResolutionResult,
+        completed: Optional[Dict[str, dict]] = None,
+    ):
+        """
+        Initialize the state of a run.
+
+        Args:
+            output_path: Destination of the run
+            requested: Mods given to the run, a resumed run must be given the same
+            resolution: Mods selected for the run
+            completed: Entries of the mods already downloaded, by mod name
+        """
+        self.path = Path(output_path) / RESUME_FILE
+        self.requested = requested
+        self.resolution = resolution
+        self.completed: Dict[str, dict] = completed or {}
+        self._lock = threading.Lock()
+
+    @classmethod
+    def load(cls, output_path: str) -> Optional["ResumeState"]:
+        """
+        Load the state an interrupted run left in its destination.
+
+        Args:
+            output_path: Destination of the run
+
+        Returns:
+            The state, None if no run was interrupted or its state is unreadable
+        """
+        path = Path(output_path) / RESUME_FILE
+        try:
+            with open(path, encoding="utf-8") as file:
+                lines = file.read().splitlines()
+            plan = json.loads(lines[0])
+            resolution = _resolution_from_dict(plan["resolution"])
+        except (OSError, IndexError, KeyError, TypeError, ValueError):
+            return None
+
+        completed = {}
+        for line in lines[1:]:
+            try:
+                entry = json.loads(line)
+            except ValueError:
+                # The last line can be cut short by the interruption
+                break
+            completed[entry["name"]] = entry
+        return cls(output_path, plan["requested"], resolution, completed)
+
+    def save(self):
+        """Write the plan and the completed downloads, replacing an earlier state."""
+        lines = [
+            json.dumps(
+                {"requested": self.requested, "resolution": _resolution_to_dict(self.resolution)}
+            )
+        ]
+        with self._lock:
+            lines.extend(json.dumps(entry) for entry in self.completed.values())
+
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        temp_path = self.path.with_name(f"{RESUME_FILE}.{os.getpid()}.tmp")
+        with open(temp_path, "w", encoding="utf-8") as file:
+            file.write("\n".join(lines) + "\n")
+        os.replace(temp_path, self.path)
+
+    def complete(self, entry: dict):
+        """
+        Record a completed download.
+
+        Args:
+            entry: Entry of the mod, with at least its "name"
+        """
+        with self._lock:
+            self.completed[entry["name"]] = entry
+            with open(self.path, "a", encoding="utf-8") as file:
+                file.write(json.dumps(entry) + "\n")
+
+    def remove(self):
+        """Forget the run, once there is nothing left to resume."""
+        self.path.unlink(missing_ok=True)
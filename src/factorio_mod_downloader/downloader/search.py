@@ -0,0 +1,134 @@
+"""
+Searching the mod portal and summarising mods for display.
+"""
+
+from dataclasses import dataclass
+from dataclasses import field
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import Release
+
+
+@dataclass
+class ModInfo:
+    """A mod as listed on the portal."""
+
+    name: str
+    title: str = ""
+    owner: str = ""
+    summary: str = ""
+    downloads_count: int = 0
+    # Portal category, e.g. "content" or "cheats", None if uncategorised
+    category: Optional[str] = None
+    tags: List[str] = field(default_factory=list)
+    # Path of the thumbnail on the asset server, see thumbnails.py
+    thumbnail: Optional[str] = None
+    # Marked deprecated by its author, still downloadable but no longer maintained
+    deprecated: bool = False
+    # Only filled in from a mod's `/full` response, mod list entries lack them
+    releases: List[Release] = field(default_factory=list)
+
+    @classmethod
+    def from_portal(cls, data: dict) -> "ModInfo":
+        """
+        Build the summary of a mod from a portal response.
+
+        Args:
+            data: Entry of the mod list, or a mod's `/full` response
+
+        Returns:
+            Summary of the mod
+        """
+        return cls(
+            name=data["name"],
+            title=data.get("title") or "",
+            owner=data.get("owner") or "",
+            summary=data.get("summary") or "",
+            downloads_count=data.get("downloads_count") or 0,
+            category=data.get("category") or None,
+            tags=list(data.get("tags") or []),
+            thumbnail=data.get("thumbnail"),
+            deprecated=bool(data.get("deprecated")),
+            releases=[Release.from_portal(release) for release in data.get("releases") or []],
+        )
+
+    def latest_release(self, factorio_version: Optional[str] = None) -> Optional[Release]:
+        """
+        Find the newest release of the mod.
+
+        Args:
+            factorio_version: Only releases targeting this version, "major.minor"
+
+        Returns:
+            The newest release, None if there is none
+        """
+        releases = [
+            release
+            for release in self.releases
+            if factorio_version is None or release.factorio_version == factorio_version
+        ]
+        if not releases:
+            return None
+        return max(releases, key=lambda release: parse_version(release.version))
+
+
+def get_mod_info(mod_name: str, client: Optional[PortalClient] = None) -> ModInfo:
+    """
+    Look up the summary of a mod.
+
+    Args:
+        mod_name: Name of the mod
+        client: Source of mod metadata, defaults to the default endpoints
+
+    Returns:
+        Summary of the mod
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    client = client or PortalClient()
+    return ModInfo.from_portal({"name": mod_name, **client.get_mod_details(mod_name)})
+
+
+def search_mods(
+    query: str = "",
+    category: Optional[str] = None,
+    tag: Optional[str] = None,
+    factorio_version: Optional[str] = None,
+    client: Optional[PortalClient] = None,
+) -> List[ModInfo]:
+    """
+    Search the mods of the portal.
+
+    The portal has no full text search, its mod list is filtered locally.
+
+    Args:
+        query: Text the name or title must contain, case insensitive
+        category: Only mods of this category, e.g. "content"
+        tag: Only mods with this tag, e.g. "logistics"
+        factorio_version: Only mods with a release for this version
+        client: Client whose session and endpoints are used
+
+    Returns:
+        Matching mods, most downloaded first
+
+    Raises:
+        requests.RequestException: If the portal could not be reached
+    """
+    client = client or PortalClient()
+    query = query.lower()
+    mods = []
+    for data in client.list_mods(factorio_version):
+        mod = ModInfo.from_portal(data)
+        if query and query not in mod.name.lower() and query not in mod.title.lower():
+            continue
+        if category and mod.category != category:
+            continue
+        if tag and tag not in mod.tags:
+            continue
+        mods.append(mod)
+
+    return sorted(mods, key=lambda mod: (-mod.downloads_count, mod.name))
@@ -0,0 +1,212 @@
+"""
+Mods taken from a direct download link, a local zip or a GitHub repository
+instead of the mod portal.
+
+Such a mod is not resolved, its release is whatever the zip contains. Its
+info.json still provides the dependencies resolved from the portal.
+"""
+
+import hashlib
+import os
+import re
+import shutil
+import zipfile
+from pathlib import Path
+from typing import Final
+from typing import List
+from urllib.parse import urlparse
+
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import check_content_length
+from factorio_mod_downloader.game.installed import read_mod_info
+from factorio_mod_downloader.game.installed import sha1_file
+
+
+GITHUB_API_URL: Final = "https://api.github.com"
+# Token raising GitHub's rate limit of anonymous requests, unless the client has one
+GITHUB_TOKEN_ENV: Final = "GITHUB_TOKEN"
+# Characters the portal allows in mod names, a zip's info.json may claim anything
+MOD_NAME_PATTERN: Final = re.compile(r"[A-Za-z0-9_\- ]+")
+RELEASE_VERSION_PATTERN: Final = re.compile(r"\d+\.\d+\.\d+")
+
+
+def is_zip_source(mod: str) -> bool:
+    """
+    Check whether a mod entry is a direct link or path to a zip.
+
+    Args:
+        mod: Mod entry as given by the user
+
+    Returns:
+        True for http(s) URLs and local paths ending in .zip
+    """
+    parsed = urlparse(mod)
+    if parsed.scheme in ("http", "https"):
+        return parsed.path.lower().endswith(".zip")
+    return mod.lower().endswith(".zip")
+
+
+def _download_zip(url: str, temp_path: str, client: PortalClient):
+    with client.session.get(url, stream=True, timeout=client.download_timeout) as response:
+        response.raise_for_status()
+        bytes_written = 0
+        with open(temp_path, "wb") as file:
+            for chunk in response.iter_content(chunk_size=8192):
+                file.write(chunk)
+                bytes_written += len(chunk)
+        check_content_length(response, bytes_written)
+
+
+def fetch_zip_source(source: str, output_path: str, client: PortalClient) -> dict:
+    """
+    Place the zip of a direct link or local path in the output directory.
+
+    The zip is saved as `name_version.zip` after its info.json, whatever it
+    was called at its source.
+
+    Args:
+        source: http(s) URL or local path of the zip
+        output_path: Directory to save the zip in
+        client: Client whose session downloads the zip
+
+    Returns:
+        Release in the format of the portal, with the zip's info.json, sha1
+        and the source it came from
+
+    Raises:
+        requests.RequestException: If the zip could not be downloaded
+        zipfile.BadZipFile: If the file is not a zip archive
+        ValueError: If the zip has no info.json naming the mod and its version, or
+            the name or version is not one a mod can have
+    """
+    os.makedirs(output_path, exist_ok=True)
+    is_url = urlparse(source).scheme in ("http", "https")
+    if is_url:
+        # Named after the URL, so concurrent runs fetching different zips never clash
+        temp_name = f".{hashlib.sha1(source.encode()).hexdigest()}.zip.part"
+        zip_path = os.path.join(output_path, temp_name)
+        _download_zip(source, zip_path, client)
+    else:
+        zip_path = source
+
+    try:
+        info_json = read_mod_info(zip_path)
+        if not info_json.get("name") or not info_json.get("version"):
+            raise ValueError(f"info.json of {source} does not name the mod and its version")
+        # Both end up in a path, a name like ../x would escape the output directory
+        if not MOD_NAME_PATTERN.fullmatch(str(info_json["name"])):
+            raise ValueError(
+                f"info.json of {source} has an invalid mod name: {info_json['name']!r}"
+            )
+        if not RELEASE_VERSION_PATTERN.fullmatch(str(info_json["version"])):
+            raise ValueError(
+                f"info.json of {source} has an invalid version: {info_json['version']!r}"
+            )
+
+        file_name = f"{info_json['name']}_{info_json['version']}.zip"
+        file_path = os.path.join(output_path, file_name)
+        if is_url:
+            os.replace(zip_path, file_path)
+        elif Path(zip_path).resolve() != Path(file_path).resolve():
+            shutil.copyfile(zip_path, file_path)
+    finally:
+        if is_url and os.path.exists(zip_path):
+            os.remove(zip_path)
+
+    return {
+        "version": info_json["version"],
+        "file_name": file_name,
+        "info_json": info_json,
+        "sha1": sha1_file(file_path),
+        "source": source,
+    }
+
+
+def is_github_source(mod: str) -> bool:
+    """
+    Check whether a mod entry is a GitHub repository.
+
+    Args:
+        mod: Mod entry as given by the user
+
+    Returns:
+        True for `github.com/owner/repo` URLs, optionally followed by
+        `/releases/tag/<tag>`
+    """
+    parsed = urlparse(mod if "://" in mod else f"https://{mod}")
+    parts = [part for part in parsed.path.split("/") if part]
+    return parsed.netloc.lower() in ("github.com", "www.github.com") and (
+        len(parts) == 2 or (len(parts) == 5 and parts[2:4] == ["releases", "tag"])
+    )
+
+
+def github_release_zips(url: str, client: PortalClient) -> List[str]:
+    """
+    List the zips of the latest release of a GitHub repository.
+
+    Args:
+        url: Repository URL, or the URL of a specific release
+        client: Client whose session queries the GitHub API
+
+    Returns:
+        Download URLs of the zip assets, followed by the release's source archive
+
+    Raises:
+        requests.RequestException: If the release could not be looked up
+    """
+    parsed = urlparse(url if "://" in url else f"https://{url}")
+    parts = [part for part in parsed.path.split("/") if part]
+    owner, repo = parts[0], parts[1].removesuffix(".git")
+    release = f"tags/{parts[4]}" if len(parts) == 5 else "latest"
+
+    headers = {"Accept": "application/vnd.github+json"}
+    token = client.github_token or os.environ.get(GITHUB_TOKEN_ENV)
+    if token:
+        headers["Authorization"] = f"Bearer {token}"
+
+    response = client.session.get(
+        f"{GITHUB_API_URL}/repos/{owner}/{repo}/releases/{release}",
+        headers=headers,
+        timeout=(client.connect_timeout, client.metadata_timeout),
+    )
+    response.raise_for_status()
+    data = response.json()
+
+    zips = [
+        asset["browser_download_url"]
+        for asset in data.get("assets", [])
+        if asset.get("name", "").lower().endswith(".zip")
+    ]
+    # Repositories holding the mod at their root work from the source archive
+    if data.get("zipball_url"):
+        zips.append(data["zipball_url"])
+    return zips
+
+
+def fetch_github_source(url: str, output_path: str, client: PortalClient) -> dict:
+    """
+    Place the mod zip of a GitHub release in the output directory.
+
+    The first zip of the release with an info.json is used, see fetch_zip_source.
+
+    Args:
+        url: Repository URL, or the URL of a specific release
+        output_path: Directory to save the zip in
+        client: Client whose session downloads the zip
+
+    Returns:
+        Release in the format of the portal, see fetch_zip_source
+
+    Raises:
+        requests.RequestException: If the release could not be looked up
+        ValueError: If no zip of the release is a mod
+    """
+    for zip_url in github_release_zips(url, client):
+        try:
+            release = fetch_zip_source(zip_url, output_path, client)
+        except (zipfile.BadZipFile, ValueError):
+            continue
+        release["source"] = url
+        return release
+
+    raise ValueError(f"No zip of the release of {url} contains a mod")
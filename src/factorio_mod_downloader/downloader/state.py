@@ -0,0 +1,88 @@
+"""
+Checksums of the mods in an output directory, kept between runs.
+
+Hashing every zip of a large pack on each run is slow, so the sha1 of each
+file is recorded in `.fmd-state.json` with the size and modification time it
+had. A file that still has both is not hashed again.
+"""
+
+import json
+import os
+import threading
+from pathlib import Path
+from typing import Dict
+from typing import Final
+from typing import Optional
+
+from factorio_mod_downloader.game.installed import sha1_file
+
+
+STATE_FILE: Final = ".fmd-state.json"
+
+
+class InstallState:
+    """Recorded checksums of the files of an output directory, safe to share between threads."""
+
+    def __init__(self, output_path: str):
+        """
+        Load the state of an output directory.
+
+        Args:
+            output_path: Output directory, a missing or unreadable state starts empty
+        """
+        self.path = Path(output_path) / STATE_FILE
+        self._lock = threading.Lock()
+        try:
+            with open(self.path, "r", encoding="utf-8") as file:
+                self.files: Dict[str, dict] = json.load(file).get("files", {})
+        except (OSError, ValueError):
+            self.files = {}
+
+    def sha1(
+        self, file_path: str, name: Optional[str] = None, version: Optional[str] = None
+    ) -> str:
+        """
+        Get the sha1 of a file, hashing it only if it changed since it was recorded.
+
+        Args:
+            file_path: File in the output directory
+            name: Mod the file holds, recorded along with the checksum
+            version: Version of the mod
+
+        Returns:
+            Hex encoded sha1 digest
+        """
+        stat = os.stat(file_path)
+        file_name = os.path.basename(file_path)
+        with self._lock:
+            entry = self.files.get(file_name)
+        if entry and entry["size"] == stat.st_size and entry["mtime"] == stat.st_mtime_ns:
+            return entry["sha1"]
+
+        sha1 = sha1_file(file_path)
+        with self._lock:
+            self.files[file_name] = {
+                "name": name,
+                "version": version,
+                "sha1": sha1,
+                "size": stat.st_size,
+                "mtime": stat.st_mtime_ns,
+            }
+        return sha1
+
+    def save(self):
+        """Write the state, forgetting files that no longer exist."""
+        with self._lock:
+            self.files = {
+                file_name: entry
+                for file_name, entry in self.files.items()
+                if (self.path.parent / file_name).is_file()
+            }
+            data = {"files": self.files}
+
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        temp_path = self.path.with_name(f"{STATE_FILE}.{os.getpid()}.tmp")
+        with open(temp_path, "w", encoding="utf-8") as file:
+            json.dump(data, file, indent=2, sort_keys=True)
+            file.write("\n")
+        os.replace(temp_path, self.path)
@@ -0,0 +1,74 @@
+"""
+Statistics of a resolution, to find what makes a modpack large before publishing it.
+"""
+
+from dataclasses import dataclass
+from dataclasses import field
+from typing import Dict
+from typing import List
+from typing import Tuple
+
+from factorio_mod_downloader.downloader.resolver import ResolutionResult
+from factorio_mod_downloader.downloader.resolver import format_size
+
+
+@dataclass
+class ResolutionStatistics:
+    """Size and shape of the dependency tree of a resolution."""
+
+    # Number of resolved mods each requested mod pulls in, itself included
+    closure_sizes: Dict[str, int] = field(default_factory=dict)
+    # Dependencies on the longest of the shortest chains from a requested mod to a resolved mod
+    max_depth: int = 0
+    # Mods no requested mod reaches through required dependencies alone
+    optional_only: List[str] = field(default_factory=list)
+    # Largest release zips, largest first, as (mod name, size in bytes)
+    largest: List[Tuple[str, int]] = field(default_factory=list)
+
+    def format(self) -> str:
+        """
+        Describe the statistics in human readable form.
+
+        Returns:
+            One section per statistic, one line per mod
+        """
+        lines = ["Mods pulled in by each requested mod:"]
+        lines.extend(f"  {root}: {size}" for root, size in self.closure_sizes.items())
+        lines.append(f"Maximum dependency depth: {self.max_depth}")
+        if self.optional_only:
+            lines.append("Mods only pulled in by optional dependencies:")
+            lines.extend(f"  {mod_name}" for mod_name in self.optional_only)
+        if self.largest:
+            lines.append("Largest mods:")
+            lines.extend(f"  {mod_name}: {format_size(size)}" for mod_name, size in self.largest)
+        return "\n".join(lines)
+
+
+def resolution_statistics(result: ResolutionResult, top: int = 10) -> ResolutionStatistics:
+    """
+    Analyse the mods of a resolution.
+
+    Args:
+        result: Resolution to analyse, see fetch_release_sizes to include the
+            largest mods, mods of unknown size are left out of them
+        top: Number of largest mods to list
+
+    Returns:
+        Closure sizes, depth, optional-only mods and largest mods
+    """
+    statistics = ResolutionStatistics()
+    for root in result.graph.roots:
+        depths = {
+            mod_name: depth
+            for mod_name, depth in result.graph.depths(root).items()
+            if mod_name in result.mods
+        }
+        statistics.closure_sizes[root] = len(depths)
+        statistics.max_depth = max([statistics.max_depth, *depths.values()])
+
+    required = result.graph.required_mods()
+    statistics.optional_only = [mod_name for mod_name in result.mods if mod_name not in required]
+
+    sized = [(mod.name, mod.size) for mod in result.mods.values() if mod.size is not None]
+    statistics.largest = sorted(sized, key=lambda item: (-item[1], item[0]))[:top]
+    return statistics
@@ -0,0 +1,178 @@
+"""
+In-memory stand-in for the mod portal and storage, for tests and offline runs.
+"""
+
+import hashlib
+import io
+import json
+import os
+import threading
+import zipfile
+from pathlib import Path
+from typing import Dict
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+import requests
+
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import find_release
+
+
+class MockPortalClient(PortalClient):
+    """
+    Serves canned portal responses and builds release zips on the fly.
+
+    Responses use the format of the portal's `/api/mods/<name>/full`
+    endpoint. Release zips only contain an info.json generated from the
+    release's info_json, and their sha1 matches the one reported by the mock.
+    """
+
+    def __init__(self, mods: Optional[Dict[str, dict]] = None):
+        """
+        Initialize the mock.
+
+        Args:
+            mods: Mapping of mod name to its `/full` response
+        """
+        super().__init__(storage_base_url="mock://storage")
+        self.mods: Dict[str, dict] = mods or {}
+        # Releases downloaded so far, as (name, version)
+        self.downloads: List[Tuple[str, str]] = []
+        # Number of times downloads of a mod fail before succeeding
+        self.failures: Dict[str, int] = {}
+        # Number of times requests for a mod are answered 429 Too Many Requests
+        self.overloads: Dict[str, int] = {}
+        self._lock = threading.Lock()
+
+    @classmethod
+    def from_directory(cls, path: str) -> "MockPortalClient":
+        """
+        Load `/full` responses saved as `<name>.json` files.
+
+        Args:
+            path: Directory of saved responses
+
+        Returns:
+            Mock serving the saved responses
+        """
+        mods = {}
+        for file_path in sorted(Path(path).glob("*.json")):
+            with open(file_path, "r", encoding="utf-8") as file:
+                mods[file_path.stem] = json.load(file)
+        return cls(mods)
+
+    def add_release(
+        self,
+        mod_name: str,
+        version: str,
+        factorio_version: str = "2.0",
+        dependencies: Optional[List[str]] = None,
+        released_at: Optional[str] = None,
+    ) -> dict:
+        """
+        Add a release to the mock.
+
+        Args:
+            mod_name: Name of the mod
+            version: Release version
+            factorio_version: Factorio version the release targets
+            dependencies: Dependency strings of the release's info.json
+            released_at: Release date as ISO 8601, e.g. "2024-10-21T12:00:00.000000Z"
+
+        Returns:
+            The added release
+        """
+        info_json = {
+            "name": mod_name,
+            "version": version,
+            "factorio_version": factorio_version,
+            "dependencies": dependencies if dependencies is not None else ["base"],
+        }
+        release = {
+            "version": version,
+            "file_name": f"{mod_name}_{version}.zip",
+            "info_json": info_json,
+        }
+        if released_at:
+            release["released_at"] = released_at
+        mod = self.mods.setdefault(mod_name, {"name": mod_name, "releases": []})
+        mod["releases"].append(release)
+        release["sha1"] = hashlib.sha1(self.release_zip(mod_name, version)).hexdigest()
+        return release
+
+    def release_zip(self, mod_name: str, version: str) -> bytes:
+        """
+        Build the zip of a release.
+
+        Args:
+            mod_name: Name of the mod
+            version: Release version
+
+        Returns:
+            Zip contents, identical for every call
+        """
+        release = find_release(self.mods.get(mod_name, {}).get("releases", []), version)
+        info_json = (release or {}).get("info_json") or {"name": mod_name, "version": version}
+
+        buffer = io.BytesIO()
+        with zipfile.ZipFile(buffer, "w") as archive:
+            # Fixed timestamp, so the sha1 does not depend on when the zip was built
+            entry = zipfile.ZipInfo(f"{mod_name}_{version}/info.json", (2020, 1, 1, 0, 0, 0))
+            archive.writestr(entry, json.dumps(info_json, indent=2))
+        return buffer.getvalue()
+
+    def _overload(self, mod_name: str):
+        with self._lock:
+            if not self.overloads.get(mod_name):
+                return
+            self.overloads[mod_name] -= 1
+
+        response = requests.Response()
+        response.status_code = 429
+        response.headers["Retry-After"] = "0"
+        raise requests.HTTPError("429 Client Error: Too Many Requests", response=response)
+
+    def _get_mod_details(self, mod_name: str) -> dict:
+        self._overload(mod_name)
+        if mod_name not in self.mods:
+            response = requests.Response()
+            response.status_code = 404
+            raise requests.HTTPError(
+                f"404 Client Error: Not Found for mod {mod_name}", response=response
+            )
+        return self.mods[mod_name]
+
+    def _list_mods(self, factorio_version: Optional[str]) -> List[dict]:
+        return [
+            {"name": name, **{key: value for key, value in details.items() if key != "releases"}}
+            for name, details in self.mods.items()
+            if factorio_version is None
+            or any(
+                release.get("info_json", {}).get("factorio_version") == factorio_version
+                for release in details.get("releases", [])
+            )
+        ]
+
+    def _get_release_size(self, mod_name: str, version: str) -> Optional[int]:
+        return len(self.release_zip(mod_name, version))
+
+    def _download_release(self, mod_name: str, version: str, output_path: str) -> str:
+        self._overload(mod_name)
+        with self._lock:
+            if self.failures.get(mod_name):
+                self.failures[mod_name] -= 1
+                raise requests.ConnectionError(f"Simulated failure downloading {mod_name}")
+            self.downloads.append((mod_name, version))
+
+        if not find_release(self.mods.get(mod_name, {}).get("releases", []), version):
+            raise requests.HTTPError(f"404 Client Error: Not Found for {mod_name} {version}")
+
+        os.makedirs(output_path, exist_ok=True)
+        file_path = os.path.join(output_path, f"{mod_name}_{version}.zip")
+        temp_path = f"{file_path}.part"
+        with open(temp_path, "wb") as file:
+            file.write(self.release_zip(mod_name, version))
+        os.replace(temp_path, file_path)
+        return file_path
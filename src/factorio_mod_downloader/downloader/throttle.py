@@ -0,0 +1,115 @@
+"""
+Adaptive limit on concurrent requests to the portal and storage.
+"""
+
+import threading
+import time
+from typing import Callable
+from typing import Final
+from typing import Optional
+
+import requests
+
+
+# Responses telling the client to slow down
+THROTTLE_STATUS_CODES: Final = (429, 502, 503, 504)
+MAX_BACKOFF: Final = 60  # seconds
+
+
+def retry_after(response: Optional[requests.Response]) -> Optional[float]:
+    """
+    Read the delay requested by a Retry-After header.
+
+    Args:
+        response: Response to read, may be None
+
+    Returns:
+        Delay in seconds, None if absent or given as a date
+    """
+    if response is None:
+        return None
+    try:
+        return float(response.headers.get("Retry-After", ""))
+    except ValueError:
+        return None
+
+
+class Throttle:
+    """
+    Limits concurrent requests and backs off when the server is overloaded.
+
+    Every overload halves the limit and pauses all requests, every `limit`
+    successful requests in a row raise it by one again, up to max_concurrency.
+    """
+
+    def __init__(self, max_concurrency: int = 4, log: Callable[[str], None] = lambda info: None):
+        """
+        Initialize the throttle.
+
+        Args:
+            max_concurrency: Highest number of concurrent requests
+            log: Callback receiving throttling events
+        """
+        self.max_concurrency = max(max_concurrency, 1)
+        self.limit = self.max_concurrency
+        self.log = log
+        self._active = 0
+        self._successes = 0
+        self._backoff = 1.0
+        self._resume_at = 0.0
+        self._condition = threading.Condition()
+
+    def __enter__(self):
+        with self._condition:
+            while True:
+                wait = self._resume_at - time.monotonic()
+                if wait > 0:
+                    self._condition.wait(wait)
+                elif self._active < self.limit:
+                    break
+                else:
+                    self._condition.wait()
+            self._active += 1
+        return self
+
+    def __exit__(self, *exc_info):
+        with self._condition:
+            self._active -= 1
+            self._condition.notify_all()
+
+    def overloaded(self, status_code: int, delay: Optional[float] = None):
+        """
+        Record an overload response, reducing concurrency and pausing requests.
+
+        Args:
+            status_code: HTTP status of the response
+            delay: Delay requested by the server, exponential backoff if None
+        """
+        with self._condition:
+            delay = min(delay if delay is not None else self._backoff, MAX_BACKOFF)
+            self._backoff = min(self._backoff * 2, MAX_BACKOFF)
+            self._resume_at = max(self._resume_at, time.monotonic() + delay)
+            self._successes = 0
+            self.limit = max(self.limit // 2, 1)
+
+        self.log(
+            f"Server overloaded ({status_code}), reducing concurrency to {self.limit} "
+            f"and pausing for {delay:.0f}s.\n"
+        )
+
+    def succeeded(self):
+        """Record a successful request, restoring concurrency over time."""
+        with self._condition:
+            self._backoff = 1.0
+            if self.limit >= self.max_concurrency:
+                return
+
+            self._successes += 1
+            if self._successes < self.limit:
+                return
+
+            self._successes = 0
+            self.limit += 1
+            self._condition.notify_all()
+
+        self.log(f"Increasing concurrency to {self.limit}.\n")
@@ -0,0 +1,101 @@
+"""
+Thumbnails of mods as shown on the portal, cached on disk for the GUI.
+
+The portal names thumbnails after their content, so a cached file never
+goes stale and is reused as long as the portal references it.
+"""
+
+import os
+from concurrent.futures import ThreadPoolExecutor
+from pathlib import Path
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+
+import requests
+
+from factorio_mod_downloader.downloader.cache import default_cache_dir
+from factorio_mod_downloader.downloader.portal import PortalClient
+
+
+ASSETS_BASE_URL: Final = "https://assets-mod.factorio.com"
+# Thumbnail the portal reports for mods without one
+PLACEHOLDER_THUMBNAIL: Final = "/assets/.thumb.png"
+
+
+def default_thumbnail_dir() -> Path:
+    """
+    Locate the directory thumbnails are cached in.
+
+    Returns:
+        `factorio-mod-downloader/thumbnails` in the user's cache directory
+    """
+    return default_cache_dir().parent / "thumbnails"
+
+
+def _fetch_thumbnail(
+    mod_name: str, cache_dir: Path, client: PortalClient, assets_base_url: str
+) -> Optional[str]:
+    try:
+        thumbnail = client.get_mod_details(mod_name).get("thumbnail")
+    except requests.RequestException:
+        return None
+    if not thumbnail or thumbnail == PLACEHOLDER_THUMBNAIL:
+        return None
+
+    file_path = cache_dir / os.path.basename(thumbnail)
+    if file_path.is_file():
+        return str(file_path)
+
+    temp_path = file_path.with_name(f"{file_path.name}.{os.getpid()}.part")
+    try:
+        response = client.session.get(
+            f"{assets_base_url.rstrip('/')}{thumbnail}",
+            timeout=(client.connect_timeout, client.metadata_timeout),
+        )
+        response.raise_for_status()
+        with open(temp_path, "wb") as file:
+            file.write(response.content)
+        os.replace(temp_path, file_path)
+    except (requests.RequestException, OSError):
+        return None
+    finally:
+        if temp_path.exists():
+            temp_path.unlink()
+
+    return str(file_path)
+
+
+def fetch_mod_thumbnails(
+    mod_names: List[str],
+    cache_dir: Optional[str] = None,
+    client: Optional[PortalClient] = None,
+    max_workers: int = 4,
+    assets_base_url: str = ASSETS_BASE_URL,
+) -> Dict[str, Optional[str]]:
+    """
+    Download the thumbnails of mods, reusing the ones already cached.
+
+    Args:
+        mod_names: Mods to fetch the thumbnail of
+        cache_dir: Directory to keep thumbnails in, defaults to default_thumbnail_dir()
+        client: Source of mod metadata, defaults to the default endpoints
+        max_workers: Number of concurrent lookups and downloads
+        assets_base_url: Server of the images the portal references
+
+    Returns:
+        Local path of each mod's thumbnail, None for mods without one or
+        whose thumbnail could not be fetched
+    """
+    cache_path = Path(cache_dir) if cache_dir else default_thumbnail_dir()
+    cache_path.mkdir(parents=True, exist_ok=True)
+    client = client or PortalClient(max_concurrency=max_workers)
+
+    unique_names = list(dict.fromkeys(mod_names))
+    with ThreadPoolExecutor(max_workers=max_workers) as executor:
+        paths = executor.map(
+            lambda mod_name: _fetch_thumbnail(mod_name, cache_path, client, assets_base_url),
+            unique_names,
+        )
+        return dict(zip(unique_names, paths))
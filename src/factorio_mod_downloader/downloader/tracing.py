@@ -0,0 +1,160 @@
+"""
+Spans timing each step of a run, written out as a Chrome trace.
+
+The resolver and batch downloads open spans carrying the mod they concern:
+
+    metadata  loading a mod's releases: mod, source
+    select    selecting a release: mod, depth in the dependency graph, version
+    resolve   the whole resolution: mods requested, mods resolved
+    download  downloading a release: mod, version, source
+    verify    checking a downloaded release: mod, version
+
+Spans are only recorded while a tracer is active, see tracing(). The trace
+file opens in chrome://tracing or https://ui.perfetto.dev, one row per
+thread, which shows where a slow resolution spends its time. A single
+tracer is active per process, concurrent runs share it.
+"""
+
+import json
+import os
+import threading
+import time
+from contextlib import contextmanager
+from contextlib import nullcontext
+from dataclasses import dataclass
+from dataclasses import field
+from typing import ContextManager
+from typing import Iterator
+from typing import List
+from typing import Optional
+
+
+_active: Optional["Tracer"] = None
+
+
+@dataclass
+class Span:
+    """A timed step of a run."""
+
+    name: str
+    # Seconds since the tracer started
+    start: float
+    duration: float
+    thread_id: int
+    thread_name: str
+    attributes: dict = field(default_factory=dict)
+
+
+class Tracer:
+    """Collects spans, safe to share between threads."""
+
+    def __init__(self):
+        self.spans: List[Span] = []
+        self._origin = time.perf_counter()
+        self._lock = threading.Lock()
+
+    @contextmanager
+    def span(self, name: str, **attributes) -> Iterator[dict]:
+        """
+        Time a block.
+
+        Args:
+            name: Name of the step
+            **attributes: Context of the step, e.g. mod="flib"
+
+        Yields:
+            The attributes, to add those only known at the end of the block
+        """
+        start = time.perf_counter()
+        try:
+            yield attributes
+        finally:
+            thread = threading.current_thread()
+            span = Span(
+                name,
+                start - self._origin,
+                time.perf_counter() - start,
+                thread.ident or 0,
+                thread.name,
+                attributes,
+            )
+            with self._lock:
+                self.spans.append(span)
+
+    def to_chrome_trace(self) -> dict:
+        """
+        The spans in Chrome's trace event format.
+
+        Returns:
+            Document with a complete event per span and the name of each thread
+        """
+        pid = os.getpid()
+        with self._lock:
+            spans = list(self.spans)
+
+        events = [
+            {"name": "thread_name", "ph": "M", "pid": pid, "tid": thread_id, "args": {"name": name}}
+            for thread_id, name in {span.thread_id: span.thread_name for span in spans}.items()
+        ]
+        events.extend(
+            {
+                "name": span.name,
+                "cat": "factorio-mod-downloader",
+                "ph": "X",
+                "ts": span.start * 1_000_000,
+                "dur": span.duration * 1_000_000,
+                "pid": pid,
+                "tid": span.thread_id,
+                "args": span.attributes,
+            }
+            for span in spans
+        )
+        return {"traceEvents": events, "displayTimeUnit": "ms"}
+
+    def write_chrome_trace(self, path: str):
+        """
+        Write the spans as a Chrome trace.
+
+        Args:
+            path: File to write
+        """
+        with open(path, "w", encoding="utf-8") as file:
+            json.dump(self.to_chrome_trace(), file, default=str)
+
+
+def span(name: str, **attributes) -> ContextManager[dict]:
+    """
+    Time a block under the active tracer, see Tracer.span.
+
+    Without an active tracer nothing is recorded, so instrumented code
+    costs next to nothing when not traced.
+    """
+    tracer = _active
+    if tracer is None:
+        return nullcontext(attributes)
+    return tracer.span(name, **attributes)
+
+
+@contextmanager
+def tracing(path: Optional[str]) -> Iterator[Optional[Tracer]]:
+    """
+    Record the spans of a block and write them as a Chrome trace.
+
+    Args:
+        path: File to write the trace to, None to trace nothing
+
+    Yields:
+        The active tracer, None if path is None
+    """
+    global _active
+    if path is None:
+        yield None
+        return
+
+    tracer = Tracer()
+    previous, _active = _active, tracer
+    try:
+        yield tracer
+    finally:
+        _active = previous
+        tracer.write_chrome_trace(path)
@@ -0,0 +1,74 @@
+"""
+Rollback of an output directory to its state before a download.
+
+The contents of the directory are hard linked into a staging directory
+first, so keeping them costs no copy on most file systems. Rolling back
+removes everything the download added and moves the staged files back.
+"""
+
+import os
+import shutil
+from pathlib import Path
+from typing import Final
+
+
+STAGING_DIR: Final = ".fmd-staging"
+# Prefix of the directories the downloader keeps its own files in
+INTERNAL_PREFIX: Final = ".fmd-"
+
+
+def link_or_copy(source: str, target: str):
+    """Hard link a file, or copy it where the file system does not support links."""
+    try:
+        os.link(source, target)
+    except OSError:
+        shutil.copy2(source, target)
+
+
+class Transaction:
+    """Snapshot of an output directory that a download can be rolled back to."""
+
+    def __init__(self, output_path: str):
+        """
+        Take the snapshot.
+
+        Args:
+            output_path: Output directory, created if missing
+        """
+        self.output_path = Path(output_path)
+        self.staging = self.output_path / STAGING_DIR
+        # A staging directory left by an interrupted run is stale
+        if self.staging.exists():
+            shutil.rmtree(self.staging)
+        self.staging.mkdir(parents=True)
+
+        self.existing = set()
+        for path in self.output_path.iterdir():
+            if path.name.startswith(INTERNAL_PREFIX):
+                continue
+            self.existing.add(path.name)
+            if path.is_dir():
+                shutil.copytree(path, self.staging / path.name, copy_function=link_or_copy)
+            else:
+                link_or_copy(path, self.staging / path.name)
+
+    def commit(self):
+        """Keep the changes and drop the snapshot."""
+        shutil.rmtree(self.staging, ignore_errors=True)
+
+    def rollback(self):
+        """Restore the output directory to the snapshot."""
+        for path in self.output_path.iterdir():
+            if not path.name.startswith(INTERNAL_PREFIX) and path.name not in self.existing:
+                if path.is_dir():
+                    shutil.rmtree(path)
+                else:
+                    path.unlink()
+
+        for staged in self.staging.iterdir():
+            target = self.output_path / staged.name
+            if target.is_dir():
+                shutil.rmtree(target)
+            os.replace(staged, target)
+
+        shutil.rmtree(self.staging, ignore_errors=True)
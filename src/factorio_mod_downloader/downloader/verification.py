@@ -0,0 +1,104 @@
+"""
+Integrity checks of release zips, run on a thread pool of their own.
+
+Hashing and inflating zips is CPU bound. hashlib and zlib release the GIL on
+large buffers, so checks spread over a pool sized to the number of cores
+scale with them, and downloads waiting on the network do not queue behind
+them.
+"""
+
+import os
+import zipfile
+from concurrent.futures import Executor
+from concurrent.futures import Future
+from concurrent.futures import ThreadPoolExecutor
+from typing import Any
+from typing import Callable
+from typing import Optional
+
+
+class IntegrityError(ValueError):
+    """A release zip does not match its checksum or is damaged."""
+
+
+def verification_pool(max_workers: Optional[int] = None) -> ThreadPoolExecutor:
+    """
+    Create the pool integrity checks run on.
+
+    Args:
+        max_workers: Number of threads, defaults to the number of cores
+
+    Returns:
+        Thread pool, to be shut down by the caller
+    """
+    return ThreadPoolExecutor(
+        max_workers=max_workers or os.cpu_count() or 1, thread_name_prefix="fmd-verify"
+    )
+
+
+def check_zip(file_path: str, sha1: str, expected_sha1: Optional[str] = None):
+    """
+    Check a release zip.
+
+    A zip matching the expected sha1 is intact. Without one, every member is
+    inflated and checked against its CRC.
+
+    Args:
+        file_path: Release zip
+        sha1: sha1 of the file
+        expected_sha1: sha1 reported by the portal, if known
+
+    Raises:
+        IntegrityError: If the sha1 differs or a member is damaged
+        zipfile.BadZipFile: If the file is not a zip archive
+    """
+    file_name = os.path.basename(file_path)
+    if expected_sha1:
+        if sha1 != expected_sha1:
+            raise IntegrityError(f"{file_name} has sha1 {sha1}, expected {expected_sha1}")
+        return
+
+    with zipfile.ZipFile(file_path) as archive:
+        damaged = archive.testzip()
+    if damaged:
+        raise IntegrityError(f"{file_name} is damaged, {damaged} fails its CRC check")
+
+
+def then(future: Future, executor: Executor, fn: Callable[[Any], Any]) -> Future:
+    """
+    Run a function on the result of a future, without a thread waiting for it.
+
+    Args:
+        future: Future whose result is passed on
+        executor: Executor to run fn on once future is done
+        fn: Function of the result
+
+    Returns:
+        Future of fn's result, or of the exception of either step. It is
+        cancelled along with either step.
+    """
+    chained: Future = Future()
+
+    def forward(done: Future):
+        if done.cancelled():
+            chained.cancel()
+        elif done.exception() is not None:
+            chained.set_exception(done.exception())
+        else:
+            chained.set_result(done.result())
+
+    def submit(done: Future):
+        if done.cancelled():
+            chained.cancel()
+            return
+        if done.exception() is not None:
+            chained.set_exception(done.exception())
+            return
+        try:
+            executor.submit(fn, done.result()).add_done_callback(forward)
+        except RuntimeError as e:
+            # The executor was shut down, e.g. by a rollback
+            chained.set_exception(e)
+
+    future.add_done_callback(submit)
+    return chained
@@ -0,0 +1,3 @@
+"""
+Game package initialization
+"""
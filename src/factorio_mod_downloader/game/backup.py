@@ -0,0 +1,119 @@
+"""
+Timestamped backups of a mods directory, taken before it is changed.
+
+Backups are kept in a `.fmd-backups` folder of the mods directory, one
+folder per backup named after the time it was taken. Files are hard linked
+rather than copied, and mod-list.json and zips are only ever replaced, never
+written in place, so a backup costs next to no space:
+
+    mods/.fmd-backups/20240501-142355-123456/mod-list.json
+    mods/.fmd-backups/20240501-142355-123456/Krastorio2_1.3.24.zip
+"""
+
+import os
+import shutil
+from datetime import datetime
+from pathlib import Path
+from typing import Final
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.transaction import INTERNAL_PREFIX
+from factorio_mod_downloader.downloader.transaction import link_or_copy
+
+
+BACKUP_DIR: Final = ".fmd-backups"
+# Number of backups kept per mods directory, older ones are removed
+MAX_BACKUPS: Final = 10
+
+
+def _contents(directory: Path) -> List[Path]:
+    return [path for path in directory.iterdir() if not path.name.startswith(INTERNAL_PREFIX)]
+
+
+def _link(source: Path, target: Path):
+    if source.is_dir():
+        shutil.copytree(source, target, copy_function=link_or_copy)
+    else:
+        link_or_copy(source, target)
+
+
+def list_backups(mods_dir: str) -> List[Path]:
+    """
+    List the backups of a mods directory.
+
+    Args:
+        mods_dir: Factorio mods directory
+
+    Returns:
+        Folders of the backups, oldest first
+    """
+    backups_path = Path(mods_dir) / BACKUP_DIR
+    if not backups_path.is_dir():
+        return []
+    return sorted(path for path in backups_path.iterdir() if path.is_dir())
+
+
+def backup_mods_dir(mods_dir: str, keep: int = MAX_BACKUPS) -> Optional[Path]:
+    """
+    Back up mod-list.json and the mods of a mods directory.
+
+    Args:
+        mods_dir: Factorio mods directory
+        keep: Number of backups to keep, older ones are removed
+
+    Returns:
+        Folder of the backup, None if the mods directory does not exist yet
+    """
+    mods_path = Path(mods_dir)
+    if not mods_path.is_dir():
+        return None
+
+    backup = mods_path / BACKUP_DIR / datetime.now().strftime("%Y%m%d-%H%M%S-%f")
+    temp_backup = backup.with_name(f"{backup.name}.part")
+    temp_backup.mkdir(parents=True)
+    for path in _contents(mods_path):
+        _link(path, temp_backup / path.name)
+    os.replace(temp_backup, backup)
+
+    for old_backup in list_backups(mods_dir)[:-keep]:
+        shutil.rmtree(old_backup)
+    return backup
+
+
+def restore_backup(mods_dir: str, backup: Optional[str] = None) -> Path:
+    """
+    Restore a mods directory to a backup.
+
+    Mods and files added since the backup are removed. The backup itself is
+    kept, so it can be restored again.
+
+    Args:
+        mods_dir: Factorio mods directory
+        backup: Folder or name of the backup, defaults to the latest one
+
+    Returns:
+        Folder of the restored backup
+
+    Raises:
+        FileNotFoundError: If the backup does not exist, or there is none
+    """
+    if backup is None:
+        backups = list_backups(mods_dir)
+        if not backups:
+            raise FileNotFoundError(f"No backups of {mods_dir}")
+        backup_path = backups[-1]
+    else:
+        backup_path = Path(mods_dir) / BACKUP_DIR / Path(backup).name
+        if not backup_path.is_dir():
+            raise FileNotFoundError(f"No backup named {Path(backup).name} in {mods_dir}")
+
+    mods_path = Path(mods_dir)
+    for path in _contents(mods_path):
+        if path.is_dir():
+            shutil.rmtree(path)
+        else:
+            path.unlink()
+    for path in _contents(backup_path):
+        _link(path, mods_path / path.name)
+    return backup_path
@@ -0,0 +1,112 @@
+"""
+Comparison of two sets of mods, e.g. a client's mods directory with a server's.
+
+Each side can be a mods directory, a modpack file or a text list of mods.
+Modpacks pinning every mod with `name@version` serve as lockfiles; mods
+left unpinned have no version to compare and only count when added or
+removed.
+"""
+
+from dataclasses import dataclass
+from dataclasses import field
+from pathlib import Path
+from typing import Dict
+from typing import List
+from typing import Optional
+from typing import Union
+
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.game.modpack import load_modpack
+from factorio_mod_downloader.game.modpack import parse_mod_spec
+from factorio_mod_downloader.game.modpack import snapshot
+
+
+# Mod name to version, None where a modpack does not pin it
+ModSet = Dict[str, Optional[str]]
+
+
+@dataclass
+class ModChange:
+    """A mod that differs between two sets."""
+
+    name: str
+    # Version on each side, None if the mod is missing there or not pinned
+    old_version: Optional[str]
+    new_version: Optional[str]
+
+    def __str__(self) -> str:
+        return f"{self.name} {self.old_version or '-'} -> {self.new_version or '-'}"
+
+
+@dataclass
+class ModSetDiff:
+    """Differences from a set of mods to another, each list sorted by name."""
+
+    added: List[ModChange] = field(default_factory=list)
+    removed: List[ModChange] = field(default_factory=list)
+    upgraded: List[ModChange] = field(default_factory=list)
+    downgraded: List[ModChange] = field(default_factory=list)
+
+    def __bool__(self) -> bool:
+        return bool(self.added or self.removed or self.upgraded or self.downgraded)
+
+    def format(self) -> str:
+        """One line per change, e.g. "+ flib 0.14.0" or "^ Krastorio2 1.3.23 -> 1.3.24"."""
+        lines = [f"+ {change.name} {change.new_version or ''}".rstrip() for change in self.added]
+        lines += [f"- {change.name} {change.old_version or ''}".rstrip() for change in self.removed]
+        lines += [f"^ {change}" for change in self.upgraded]
+        lines += [f"v {change}" for change in self.downgraded]
+        return "\n".join(lines)
+
+
+def load_mod_set(source: Union[str, ModSet]) -> ModSet:
+    """
+    Read the mods of a mods directory or modpack file.
+
+    Args:
+        source: Mods directory, modpack JSON file or text list of mods, or
+            an already loaded set
+
+    Returns:
+        Version of each mod. For a mods directory the newest installed one,
+        named after each zip's info.json.
+
+    Raises:
+        ModpackError: If a modpack file is invalid
+    """
+    if isinstance(source, dict):
+        return dict(source)
+
+    modpack = snapshot(source, False) if Path(source).is_dir() else load_modpack(source)
+    return dict(parse_mod_spec(mod) for mod in modpack.mods)
+
+
+def diff_mod_sets(a: Union[str, ModSet], b: Union[str, ModSet]) -> ModSetDiff:
+    """
+    Compare two sets of mods.
+
+    Args:
+        a: Old set, a mods directory, modpack file or loaded set
+        b: New set, a mods directory, modpack file or loaded set
+
+    Returns:
+        Mods only in b, mods only in a, and mods whose version b raises or lowers
+
+    Raises:
+        ModpackError: If a modpack file is invalid
+    """
+    old, new = load_mod_set(a), load_mod_set(b)
+    diff = ModSetDiff()
+
+    for name in sorted(old.keys() | new.keys()):
+        if name not in old:
+            diff.added.append(ModChange(name, None, new[name]))
+        elif name not in new:
+            diff.removed.append(ModChange(name, old[name], None))
+        elif old[name] and new[name]:
+            change = ModChange(name, old[name], new[name])
+            if parse_version(new[name]) > parse_version(old[name]):
+                diff.upgraded.append(change)
+            elif parse_version(new[name]) < parse_version(old[name]):
+                diff.downgraded.append(change)
+    return diff
@@ -0,0 +1,67 @@
+"""
+Unpacking of mod zips into folders, as run by mod developers and some servers.
+"""
+
+import os
+import shutil
+import zipfile
+from pathlib import Path
+
+
+def extract_mod(zip_path: str, mods_dir: str, mod_name: str, version: str) -> str:
+    """
+    Unpack a mod zip into a `name_version` folder of a mods directory.
+
+    The contents of the archive's top level folder end up directly in the mod
+    folder, whatever that top level folder is called. An existing folder for
+    the same version is replaced.
+
+    Args:
+        zip_path: Path to the mod zip
+        mods_dir: Factorio mods directory
+        mod_name: Name of the mod
+        version: Version of the mod
+
+    Returns:
+        Path of the mod folder
+
+    Raises:
+        zipfile.BadZipFile: If the file is not a zip archive
+        ValueError: If an entry would be extracted outside the mod folder
+    """
+    folder = Path(mods_dir) / f"{mod_name}_{version}"
+    temp_folder = folder.with_name(f"{folder.name}.part")
+    if temp_folder.exists():
+        shutil.rmtree(temp_folder)
+    temp_folder.mkdir(parents=True)
+    temp_root = temp_folder.resolve()
+
+    try:
+        with zipfile.ZipFile(zip_path) as archive:
+            for entry in archive.infolist():
+                # Drop the top level folder, the mod folder takes its place
+                _, _, relative_path = entry.filename.replace("\\", "/").partition("/")
+                if not relative_path:
+                    continue
+
+                # Refuse entries escaping the mod folder through ".." or absolute paths
+                target = (temp_root / relative_path).resolve()
+                if not target.is_relative_to(temp_root):
+                    raise ValueError(f"Unsafe path {entry.filename!r} in {zip_path}")
+
+                if entry.is_dir():
+                    target.mkdir(parents=True, exist_ok=True)
+                    continue
+
+                target.parent.mkdir(parents=True, exist_ok=True)
+                with archive.open(entry) as source, open(target, "wb") as destination:
+                    shutil.copyfileobj(source, destination)
+
+        if folder.exists():
+            shutil.rmtree(folder)
+        os.replace(temp_folder, folder)
+    finally:
+        if temp_folder.exists():
+            shutil.rmtree(temp_folder)
+
+    return str(folder)
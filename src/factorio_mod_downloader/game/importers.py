@@ -0,0 +1,198 @@
+"""
+Conversion of mod lists from other tools into modpacks, see modpack.py.
+
+Supported sources:
+
+- Factorio's mod-list.json, e.g. shared along with a save. Disabled mods stay
+  disabled in the startup section.
+- Factorio's log, factorio-current.log, after loading a save with "Sync mods
+  with save". Every mod the game loaded is pinned to the loaded version.
+- JSON exports of pack managers listing mods as objects with a name and an
+  optional version and enabled state, at the top level or under a "mods"
+  key, whatever the case of the keys: [{"Name": "flib", "Version": "0.14.0"}]
+
+Mods shipped with the game are never listed as mods to download, only their
+enabled state is kept.
+"""
+
+import json
+import re
+from pathlib import Path
+from typing import Any
+from typing import Collection
+from typing import Dict
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.dependencies import BUILTIN_MODS
+from factorio_mod_downloader.game.mod_list import MOD_LIST_FILE
+from factorio_mod_downloader.game.modpack import Modpack
+from factorio_mod_downloader.game.modpack import ModpackError
+
+
+IMPORT_FORMATS = ("mod-list", "factorio-log", "json-list")
+# Logged once per mod and stage, e.g. "   0.512 Loading mod flib 0.14.0 (data.lua)"
+LOADING_MOD_PATTERN = re.compile(r"Loading mod (?P<name>\S+) (?P<version>\d+\.\d+\.\d+) \(")
+# Releases have three components, two are a Factorio version some exports give instead
+RELEASE_VERSION_PATTERN = re.compile(r"^\d+\.\d+\.\d+$")
+# Keys of mod-list.json entries, pack manager exports have others
+MOD_LIST_KEYS = {"name", "enabled", "version"}
+# Part of the engine, logged like a mod but neither downloadable nor listed in mod-list.json
+CORE_MOD = "core"
+
+
+def _to_modpack(
+    versions: Dict[str, Optional[str]], enabled: Dict[str, bool], builtin_mods: Collection[str]
+) -> Modpack:
+    # Disabled mods are downloaded too, the startup section keeps them disabled
+    mods = [
+        f"{name}@{version}" if version else name
+        for name, version in versions.items()
+        if name not in builtin_mods
+    ]
+    startup = {name: False for name, state in enabled.items() if not state}
+    return Modpack(mods=mods, startup=startup)
+
+
+def _entries(data: Any, source: str) -> List[Any]:
+    if isinstance(data, dict):
+        data = next((value for key, value in data.items() if key.lower() == "mods"), None)
+    if not isinstance(data, list):
+        raise ModpackError(f"{source} does not contain a list of mods")
+    return data
+
+
+def import_mod_list_json(
+    content: str, source: str = "<input>", builtin_mods: Collection[str] = BUILTIN_MODS
+) -> Modpack:
+    """
+    Convert a mod-list.json.
+
+    Args:
+        content: Contents of the file
+        source: Name of the file in error messages
+        builtin_mods: Mods shipped with the game
+
+    Returns:
+        Modpack of the listed mods, pinned where mod-list.json pins a version
+
+    Raises:
+        ModpackError: If the content is not a mod-list.json
+    """
+    try:
+        entries = _entries(json.loads(content), source)
+    except ValueError as e:
+        raise ModpackError(f"{source} is not valid JSON: {e}") from e
+
+    versions: Dict[str, Optional[str]] = {}
+    enabled: Dict[str, bool] = {}
+    for entry in entries:
+        if not isinstance(entry, dict) or not isinstance(entry.get("name"), str):
+            raise ModpackError(f"{source}: mod-list.json entries need a name")
+        versions[entry["name"]] = entry.get("version")
+        enabled[entry["name"]] = bool(entry.get("enabled", True))
+    return _to_modpack(versions, enabled, builtin_mods)
+
+
+def import_factorio_log(content: str, builtin_mods: Collection[str] = BUILTIN_MODS) -> Modpack:
+    """
+    Convert the mods a Factorio log shows loaded.
+
+    Args:
+        content: Contents of factorio-current.log
+        builtin_mods: Mods shipped with the game
+
+    Returns:
+        Modpack pinning every loaded mod, empty if the log loaded none
+    """
+    versions: Dict[str, Optional[str]] = {}
+    for match in LOADING_MOD_PATTERN.finditer(content):
+        if match["name"] != CORE_MOD:
+            versions.setdefault(match["name"], match["version"])
+    return _to_modpack(versions, {}, builtin_mods)
+
+
+def import_json_list(
+    content: str, source: str = "<input>", builtin_mods: Collection[str] = BUILTIN_MODS
+) -> Modpack:
+    """
+    Convert a pack manager's JSON export.
+
+    Args:
+        content: Contents of the export
+        source: Name of the export in error messages
+        builtin_mods: Mods shipped with the game
+
+    Returns:
+        Modpack of the listed mods, pinned where the export gives a version
+
+    Raises:
+        ModpackError: If the export does not list mods
+    """
+    try:
+        entries = _entries(json.loads(content), source)
+    except ValueError as e:
+        raise ModpackError(f"{source} is not valid JSON: {e}") from e
+
+    versions: Dict[str, Optional[str]] = {}
+    enabled: Dict[str, bool] = {}
+    for entry in entries:
+        if isinstance(entry, str):
+            versions[entry] = None
+            continue
+        if not isinstance(entry, dict):
+            raise ModpackError(f"{source}: every mod needs a name, got {entry!r}")
+        fields = {str(key).lower(): value for key, value in entry.items()}
+        name = fields.get("name")
+        if not isinstance(name, str) or not name:
+            raise ModpackError(f"{source}: every mod needs a name, got {entry!r}")
+        version = str(fields.get("version") or "")
+        versions[name] = version if RELEASE_VERSION_PATTERN.match(version) else None
+        if "enabled" in fields:
+            enabled[name] = bool(fields["enabled"])
+    return _to_modpack(versions, enabled, builtin_mods)
+
+
+def _guess_import_format(path: str, content: str) -> str:
+    if LOADING_MOD_PATTERN.search(content):
+        return "factorio-log"
+    if Path(path).name == MOD_LIST_FILE:
+        return "mod-list"
+    try:
+        entries = _entries(json.loads(content), path)
+    except (ValueError, ModpackError):
+        raise ModpackError(f"Could not tell the format of {path}") from None
+    if all(isinstance(entry, dict) and set(entry) <= MOD_LIST_KEYS for entry in entries):
+        return "mod-list"
+    return "json-list"
+
+
+def import_modpack(
+    path: str, format_hint: Optional[str] = None, builtin_mods: Collection[str] = BUILTIN_MODS
+) -> Modpack:
+    """
+    Convert a mod list exported by another tool.
+
+    Args:
+        path: Path to the export
+        format_hint: One of IMPORT_FORMATS, guessed from the file if None
+        builtin_mods: Mods shipped with the game
+
+    Returns:
+        Modpack of the listed mods, to save with save_modpack
+
+    Raises:
+        ModpackError: If the file is not in a supported format
+    """
+    if format_hint not in (None, *IMPORT_FORMATS):
+        raise ModpackError(f"Unknown format {format_hint!r}, expected one of {IMPORT_FORMATS}")
+
+    with open(path, encoding="utf-8", errors="replace") as file:
+        content = file.read()
+
+    format_hint = format_hint or _guess_import_format(path, content)
+    if format_hint == "factorio-log":
+        return import_factorio_log(content, builtin_mods)
+    if format_hint == "mod-list":
+        return import_mod_list_json(content, path, builtin_mods)
+    return import_json_list(content, path, builtin_mods)
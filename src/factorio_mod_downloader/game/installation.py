@@ -0,0 +1,435 @@
+"""
+Factorio installation detection for Windows, Linux and macOS.
+"""
+
+import json
+import os
+import re
+import subprocess
+import sys
+from dataclasses import dataclass
+from pathlib import Path
+from typing import FrozenSet
+from typing import List
+from typing import Optional
+
+import requests
+
+from factorio_mod_downloader.downloader.config import Channel
+from factorio_mod_downloader.downloader.dependencies import BUILTIN_MODS
+from factorio_mod_downloader.downloader.dependencies import DLC_MODS
+
+
+DEFAULT_FACTORIO_VERSION = "2.0"
+# Config.factorio_version requiring the version of the destination's game
+AUTO_FACTORIO_VERSION = "auto"
+# Newest game version of each release channel, by build
+LATEST_RELEASES_URL = "https://factorio.com/api/latest-releases"
+STEAM_APP_DIR = Path("steamapps") / "common" / "Factorio"
+FLATPAK_STEAM_DIR = Path.home() / ".var" / "app" / "com.valvesoftware.Steam"
+
+
+class GameVersionError(Exception):
+    """Raised when the version of the destination's game is required but cannot be detected."""
+
+
+@dataclass
+class FactorioInstallation:
+    """A detected Factorio installation and the directory it keeps mods in."""
+
+    install_dir: Optional[Path]
+    user_data_dir: Path
+    mods_dir: Path
+    version: Optional[str]
+
+
+def _platform() -> str:
+    if sys.platform.startswith("win"):
+        return "windows"
+    if sys.platform == "darwin":
+        return "macos"
+    return "linux"
+
+
+def _windows_steam_root() -> Optional[Path]:
+    """Read the Steam install path from the registry on Windows."""
+    try:
+        import winreg
+
+        with winreg.OpenKey(winreg.HKEY_CURRENT_USER, r"Software\Valve\Steam") as key:
+            steam_path, _ = winreg.QueryValueEx(key, "SteamPath")
+            return Path(steam_path)
+    except Exception:
+        return None
+
+
+def _steam_roots() -> List[Path]:
+    platform = _platform()
+    roots = []
+
+    if platform == "windows":
+        registry_root = _windows_steam_root()
+        if registry_root:
+            roots.append(registry_root)
+        program_files_x86 = os.environ.get("ProgramFiles(x86)", r"C:\Program Files (x86)")
+        roots.append(Path(program_files_x86) / "Steam")
+    elif platform == "macos":
+        roots.append(Path.home() / "Library" / "Application Support" / "Steam")
+    else:
+        roots.append(Path.home() / ".steam" / "steam")
+        roots.append(Path.home() / ".local" / "share" / "Steam")
+        roots.append(FLATPAK_STEAM_DIR / ".local" / "share" / "Steam")
+
+    return roots
+
+
+def _steam_library_dirs() -> List[Path]:
+    """
+    List all Steam library folders, including the ones added on other drives.
+
+    Returns:
+        Steam library directories in the order Steam reports them
+    """
+    libraries = []
+
+    for root in _steam_roots():
+        if root not in libraries:
+            libraries.append(root)
+
+        vdf_file = root / "steamapps" / "libraryfolders.vdf"
+        if not vdf_file.is_file():
+            continue
+
+        try:
+            content = vdf_file.read_text(encoding="utf-8", errors="ignore")
+        except OSError:
+            continue
+
+        for match in re.finditer(r'"path"\s+"([^"]+)"', content):
+            library = Path(match.group(1).replace("\\\\", "\\"))
+            if library not in libraries:
+                libraries.append(library)
+
+    return libraries
+
+
+def _install_dir_candidates() -> List[Path]:
+    platform = _platform()
+    candidates = [library / STEAM_APP_DIR for library in _steam_library_dirs()]
+
+    if platform == "windows":
+        program_files = os.environ.get("ProgramFiles", r"C:\Program Files")
+        candidates.append(Path(program_files) / "Factorio")
+    elif platform == "macos":
+        candidates.append(Path("/Applications") / "factorio.app" / "Contents")
+    else:
+        candidates.append(Path.home() / "factorio")
+        candidates.append(Path("/opt") / "factorio")
+
+    return candidates
+
+
+def _user_data_dir_candidates() -> List[Path]:
+    platform = _platform()
+
+    if platform == "windows":
+        appdata = os.environ.get("APPDATA", str(Path.home() / "AppData" / "Roaming"))
+        return [Path(appdata) / "Factorio"]
+    if platform == "macos":
+        return [Path.home() / "Library" / "Application Support" / "factorio"]
+    return [Path.home() / ".factorio", FLATPAK_STEAM_DIR / ".factorio"]
+
+
+def _game_root(install_dir: Path) -> Optional[Path]:
+    """Return the directory holding `data/`, accounting for the macOS app bundle."""
+    for root in (install_dir, install_dir / "factorio.app" / "Contents"):
+        if (root / "data" / "base" / "info.json").is_file():
+            return root
+    return None
+
+
+def _is_portable(install_dir: Path) -> bool:
+    """Check whether the install keeps its mods next to the game instead of in user data."""
+    config_path = install_dir / "config-path.cfg"
+    if not config_path.is_file():
+        return False
+
+    try:
+        content = config_path.read_text(encoding="utf-8", errors="ignore")
+    except OSError:
+        return False
+
+    return "use-system-read-write-data-directories=false" in content.replace(" ", "")
+
+
+def _executable_version(install_dir: Path) -> Optional[str]:
+    """Ask the game binary for its version via `factorio --version`."""
+    for executable in (
+        install_dir / "bin" / "x64" / "factorio.exe",
+        install_dir / "bin" / "x64" / "factorio",
+        install_dir / "MacOS" / "factorio",
+    ):
+        if not executable.is_file():
+            continue
+
+        try:
+            output = subprocess.run(
+                [str(executable), "--version"],
+                capture_output=True,
+                text=True,
+                timeout=10,
+            ).stdout
+        except (OSError, subprocess.SubprocessError):
+            continue
+
+        match = re.search(r"Version:\s*(\d+\.\d+\.\d+)", output)
+        if match:
+            return match.group(1)
+
+    return None
+
+
+def read_game_version(install_dir: Path) -> Optional[str]:
+    """
+    Read the game version of a Factorio installation.
+
+    Parses `data/base/info.json`, falling back to `factorio --version` output.
+
+    Args:
+        install_dir: Factorio installation directory
+
+    Returns:
+        Game version (e.g. "2.0.28"), or None if it could not be read
+    """
+    install_dir = Path(install_dir)
+    root = _game_root(install_dir)
+
+    if root:
+        try:
+            with open(root / "data" / "base" / "info.json", encoding="utf-8") as file:
+                version = json.load(file).get("version")
+            if version:
+                return version
+        except (OSError, ValueError):
+            pass
+
+    return _executable_version(root or install_dir)
+
+
+def read_builtin_mods(install_dir: Path) -> List[str]:
+    """
+    List the mods shipped with a Factorio installation, DLCs included.
+
+    Args:
+        install_dir: Factorio installation directory
+
+    Returns:
+        Names of the mods in the game's `data` directory, empty if it could not be read
+    """
+    root = _game_root(Path(install_dir))
+    if not root:
+        return []
+
+    # core is the engine's own data, mods cannot depend on it
+    return sorted(
+        path.parent.name
+        for path in (root / "data").glob("*/info.json")
+        if path.parent.name != "core"
+    )
+
+
+def detect_factorio() -> Optional[FactorioInstallation]:
+    """
+    Locate the Factorio installation and the user mods directory.
+
+    Looks through Steam libraries (including Flatpak Steam), standalone install
+    locations and the per-user data directories of the current platform.
+
+    Returns:
+        The detected installation, or None if Factorio could not be found
+    """
+    install_dir = None
+    for candidate in _install_dir_candidates():
+        install_dir = _game_root(candidate)
+        if install_dir:
+            break
+
+    if install_dir and _is_portable(install_dir):
+        user_data_dir = install_dir
+    else:
+        user_data_dir = next(
+            (path for path in _user_data_dir_candidates() if path.is_dir()),
+            None,
+        )
+
+    if not user_data_dir:
+        if not install_dir:
+            return None
+        user_data_dir = _user_data_dir_candidates()[0]
+
+    return FactorioInstallation(
+        install_dir=install_dir,
+        user_data_dir=user_data_dir,
+        mods_dir=user_data_dir / "mods",
+        version=read_game_version(install_dir) if install_dir else None,
+    )
+
+
+def find_installation_for_mods_dir(mods_dir: str) -> Optional[FactorioInstallation]:
+    """
+    Find the Factorio installation a mods directory belongs to.
+
+    Args:
+        mods_dir: Mods directory chosen as the download destination
+
+    Returns:
+        The owning installation, or None if the directory is not a game mods directory
+    """
+    mods_dir = Path(mods_dir).expanduser().resolve()
+
+    # Portable installs keep mods next to the game data
+    install_dir = _game_root(mods_dir.parent)
+    if install_dir:
+        return FactorioInstallation(
+            install_dir=install_dir,
+            user_data_dir=mods_dir.parent,
+            mods_dir=mods_dir,
+            version=read_game_version(install_dir),
+        )
+
+    installation = detect_factorio()
+    if installation and installation.mods_dir.expanduser().resolve() == mods_dir:
+        return installation
+
+    return None
+
+
+def latest_game_version(channel: Channel = Channel.STABLE, timeout: float = 10) -> Optional[str]:
+    """
+    Look up the newest game version of a release channel on factorio.com.
+
+    Args:
+        channel: Release channel
+        timeout: Request timeout in seconds
+
+    Returns:
+        Game version (e.g. "2.1.3"), or None if it could not be looked up
+    """
+    try:
+        response = requests.get(LATEST_RELEASES_URL, timeout=timeout)
+        response.raise_for_status()
+        builds = response.json().get(channel.value)
+    except (requests.RequestException, ValueError, AttributeError):
+        return None
+
+    if not isinstance(builds, dict):
+        return None
+    # Every build of a channel shares its version, the headless one exists for all of them
+    return builds.get("headless") or builds.get("alpha")
+
+
+def target_factorio_version(
+    mods_dir: str, factorio_version: Optional[str] = None, channel: Channel = Channel.STABLE
+) -> str:
+    """
+    Determine which Factorio version releases should be selected for.
+
+    Args:
+        mods_dir: Download destination
+        factorio_version: Explicit version, always takes precedence when given.
+            AUTO_FACTORIO_VERSION requires the version of the destination's game.
+        channel: Release channel targeted when the destination does not belong
+            to a game. The newest experimental version is looked up online,
+            stable falls back to DEFAULT_FACTORIO_VERSION.
+
+    Returns:
+        Factorio version in "major.minor" form (e.g. "2.0")
+
+    Raises:
+        GameVersionError: If factorio_version is AUTO_FACTORIO_VERSION and the
+            destination's game or its version cannot be found
+    """
+    if factorio_version and factorio_version != AUTO_FACTORIO_VERSION:
+        return factorio_version
+
+    installation = find_installation_for_mods_dir(mods_dir)
+    if installation and installation.version:
+        return ".".join(installation.version.split(".")[:2])
+
+    if factorio_version == AUTO_FACTORIO_VERSION:
+        if installation and installation.install_dir:
+            raise GameVersionError(
+                f"Could not read the version of the game installed in {installation.install_dir}, "
+                "give the Factorio version explicitly, e.g. --factorio-version 2.0"
+            )
+        if installation:
+            raise GameVersionError(
+                f"{mods_dir} is in the Factorio user data directory "
+                f"{installation.user_data_dir}, but the game's install directory could not "
+                "be found to read its version. Give the Factorio version explicitly, "
+                "e.g. --factorio-version 2.0"
+            )
+        raise GameVersionError(
+            f"{mods_dir} is not the mods directory of a Factorio installation, so the game "
+            "version cannot be detected. Download to the game's mods directory or give "
+            "the Factorio version explicitly, e.g. --factorio-version 2.0"
+        )
+
+    if channel == Channel.EXPERIMENTAL:
+        version = latest_game_version(channel)
+        if version:
+            return ".".join(version.split(".")[:2])
+
+    return DEFAULT_FACTORIO_VERSION
+
+
+def target_builtin_mods(
+    mods_dir: str, builtin_mods: Optional[List[str]] = None
+) -> FrozenSet[str]:
+    """
+    Determine which mods are shipped with the game and never downloaded.
+
+    Args:
+        mods_dir: Download destination
+        builtin_mods: Explicit list, always takes precedence when given
+
+    Returns:
+        The explicit list, the mods shipped with the game owning the
+        destination, or BUILTIN_MODS if it does not belong to a game
+    """
+    if builtin_mods is not None:
+        return frozenset(builtin_mods)
+
+    installation = find_installation_for_mods_dir(mods_dir)
+    # A user data directory may be found without the game it belongs to
+    shipped = (
+        read_builtin_mods(installation.install_dir)
+        if installation and installation.install_dir
+        else []
+    )
+    # Older games ship fewer mods, but their mods never depend on newer official ones
+    return frozenset(shipped or BUILTIN_MODS)
+
+
+def target_has_dlc(mods_dir: str, has_dlc: Optional[bool] = None) -> bool:
+    """
+    Determine whether the game mods are downloaded for has the Space Age DLC.
+
+    Args:
+        mods_dir: Download destination
+        has_dlc: Explicit answer, always takes precedence when given
+
+    Returns:
+        Whether the game owning the destination ships the DLC's mods, True
+        if the destination does not belong to a game
+    """
+    if has_dlc is not None:
+        return has_dlc
+
+    installation = find_installation_for_mods_dir(mods_dir)
+    shipped = (
+        read_builtin_mods(installation.install_dir)
+        if installation and installation.install_dir
+        else []
+    )
+    return not shipped or any(mod_name in shipped for mod_name in DLC_MODS)
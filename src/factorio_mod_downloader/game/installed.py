@@ -0,0 +1,176 @@
+"""
+Inspection of the mod zips installed in a mods directory.
+"""
+
+import hashlib
+import json
+import re
+import zipfile
+from dataclasses import dataclass
+from dataclasses import field
+from pathlib import Path
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+
+# `name_version.zip` or an extracted `name_version` folder, names may contain underscores
+MOD_FILENAME_PATTERN: Final = re.compile(r"^(?P<name>.+)_(?P<version>\d+\.\d+\.\d+)(?:\.zip)?$")
+
+
+@dataclass
+class InstalledMod:
+    """A mod zip found in a mods directory, described by its info.json."""
+
+    path: Path
+    name: str
+    version: str
+    factorio_version: Optional[str] = None
+    dependencies: List[str] = field(default_factory=list)
+
+
+def parse_mod_filename(file_name: str) -> Tuple[str, str]:
+    """
+    Split the file name of a mod zip or folder into the mod's name and version.
+
+    The version follows the last underscore, so names such as Squeak_Through
+    stay intact.
+
+    Args:
+        file_name: File or folder name, e.g. "Squeak_Through_1.8.2.zip"
+
+    Returns:
+        Mod name and version, e.g. ("Squeak_Through", "1.8.2")
+
+    Raises:
+        ValueError: If the name does not end in an underscore and a version
+    """
+    match = MOD_FILENAME_PATTERN.match(Path(file_name).name)
+    if not match:
+        raise ValueError(f"{file_name} is not named name_version.zip")
+    return match.group("name"), match.group("version")
+
+
+def installed_versions(mods_dir: str) -> Dict[str, List[str]]:
+    """
+    List the versions of each mod in a mods directory by their file names.
+
+    Zips and extracted folders count, nothing is opened, so large
+    directories are listed quickly.
+
+    Args:
+        mods_dir: Factorio mods directory, may not exist yet
+
+    Returns:
+        Versions by mod name
+    """
+    versions: Dict[str, List[str]] = {}
+    if not Path(mods_dir).is_dir():
+        return versions
+    for path in sorted(Path(mods_dir).iterdir()):
+        if path.suffix != ".zip" and not path.is_dir():
+            continue
+        try:
+            name, version = parse_mod_filename(path.name)
+        except ValueError:
+            continue
+        versions.setdefault(name, []).append(version)
+    return versions
+
+
+def read_mod_info(zip_path: str) -> dict:
+    """
+    Read the info.json of a mod zip.
+
+    Args:
+        zip_path: Path to the mod zip
+
+    Returns:
+        Parsed info.json
+
+    Raises:
+        zipfile.BadZipFile: If the file is not a zip archive
+        ValueError: If the archive has no valid info.json
+    """
+    with zipfile.ZipFile(zip_path) as archive:
+        # info.json lives in the single top level folder of the archive
+        info_files = [
+            name
+            for name in archive.namelist()
+            if name.count("/") == 1 and name.endswith("/info.json")
+        ]
+        if not info_files:
+            raise ValueError(f"{zip_path} does not contain an info.json")
+
+        with archive.open(info_files[0]) as file:
+            return json.load(file)
+
+
+def load_installed_mod(zip_path: str) -> InstalledMod:
+    """
+    Describe a mod zip by its info.json.
+
+    Args:
+        zip_path: Path to the mod zip
+
+    Returns:
+        The installed mod
+
+    Raises:
+        zipfile.BadZipFile: If the file is not a zip archive
+        ValueError: If the archive has no valid info.json
+    """
+    info = read_mod_info(zip_path)
+    # A zip missing the name or version in its info.json still names them
+    try:
+        file_name, file_version = parse_mod_filename(zip_path)
+    except ValueError:
+        file_name, file_version = "", ""
+    return InstalledMod(
+        path=Path(zip_path),
+        name=info.get("name") or file_name,
+        version=info.get("version") or file_version,
+        factorio_version=info.get("factorio_version"),
+        dependencies=info.get("dependencies", []),
+    )
+
+
+def sha1_file(path: str) -> str:
+    """
+    Compute the sha1 checksum of a file.
+
+    Args:
+        path: Path to the file
+
+    Returns:
+        Hex encoded sha1 digest
+    """
+    digest = hashlib.sha1()
+    with open(path, "rb") as file:
+        for chunk in iter(lambda: file.read(1024 * 1024), b""):
+            digest.update(chunk)
+    return digest.hexdigest()
+
+
+def scan_mods_dir(mods_dir: str) -> List[InstalledMod]:
+    """
+    List the mods installed in a mods directory.
+
+    Zips whose info.json cannot be read are skipped, use load_installed_mod to
+    find out why a specific zip is unreadable.
+
+    Args:
+        mods_dir: Factorio mods directory
+
+    Returns:
+        Installed mods sorted by file name
+    """
+    mods = []
+    for path in sorted(Path(mods_dir).glob("*.zip")):
+        try:
+            mods.append(load_installed_mod(path))
+        except (zipfile.BadZipFile, ValueError, OSError):
+            continue
+    return mods
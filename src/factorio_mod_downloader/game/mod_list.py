@@ -0,0 +1,291 @@
+"""
+Reading and writing of Factorio's mod-list.json file.
+"""
+
+import json
+import os
+import re
+from collections import deque
+from pathlib import Path
+from typing import Dict
+from typing import Iterable
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+from factorio_mod_downloader.downloader.changes import ChangeKind
+from factorio_mod_downloader.downloader.changes import PlannedChange
+from factorio_mod_downloader.downloader.dependencies import Dependency
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+from factorio_mod_downloader.downloader.dependencies import parse_dependencies
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.lock import DirectoryLock
+from factorio_mod_downloader.game.backup import backup_mods_dir
+from factorio_mod_downloader.game.installed import InstalledMod
+from factorio_mod_downloader.game.installed import scan_mods_dir
+
+
+MOD_LIST_FILE = "mod-list.json"
+BASE_MOD = "base"
+
+
+def read_mod_list(mods_dir: str) -> List[dict]:
+    """
+    Read the entries of mod-list.json.
+
+    Args:
+        mods_dir: Factorio mods directory
+
+    Returns:
+        List of {"name": ..., "enabled": ...} entries, only "base" if the file does not exist
+    """
+    path = Path(mods_dir) / MOD_LIST_FILE
+    if not path.is_file():
+        return [{"name": BASE_MOD, "enabled": True}]
+
+    with open(path, encoding="utf-8") as file:
+        return json.load(file).get("mods", [])
+
+
+def _read_document(mods_dir: str) -> Tuple[dict, Optional[int]]:
+    """The whole mod-list.json and its indentation, None if it is on a single line."""
+    path = Path(mods_dir) / MOD_LIST_FILE
+    if not path.is_file():
+        return {"mods": [{"name": BASE_MOD, "enabled": True}]}, 2
+
+    content = path.read_text(encoding="utf-8")
+    match = re.search(r"\n( +)\S", content)
+    return json.loads(content), len(match.group(1)) if match else None
+
+
+def _write_document(mods_dir: str, document: dict, indent: Optional[int] = 2):
+    with DirectoryLock(mods_dir):
+        backup_mods_dir(mods_dir)
+        path = Path(mods_dir) / MOD_LIST_FILE
+
+        temp_path = path.with_name(path.name + ".tmp")
+        with open(temp_path, "w", encoding="utf-8") as file:
+            json.dump(document, file, indent=indent)
+            file.write("\n")
+
+        os.replace(temp_path, path)
+
+
+def write_mod_list(mods_dir: str, mods: List[dict]):
+    """
+    Write entries to mod-list.json, backing up the mods directory first.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mods: List of {"name": ..., "enabled": ...} entries
+    """
+    _write_document(mods_dir, {"mods": mods})
+
+
+def _installed_dependencies(mods_dir: str) -> Dict[str, List[Dependency]]:
+    """Dependencies of the newest installed version of each mod, by the name in its info.json."""
+    newest: Dict[str, InstalledMod] = {}
+    for mod in scan_mods_dir(mods_dir):
+        current = newest.get(mod.name)
+        if not current or parse_version(mod.version) > parse_version(current.version):
+            newest[mod.name] = mod
+
+    dependencies: Dict[str, List[Dependency]] = {}
+    for name, mod in newest.items():
+        dependencies[name] = parse_dependencies(mod.dependencies)
+    return dependencies
+
+
+def _with_dependencies(
+    mods: Dict[str, bool], installed: Dict[str, List[Dependency]]
+) -> Dict[str, bool]:
+    changes = dict(mods)
+    queue = deque(name for name, enabled in mods.items() if enabled)
+    while queue:
+        for dependency in installed.get(queue.popleft(), []):
+            if (
+                dependency.is_required
+                and dependency.name in installed
+                and not changes.get(dependency.name)
+            ):
+                changes[dependency.name] = True
+                queue.append(dependency.name)
+    return changes
+
+
+def _without_incompatible(
+    mods: Dict[str, bool], installed: Dict[str, List[Dependency]]
+) -> Dict[str, bool]:
+    changes = dict(mods)
+    enabled = {name for name, state in mods.items() if state}
+    for name, dependencies in installed.items():
+        for dependency in dependencies:
+            if dependency.kind != DependencyKind.INCOMPATIBLE:
+                continue
+            # Incompatibilities go both ways, whichever of the two mods declares it
+            if name in enabled and dependency.name not in enabled:
+                changes[dependency.name] = False
+            elif dependency.name in enabled and name not in enabled:
+                changes[name] = False
+    if changes.get(BASE_MOD) is False:
+        del changes[BASE_MOD]
+    return changes
+
+
+def mod_list_changes(mods_dir: str, mods: Dict[str, bool]) -> List[PlannedChange]:
+    """
+    Compare enabled states with mod-list.json.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mods: Mapping of mod name to whether it should be enabled
+
+    Returns:
+        Entries that are missing or in another state, as MOD_LIST changes
+    """
+    path = str(Path(mods_dir) / MOD_LIST_FILE)
+    current = {entry["name"]: entry.get("enabled", True) for entry in read_mod_list(mods_dir)}
+    return [
+        PlannedChange(ChangeKind.MOD_LIST, path, name, action="enable" if enabled else "disable")
+        for name, enabled in mods.items()
+        if current.get(name) != enabled
+    ]
+
+
+def update_mod_list_json(
+    mods_dir: str,
+    mods: Dict[str, bool],
+    include_dependencies: bool = False,
+    disable_incompatible: bool = False,
+    dry_run: bool = False,
+) -> Dict[str, bool]:
+    """
+    Add mods to mod-list.json or update their enabled state.
+
+    Entries keep their order and every field besides "enabled", e.g. a
+    pinned version, and the file keeps its indentation. Dependencies are
+    looked up by the name in each zip's info.json, never by splitting file
+    names, so names containing underscores like Squeak_Through are safe.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mods: Mapping of mod name to whether it should be enabled
+        include_dependencies: Also enable the required dependencies of the
+            enabled mods that are installed in the mods directory
+        disable_incompatible: Also disable the installed mods incompatible
+            with the enabled mods
+        dry_run: Only work out the states, leave the file untouched
+
+    Returns:
+        Every mod added or updated, with its enabled state
+    """
+    if include_dependencies or disable_incompatible:
+        installed = _installed_dependencies(mods_dir)
+        if include_dependencies:
+            mods = _with_dependencies(mods, installed)
+        if disable_incompatible:
+            mods = _without_incompatible(mods, installed)
+    if dry_run:
+        return mods
+
+    document, indent = _read_document(mods_dir)
+    entries = document.setdefault("mods", [])
+    existing = {entry["name"]: entry for entry in entries}
+
+    for name, enabled in mods.items():
+        if name in existing:
+            existing[name]["enabled"] = enabled
+        else:
+            entries.append({"name": name, "enabled": enabled})
+
+    _write_document(mods_dir, document, indent)
+    return mods
+
+
+def _update(mods_dir: str, mods: Dict[str, bool], dry_run: bool) -> List[PlannedChange]:
+    changes = mod_list_changes(mods_dir, mods)
+    if not dry_run:
+        update_mod_list_json(mods_dir, mods)
+    return changes
+
+
+def enable_mods(
+    mods_dir: str, mod_names: Iterable[str], dry_run: bool = False
+) -> List[PlannedChange]:
+    """
+    Enable mods in mod-list.json, adding entries for mods not listed yet.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mod_names: Names of the mods to enable
+        dry_run: Only report the changes, leave the file untouched
+
+    Returns:
+        Changes to mod-list.json
+    """
+    return _update(mods_dir, {name: True for name in mod_names}, dry_run)
+
+
+def disable_mods(
+    mods_dir: str, mod_names: Iterable[str], dry_run: bool = False
+) -> List[PlannedChange]:
+    """
+    Disable mods in mod-list.json. The base mod is never disabled.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mod_names: Names of the mods to disable
+        dry_run: Only report the changes, leave the file untouched
+
+    Returns:
+        Changes to mod-list.json
+    """
+    return _update(mods_dir, {name: False for name in mod_names if name != BASE_MOD}, dry_run)
+
+
+def remove_mods(
+    mods_dir: str, mod_names: Iterable[str], dry_run: bool = False
+) -> List[PlannedChange]:
+    """
+    Remove mods from mod-list.json. The base mod is never removed.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mod_names: Names of the mods to remove
+        dry_run: Only report the changes, leave the file untouched
+
+    Returns:
+        Changes to mod-list.json
+    """
+    removed = set(mod_names) - {BASE_MOD}
+    entries = read_mod_list(mods_dir)
+    path = str(Path(mods_dir) / MOD_LIST_FILE)
+    changes = [
+        PlannedChange(ChangeKind.MOD_LIST, path, entry["name"], action="remove")
+        for entry in entries
+        if entry["name"] in removed
+    ]
+    if changes and not dry_run:
+        write_mod_list(mods_dir, [entry for entry in entries if entry["name"] not in removed])
+    return changes
+
+
+def enable_only(
+    mods_dir: str, mod_names: Iterable[str], dry_run: bool = False
+) -> List[PlannedChange]:
+    """
+    Enable exactly the given mods (and base) and disable every other mod.
+
+    Args:
+        mods_dir: Factorio mods directory
+        mod_names: Names of the mods to keep enabled
+        dry_run: Only report the changes, leave the file untouched
+
+    Returns:
+        Changes to mod-list.json
+    """
+    mod_names = list(mod_names)
+    enabled = set(mod_names) | {BASE_MOD}
+    mods = {entry["name"]: entry["name"] in enabled for entry in read_mod_list(mods_dir)}
+    mods.update({name: True for name in mod_names})
+    return _update(mods_dir, mods, dry_run)
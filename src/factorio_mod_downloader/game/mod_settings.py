@@ -0,0 +1,222 @@
+"""
+Reading and writing of Factorio's binary mod-settings.dat file.
+
+The file starts with the game version that wrote it, followed by a property
+tree holding the "startup", "runtime-global" and "runtime-per-user" settings.
+"""
+
+import os
+import struct
+from pathlib import Path
+from typing import Any
+from typing import BinaryIO
+from typing import Final
+from typing import Optional
+from typing import Tuple
+
+
+# Property tree types
+NONE: Final = 0
+BOOL: Final = 1
+NUMBER: Final = 2
+STRING: Final = 3
+LIST: Final = 4
+DICTIONARY: Final = 5
+SIGNED_INTEGER: Final = 6
+UNSIGNED_INTEGER: Final = 7
+
+# Version written when creating a new file
+DEFAULT_VERSION: Final = (2, 0, 0, 0)
+
+
+class ModSettingsError(Exception):
+    """Raised when mod-settings.dat is malformed."""
+
+
+class _Reader:
+    def __init__(self, stream: BinaryIO):
+        self.stream = stream
+
+    def read(self, fmt: str):
+        size = struct.calcsize(fmt)
+        data = self.stream.read(size)
+        if len(data) != size:
+            raise ModSettingsError("Unexpected end of file")
+        return struct.unpack(fmt, data)[0]
+
+    def read_bool(self) -> bool:
+        return self.read("<?")
+
+    def read_count(self) -> int:
+        # Space optimized uint32: one byte, or 255 followed by the full value
+        count = self.read("<B")
+        return self.read("<I") if count == 255 else count
+
+    def read_string(self) -> str:
+        if self.read_bool():
+            return ""
+        length = self.read_count()
+        data = self.stream.read(length)
+        if len(data) != length:
+            raise ModSettingsError("Unexpected end of file")
+        return data.decode("utf-8")
+
+    def read_tree(self) -> Any:
+        tree_type = self.read("<B")
+        self.read_bool()  # any-type flag, irrelevant outside of the game
+
+        if tree_type == NONE:
+            return None
+        if tree_type == BOOL:
+            return self.read_bool()
+        if tree_type == NUMBER:
+            return self.read("<d")
+        if tree_type == STRING:
+            return self.read_string()
+        if tree_type == LIST:
+            items = []
+            for _ in range(self.read("<I")):
+                self.read_string()  # list items carry an empty key
+                items.append(self.read_tree())
+            return items
+        if tree_type == DICTIONARY:
+            items = {}
+            for _ in range(self.read("<I")):
+                key = self.read_string()
+                items[key] = self.read_tree()
+            return items
+        if tree_type == SIGNED_INTEGER:
+            return self.read("<q")
+        if tree_type == UNSIGNED_INTEGER:
+            return self.read("<Q")
+
+        raise ModSettingsError(f"Unknown property tree type {tree_type}")
+
+
+class _Writer:
+    def __init__(self, stream: BinaryIO, version: Tuple[int, int, int, int]):
+        self.stream = stream
+        # Integer types only exist since Factorio 2.0, older versions store doubles
+        self.integers = version >= (2, 0, 0, 0)
+
+    def write(self, fmt: str, value):
+        self.stream.write(struct.pack(fmt, value))
+
+    def write_count(self, count: int):
+        if count < 255:
+            self.write("<B", count)
+        else:
+            self.write("<B", 255)
+            self.write("<I", count)
+
+    def write_string(self, value: str):
+        self.write("<?", not value)
+        if value:
+            data = value.encode("utf-8")
+            self.write_count(len(data))
+            self.stream.write(data)
+
+    def write_tree(self, value: Any):
+        if value is None:
+            self._write_header(NONE)
+        elif isinstance(value, bool):
+            self._write_header(BOOL)
+            self.write("<?", value)
+        elif isinstance(value, int) and self.integers:
+            self._write_header(SIGNED_INTEGER)
+            self.write("<q", value)
+        elif isinstance(value, (int, float)):
+            self._write_header(NUMBER)
+            self.write("<d", float(value))
+        elif isinstance(value, str):
+            self._write_header(STRING)
+            self.write_string(value)
+        elif isinstance(value, (list, tuple)):
+            self._write_header(LIST)
+            self.write("<I", len(value))
+            for item in value:
+                self.write_string("")
+                self.write_tree(item)
+        elif isinstance(value, dict):
+            self._write_header(DICTIONARY)
+            self.write("<I", len(value))
+            for key, item in value.items():
+                self.write_string(str(key))
+                self.write_tree(item)
+        else:
+            raise ModSettingsError(f"Cannot store {type(value).__name__} in mod-settings.dat")
+
+    def _write_header(self, tree_type: int):
+        self.write("<B", tree_type)
+        self.write("<?", False)
+
+
+def read_mod_settings_version(path: str) -> Tuple[int, int, int, int]:
+    """
+    Read the game version that wrote a mod-settings.dat file.
+
+    Args:
+        path: Path to mod-settings.dat
+
+    Returns:
+        Version as (major, minor, patch, build)
+    """
+    with open(path, "rb") as file:
+        data = file.read(8)
+    if len(data) != 8:
+        raise ModSettingsError("Unexpected end of file")
+    return struct.unpack("<HHHH", data)
+
+
+def read_mod_settings(path: str) -> dict:
+    """
+    Parse a mod-settings.dat file.
+
+    Args:
+        path: Path to mod-settings.dat
+
+    Returns:
+        Settings keyed by scope ("startup", "runtime-global", "runtime-per-user"),
+        each mapping a setting name to a dict holding its "value"
+
+    Raises:
+        ModSettingsError: If the file is malformed
+    """
+    with open(path, "rb") as file:
+        reader = _Reader(file)
+        for _ in range(4):
+            reader.read("<H")
+        reader.read_bool()  # reserved, always false
+        settings = reader.read_tree()
+
+    if not isinstance(settings, dict):
+        raise ModSettingsError("mod-settings.dat does not contain a dictionary")
+    return settings
+
+
+def write_mod_settings(
+    path: str, settings: dict, version: Optional[Tuple[int, int, int, int]] = None
+):
+    """
+    Serialize settings into a mod-settings.dat file.
+
+    Args:
+        path: Path to mod-settings.dat
+        settings: Settings in the form returned by read_mod_settings
+        version: Game version to write in the header, defaults to the version
+            of the existing file or DEFAULT_VERSION for new files
+    """
+    path = Path(path)
+    if version is None:
+        version = read_mod_settings_version(path) if path.is_file() else DEFAULT_VERSION
+
+    # Write next to the original first so a failure never leaves a truncated file
+    temp_path = path.with_name(path.name + ".tmp")
+    with open(temp_path, "wb") as file:
+        writer = _Writer(file, tuple(version))
+        for part in version:
+            writer.write("<H", part)
+        writer.write("<?", False)
+        writer.write_tree(settings)
+
+    os.replace(temp_path, path)
@@ -0,0 +1,664 @@
+"""
+Modpack files describing a deployable set of mods with their configuration.
+
+A modpack file is a JSON document of the form:
+
+    {
+        "mods": ["https://mods.factorio.com/mod/Krastorio2", "flib"],
+        "settings": {
+            "startup": {"kr-loaders": false},
+            "runtime-global": {},
+            "runtime-per-user": {}
+        },
+        "startup": {"Krastorio2": true, "quality": false}
+    }
+
+"settings" is applied to mod-settings.dat and "startup" to mod-list.json.
+Both sections are optional.
+
+Mods can also be listed in a plain text file, one mod portal URL or mod name
+per line, optionally pinned to a version with `name@version`:
+
+    # Base of the pack
+    https://mods.factorio.com/mod/Krastorio2
+    flib@0.14.0  # pinned for compatibility
+
+Pins work the same in the "mods" list of a modpack JSON file. Entries of
+that list can also be objects giving options for a single mod, every field
+but "name" being optional:
+
+    {
+        "format": 2,
+        "mods": [
+            "flib",
+            {
+                "name": "Krastorio2",
+                "version": "1.3.24",
+                "include_optional": true,
+                "enabled": true,
+                "required": false
+            }
+        ]
+    }
+
+"include_optional" decides whether the mod's optional dependencies are
+downloaded, in place of the global options. "enabled" is the mod's state in
+mod-list.json, which the startup section overrides. A mod that is not
+"required" is skipped when it cannot be downloaded, rather than failing an
+atomic download. "released_before" only selects releases published before a
+date, e.g. "2024-01-01", to recreate an earlier state of the pack, and
+"skip_newest" passes over that many of the newest releases, 1 rolling the
+mod back to its previous release. "format" is 2 for files with such entries, 1 otherwise.
+
+The same document can be written in YAML or TOML, e.g. in TOML:
+
+    mods = ["flib", { name = "Krastorio2", version = "1.3.24" }]
+
+    [settings.startup]
+    kr-loaders = false
+
+YAML files need PyYAML installed.
+
+Exported modpacks can carry an "integrity" section: the sha256 of the rest
+of the document, checked whenever the file is loaded, and optionally an
+ed25519 signature of it, checked when a public key is given to load_modpack:
+
+    "integrity": {"sha256": "9f2c...", "signature": "base64..."}
+
+Keys are base64, either the raw 32 bytes or, for public keys, a minisign
+public key. Signing and checking signatures need the cryptography package.
+
+save_modpack writes canonical JSON: keys and mods sorted, LF line endings on
+every platform. Saving the same modpack twice gives identical bytes, so
+modpacks pinning every mod serve as lockfiles that diff cleanly in git.
+"""
+
+import base64
+import binascii
+import hashlib
+import json
+import os
+import re
+import sys
+import tomllib
+from dataclasses import asdict
+from dataclasses import dataclass
+from dataclasses import field
+from dataclasses import replace
+from pathlib import Path
+from typing import Any
+from typing import Dict
+from typing import List
+from typing import Optional
+from typing import Tuple
+
+from factorio_mod_downloader.downloader.changes import ChangeKind
+from factorio_mod_downloader.downloader.changes import PlannedChange
+from factorio_mod_downloader.downloader.helpers import parse_date
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.game.installed import scan_mods_dir
+from factorio_mod_downloader.game.mod_list import mod_list_changes
+from factorio_mod_downloader.game.mod_list import read_mod_list
+from factorio_mod_downloader.game.mod_list import update_mod_list_json
+from factorio_mod_downloader.game.mod_settings import read_mod_settings
+from factorio_mod_downloader.game.mod_settings import write_mod_settings
+
+
+MOD_SETTINGS_FILE = "mod-settings.dat"
+SETTING_SCOPES = ("startup", "runtime-global", "runtime-per-user")
+MOD_LIST_FORMATS = ("json", "yaml", "toml", "text")
+# Format of a modpack file by extension, others are guessed from the content
+EXTENSION_FORMATS = {
+    ".json": "json",
+    ".yaml": "yaml",
+    ".yml": "yaml",
+    ".toml": "toml",
+    ".txt": "text",
+}
+# Newest version of the modpack JSON format, see the module documentation
+MODPACK_FORMAT = 2
+MOD_OPTION_FIELDS = ("include_optional", "enabled", "required", "released_before", "skip_newest")
+# Options of MOD_OPTION_FIELDS that are true or false
+BOOLEAN_MOD_OPTIONS = ("include_optional", "enabled", "required")
+
+# A "#" at the start of a line or after whitespace starts a comment
+COMMENT_PATTERN = re.compile(r"(^|\s)#.*$")
+VERSION_PATTERN = re.compile(r"^\d+(?:\.\d+){1,2}$")
+# Top-level keys or tables of a modpack, telling TOML and YAML apart from text lists
+TOML_PATTERN = re.compile(
+    r"^\s*(?:(?:mods|settings|startup|format)\s*=|\[{1,2}\s*(?:mods|settings|startup)\b)",
+    re.MULTILINE,
+)
+YAML_PATTERN = re.compile(r"^(?:---\s*$|-\s|(?:mods|settings|startup|format)\s*:)", re.MULTILINE)
+
+
+class ModpackError(Exception):
+    """Raised when a modpack file is invalid."""
+
+
+@dataclass
+class ModOptions:
+    """Options of a single mod of a modpack."""
+
+    # Download the mod's optional dependencies, None to follow the global options
+    include_optional: Optional[bool] = None
+    # State in mod-list.json, None to enable the mod
+    enabled: Optional[bool] = None
+    # Whether an atomic download fails without the mod
+    required: bool = True
+    # Only select releases published before this ISO 8601 date or time, e.g. "2024-01-01"
+    released_before: Optional[str] = None
+    # Newer releases to pass over, 1 for the release before the newest
+    skip_newest: int = 0
+
+
+@dataclass
+class Modpack:
+    """Mods of a modpack together with their settings and enabled states."""
+
+    mods: List[str]
+    settings: Dict[str, Dict[str, Any]] = field(default_factory=dict)
+    startup: Dict[str, bool] = field(default_factory=dict)
+    # Options of the mods given as objects, by mod name
+    options: Dict[str, ModOptions] = field(default_factory=dict)
+    # "sha256" and "signature" of the file it was loaded from, see modpack_digest
+    integrity: Dict[str, str] = field(default_factory=dict)
+
+
+def parse_mod_spec(mod: str) -> Tuple[str, Optional[str]]:
+    """
+    Split a mod entry into the mod name and the version it is pinned to.
+
+    Args:
+        mod: Mod portal URL or plain mod name, optionally followed by `@version`
+
+    Returns:
+        Mod name and pinned version, None if the entry is not pinned
+
+    Raises:
+        ModpackError: If the pinned version is not a valid version
+    """
+    name, _, version = mod.rstrip("/").split("/")[-1].partition("@")
+    if not version:
+        return name, None
+
+    if not VERSION_PATTERN.match(version):
+        raise ModpackError(f"Invalid version {version!r} pinned for {name}")
+    return name, version
+
+
+def mod_name_from_url(mod: str) -> str:
+    """
+    Extract the mod name from a mod portal URL.
+
+    Args:
+        mod: Mod portal URL or plain mod name, optionally pinned with `@version`
+
+    Returns:
+        Mod name
+    """
+    return mod.rstrip("/").split("/")[-1].partition("@")[0]
+
+
+def _parse_text_mod_list(content: str) -> List[str]:
+    mods = []
+    for line in content.splitlines():
+        line = COMMENT_PATTERN.sub("", line).strip()
+        if line:
+            mods.append(line)
+    return mods
+
+
+def _parse_mod_entry(entry: Any, source: str) -> Tuple[str, Optional[ModOptions]]:
+    """A mods list entry as a mod spec, and its options if given as an object."""
+    if not isinstance(entry, dict):
+        return str(entry), None
+
+    unknown = set(entry) - {"name", "version", *MOD_OPTION_FIELDS}
+    if unknown:
+        raise ModpackError(f"{source}: unknown mod options {', '.join(sorted(unknown))}")
+    if not isinstance(entry.get("name"), str) or not entry["name"]:
+        raise ModpackError(f"{source}: mod entries given as objects need a name")
+    name = entry["name"]
+    for option in BOOLEAN_MOD_OPTIONS:
+        if option in entry and not isinstance(entry[option], bool):
+            raise ModpackError(f"{source}: {option} of {name} must be true or false")
+    released_before = entry.get("released_before")
+    if released_before is not None:
+        try:
+            parse_date(str(released_before))
+        except ValueError:
+            raise ModpackError(
+                f"{source}: released_before of {name} must be a date, e.g. 2024-01-01"
+            ) from None
+    skip_newest = entry.get("skip_newest", 0)
+    if isinstance(skip_newest, bool) or not isinstance(skip_newest, int) or skip_newest < 0:
+        raise ModpackError(f"{source}: skip_newest of {name} must be a number of releases")
+
+    version = entry.get("version")
+    if version is not None and not isinstance(version, str):
+        raise ModpackError(f"{source}: version of {name} must be a string")
+    options = ModOptions(
+        include_optional=entry.get("include_optional"),
+        enabled=entry.get("enabled"),
+        required=entry.get("required", True),
+        released_before=None if released_before is None else str(released_before),
+        skip_newest=skip_newest,
+    )
+    return f"{name}@{version}" if version else name, options
+
+
+def modpack_digest(modpack: Modpack) -> str:
+    """
+    Hash the contents of a modpack, whatever format it was written in.
+
+    Args:
+        modpack: Modpack to hash
+
+    Returns:
+        Hex encoded sha256 of the mods, settings, startup and options
+    """
+    document = {
+        "mods": modpack.mods,
+        "settings": modpack.settings,
+        "startup": modpack.startup,
+        "options": {name: asdict(options) for name, options in modpack.options.items()},
+    }
+    canonical = json.dumps(document, sort_keys=True, separators=(",", ":"))
+    return hashlib.sha256(canonical.encode("utf-8")).hexdigest()
+
+
+def canonical_json(document: Any) -> str:
+    """
+    Serialize a document identically on every run and platform.
+
+    Args:
+        document: JSON serializable document
+
+    Returns:
+        Indented JSON with sorted keys, ending with a newline
+    """
+    return json.dumps(document, indent=4, sort_keys=True, ensure_ascii=False) + "\n"
+
+
+def _sort_key(mod: str) -> Tuple[str, str]:
+    return mod_name_from_url(mod).casefold(), mod
+
+
+def _ed25519():
+    try:
+        from cryptography.hazmat.primitives.asymmetric import ed25519
+    except ImportError as e:
+        raise ModpackError(
+            "Modpack signatures require cryptography, install it with: pip install cryptography"
+        ) from e
+    return ed25519
+
+
+def _decode_key(key: str, what: str) -> bytes:
+    try:
+        raw = base64.b64decode(key, validate=True)
+    except binascii.Error as e:
+        raise ModpackError(f"The {what} is not valid base64") from e
+    # minisign keys are "Ed", an 8 byte key id, then the key itself
+    if len(raw) == 42 and raw[:2] == b"Ed":
+        raw = raw[10:]
+    if len(raw) != 32:
+        raise ModpackError(f"The {what} must be 32 bytes, got {len(raw)}")
+    return raw
+
+
+def generate_signing_key() -> Tuple[str, str]:
+    """
+    Create a key pair for signing modpacks.
+
+    Returns:
+        Private and public key, base64 encoded
+    """
+    ed25519 = _ed25519()
+    private_key = ed25519.Ed25519PrivateKey.generate()
+    private_bytes = private_key.private_bytes_raw()
+    public_bytes = private_key.public_key().public_bytes_raw()
+    return base64.b64encode(private_bytes).decode(), base64.b64encode(public_bytes).decode()
+
+
+def sign_modpack(modpack: Modpack, private_key: str) -> str:
+    """
+    Sign the contents of a modpack.
+
+    Args:
+        modpack: Modpack to sign
+        private_key: Private key from generate_signing_key
+
+    Returns:
+        Signature of the modpack's digest, base64 encoded
+    """
+    ed25519 = _ed25519()
+    key = ed25519.Ed25519PrivateKey.from_private_bytes(_decode_key(private_key, "private key"))
+    return base64.b64encode(key.sign(modpack_digest(modpack).encode("ascii"))).decode()
+
+
+def verify_modpack_signature(modpack: Modpack, public_key: str):
+    """
+    Check that a modpack was signed by the holder of a key.
+
+    Args:
+        modpack: Modpack as loaded, with its integrity section
+        public_key: Public key of the modpack's author
+
+    Raises:
+        ModpackError: If the modpack is unsigned or the signature does not match
+    """
+    signature = modpack.integrity.get("signature")
+    if not signature:
+        raise ModpackError("The modpack is not signed")
+
+    ed25519 = _ed25519()
+    from cryptography.exceptions import InvalidSignature
+
+    key = ed25519.Ed25519PublicKey.from_public_bytes(_decode_key(public_key, "public key"))
+    try:
+        key.verify(base64.b64decode(signature), modpack_digest(modpack).encode("ascii"))
+    except (InvalidSignature, binascii.Error) as e:
+        raise ModpackError(
+            "The modpack's signature does not match, it may have been tampered with"
+        ) from e
+
+
+def _check_pins(modpack: Modpack, source: str):
+    for mod in modpack.mods:
+        try:
+            parse_mod_spec(mod)
+        except ModpackError as e:
+            raise ModpackError(f"{source}: {e}") from e
+
+
+def _guess_format(content: str) -> str:
+    if TOML_PATTERN.search(content):
+        return "toml"
+    if content.lstrip().startswith(("{", "[")):
+        return "json"
+    if YAML_PATTERN.search(content):
+        return "yaml"
+    return "text"
+
+
+def _load_document(content: str, format_hint: str, source: str) -> Any:
+    if format_hint == "toml":
+        try:
+            return tomllib.loads(content)
+        except tomllib.TOMLDecodeError as e:
+            raise ModpackError(f"{source} is not valid TOML: {e}") from e
+
+    if format_hint == "yaml":
+        try:
+            import yaml
+        except ImportError as e:
+            raise ModpackError(
+                "YAML modpacks require PyYAML, install it with: pip install pyyaml"
+            ) from e
+        try:
+            return yaml.safe_load(content)
+        except yaml.YAMLError as e:
+            raise ModpackError(f"{source} is not valid YAML: {e}") from e
+
+    try:
+        return json.loads(content)
+    except ValueError as e:
+        raise ModpackError(f"{source} is not valid JSON: {e}") from e
+
+
+def parse_mod_list(
+    content: str, format_hint: Optional[str] = None, source: str = "<input>"
+) -> Modpack:
+    """
+    Parse a list of mods given as a modpack document or as plain text.
+
+    Args:
+        content: Contents of the list
+        format_hint: One of MOD_LIST_FORMATS, guessed from the content if None
+        source: Name of the list in error messages
+
+    Returns:
+        Parsed modpack, lists given as text have neither settings nor startup
+
+    Raises:
+        ModpackError: If the content is not a valid list of mods
+    """
+    if format_hint not in (None, *MOD_LIST_FORMATS):
+        raise ModpackError(f"Unknown format {format_hint!r}, expected one of {MOD_LIST_FORMATS}")
+
+    if format_hint is None:
+        format_hint = _guess_format(content)
+    if format_hint == "text":
+        modpack = Modpack(mods=_parse_text_mod_list(content))
+        _check_pins(modpack, source)
+        return modpack
+
+    data = _load_document(content, format_hint, source)
+
+    # A bare list is the mods section on its own
+    if isinstance(data, list):
+        data = {"mods": data}
+    if not isinstance(data, dict) or not isinstance(data.get("mods"), list):
+        raise ModpackError(f"{source} must contain a list of mods")
+
+    settings = data.get("settings", {})
+    if not isinstance(settings, dict) or any(
+        scope not in SETTING_SCOPES or not isinstance(values, dict)
+        for scope, values in settings.items()
+    ):
+        raise ModpackError(f"settings must map {', '.join(SETTING_SCOPES)} to settings")
+
+    startup = data.get("startup", {})
+    if not isinstance(startup, dict) or not all(
+        isinstance(enabled, bool) for enabled in startup.values()
+    ):
+        raise ModpackError("startup must map mod names to true or false")
+
+    format_version = data.get("format", 1)
+    if not isinstance(format_version, int) or not 1 <= format_version <= MODPACK_FORMAT:
+        raise ModpackError(
+            f"{source} has format {format_version!r}, this version reads formats 1 to "
+            f"{MODPACK_FORMAT}"
+        )
+
+    integrity = data.get("integrity", {})
+    if not isinstance(integrity, dict) or not all(
+        isinstance(value, str) for value in integrity.values()
+    ):
+        raise ModpackError("integrity must map sha256 and signature to strings")
+
+    modpack = Modpack(mods=[], settings=settings, startup=startup, integrity=integrity)
+    for entry in data["mods"]:
+        mod, options = _parse_mod_entry(entry, source)
+        modpack.mods.append(mod)
+        if options:
+            modpack.options[mod_name_from_url(mod)] = options
+    _check_pins(modpack, source)
+
+    if "sha256" in integrity and integrity["sha256"] != modpack_digest(modpack):
+        raise ModpackError(
+            f"{source} does not match its sha256, it was modified after it was exported"
+        )
+    return modpack
+
+
+def load_modpack(
+    path: str, format_hint: Optional[str] = None, public_key: Optional[str] = None
+) -> Modpack:
+    """
+    Load and validate a modpack file.
+
+    Args:
+        path: Path to a modpack JSON, YAML or TOML file or a text list of mods,
+            "-" reads stdin
+        format_hint: One of MOD_LIST_FORMATS, by default taken from the
+            file's extension or guessed from the content
+        public_key: Only accept the modpack if signed with this key's
+            private key, see verify_modpack_signature
+
+    Returns:
+        Parsed modpack
+
+    Raises:
+        ModpackError: If the file is not a valid modpack, was modified since
+            it was exported, or is not signed with the key
+    """
+    if path == "-":
+        modpack = parse_mod_list(sys.stdin.read(), format_hint, "stdin")
+    else:
+        format_hint = format_hint or EXTENSION_FORMATS.get(Path(path).suffix.lower())
+        with open(path, encoding="utf-8") as file:
+            modpack = parse_mod_list(file.read(), format_hint, path)
+
+    if public_key:
+        verify_modpack_signature(modpack, public_key)
+    return modpack
+
+
+def snapshot(mods_directory: str, include_settings: bool = True) -> Modpack:
+    """
+    Capture the state of a mods directory as a modpack pinning every mod.
+
+    Each mod is pinned to its newest installed version, named after its
+    zip's info.json. Disabled mods, built-in ones like quality included, are
+    recorded as disabled in the startup section, so applying the modpack
+    elsewhere reproduces the directory.
+
+    Args:
+        mods_directory: Factorio mods directory
+        include_settings: Also capture mod-settings.dat if there is one
+
+    Returns:
+        Modpack of the installed mods
+
+    Raises:
+        ModSettingsError: If mod-settings.dat is malformed
+    """
+    versions: Dict[str, str] = {}
+    for mod in scan_mods_dir(mods_directory):
+        current = versions.get(mod.name)
+        if not current or parse_version(mod.version) > parse_version(current):
+            versions[mod.name] = mod.version
+
+    startup = {
+        entry["name"]: False
+        for entry in read_mod_list(mods_directory)
+        if not entry.get("enabled", True)
+    }
+
+    settings = {}
+    settings_path = Path(mods_directory) / MOD_SETTINGS_FILE
+    if include_settings and settings_path.is_file():
+        for scope, values in read_mod_settings(settings_path).items():
+            if scope in SETTING_SCOPES and values:
+                settings[scope] = {name: setting["value"] for name, setting in values.items()}
+
+    return Modpack(
+        mods=[f"{name}@{version}" for name, version in sorted(versions.items())],
+        settings=settings,
+        startup=startup,
+    )
+
+
+def save_modpack(
+    modpack: Modpack, path: str, checksum: bool = False, private_key: Optional[str] = None
+):
+    """
+    Write a modpack JSON file, see load_modpack.
+
+    The file is canonical JSON with mods sorted by name, see canonical_json.
+
+    Args:
+        modpack: Modpack to write
+        path: Path of the file
+        checksum: Add the sha256 of the contents, checked on load
+        private_key: Also sign the contents with this key, see generate_signing_key
+    """
+    modpack = replace(modpack, mods=sorted(modpack.mods, key=_sort_key))
+    defaults = asdict(ModOptions())
+    mods: List[Any] = []
+    for mod in modpack.mods:
+        options = modpack.options.get(mod_name_from_url(mod))
+        if not options:
+            mods.append(mod)
+            continue
+        name, version = parse_mod_spec(mod)
+        entry: Dict[str, Any] = {"name": name}
+        if version:
+            entry["version"] = version
+        for option, value in asdict(options).items():
+            if value != defaults[option]:
+                entry[option] = value
+        mods.append(entry)
+
+    data: Dict[str, Any] = {"format": MODPACK_FORMAT} if modpack.options else {}
+    data["mods"] = mods
+    if modpack.settings:
+        data["settings"] = modpack.settings
+    if modpack.startup:
+        data["startup"] = modpack.startup
+    if checksum or private_key:
+        data["integrity"] = {"sha256": modpack_digest(modpack)}
+    if private_key:
+        data["integrity"]["signature"] = sign_modpack(modpack, private_key)
+
+    temp_path = Path(path).with_name(Path(path).name + ".tmp")
+    # newline="\n" keeps Windows from writing CRLF
+    with open(temp_path, "w", encoding="utf-8", newline="\n") as file:
+        file.write(canonical_json(data))
+    os.replace(temp_path, path)
+
+
+def apply_modpack_settings(
+    modpack: Modpack, mods_dir: str, dry_run: bool = False
+) -> List[PlannedChange]:
+    """
+    Apply the settings and startup sections of a modpack to a mods directory.
+
+    Settings are merged into mod-settings.dat and the modpack's mods are enabled
+    in mod-list.json, or set to their "enabled" option, with the startup
+    section overriding their enabled state.
+
+    Args:
+        modpack: Modpack to apply
+        mods_dir: Factorio mods directory
+        dry_run: Only report the changes, leave both files untouched
+
+    Returns:
+        Changes to mod-settings.dat and mod-list.json
+    """
+    enabled_mods = {mod_name_from_url(mod): True for mod in modpack.mods}
+    enabled_mods.update(
+        {
+            name: options.enabled
+            for name, options in modpack.options.items()
+            if options.enabled is not None
+        }
+    )
+    enabled_mods.update(modpack.startup)
+    changes = mod_list_changes(mods_dir, enabled_mods)
+    if modpack.settings:
+        settings_file = str(Path(mods_dir) / MOD_SETTINGS_FILE)
+        changes.insert(0, PlannedChange(ChangeKind.SETTINGS, settings_file))
+    if dry_run:
+        return changes
+
+    Path(mods_dir).mkdir(parents=True, exist_ok=True)
+
+    if modpack.settings:
+        settings_path = Path(mods_dir) / MOD_SETTINGS_FILE
+        mod_settings = read_mod_settings(settings_path) if settings_path.is_file() else {}
+
+        for scope, values in modpack.settings.items():
+            scope_settings = mod_settings.setdefault(scope, {})
+            for name, value in values.items():
+                scope_settings[name] = {"value": value}
+
+        for scope in SETTING_SCOPES:
+            mod_settings.setdefault(scope, {})
+        write_mod_settings(settings_path, mod_settings)
+
+    update_mod_list_json(mods_dir, enabled_mods)
+    return changes
@@ -0,0 +1,205 @@
+"""
+Named mod profiles that can be saved from and switched into a mods directory.
+"""
+
+import json
+import os
+import sys
+from dataclasses import asdict
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Callable
+from typing import Dict
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.portal import download_release
+from factorio_mod_downloader.game.installed import parse_mod_filename
+from factorio_mod_downloader.game.mod_list import enable_only
+from factorio_mod_downloader.game.mod_list import read_mod_list
+
+
+PROFILES_FILE = "profiles.json"
+
+
+class ProfileError(Exception):
+    """Raised when a profile does not exist or cannot be applied."""
+
+
+@dataclass
+class ProfileMod:
+    """A mod of a profile, version is None for mods shipped with the game."""
+
+    name: str
+    version: Optional[str]
+    enabled: bool
+
+
+@dataclass
+class Profile:
+    """A named snapshot of the mods in a mods directory."""
+
+    name: str
+    mods: List[ProfileMod]
+
+
+def default_profiles_path() -> Path:
+    """
+    Get the path of the profiles file in the user's configuration directory.
+
+    Returns:
+        Path to profiles.json
+    """
+    if sys.platform.startswith("win"):
+        base = Path(os.environ.get("APPDATA", Path.home() / "AppData" / "Roaming"))
+    else:
+        base = Path(os.environ.get("XDG_CONFIG_HOME", Path.home() / ".config"))
+    return base / "factorio-mod-downloader" / PROFILES_FILE
+
+
+def _installed_versions(mods_dir: str) -> Dict[str, str]:
+    """Map installed mod names to their version from the zip file names."""
+    versions = {}
+    for path in sorted(Path(mods_dir).glob("*.zip")):
+        try:
+            name, version = parse_mod_filename(path.name)
+        except ValueError:
+            continue
+        versions[name] = version
+    return versions
+
+
+def load_profiles(profiles_path: Optional[str] = None) -> Dict[str, Profile]:
+    """
+    Load all saved profiles.
+
+    Args:
+        profiles_path: Profiles file, defaults to default_profiles_path()
+
+    Returns:
+        Profiles keyed by name
+    """
+    path = Path(profiles_path) if profiles_path else default_profiles_path()
+    if not path.is_file():
+        return {}
+
+    with open(path, encoding="utf-8") as file:
+        data = json.load(file)
+
+    return {
+        name: Profile(name=name, mods=[ProfileMod(**mod) for mod in profile["mods"]])
+        for name, profile in data.get("profiles", {}).items()
+    }
+
+
+def _write_profiles(profiles: Dict[str, Profile], profiles_path: Optional[str]):
+    path = Path(profiles_path) if profiles_path else default_profiles_path()
+    path.parent.mkdir(parents=True, exist_ok=True)
+
+    data = {
+        "profiles": {
+            name: {"mods": [asdict(mod) for mod in profile.mods]}
+            for name, profile in profiles.items()
+        }
+    }
+    with open(path, "w", encoding="utf-8") as file:
+        json.dump(data, file, indent=2)
+        file.write("\n")
+
+
+def save_profile(name: str, mods_dir: str, profiles_path: Optional[str] = None) -> Profile:
+    """
+    Snapshot the mods of a mods directory and its mod-list.json under a name.
+
+    Args:
+        name: Profile name, an existing profile with the same name is replaced
+        mods_dir: Factorio mods directory
+        profiles_path: Profiles file, defaults to default_profiles_path()
+
+    Returns:
+        The saved profile
+    """
+    versions = _installed_versions(mods_dir)
+    mods = []
+
+    for entry in read_mod_list(mods_dir):
+        mods.append(
+            ProfileMod(
+                name=entry["name"],
+                version=versions.pop(entry["name"], None),
+                enabled=entry.get("enabled", True),
+            )
+        )
+
+    # Zips the game has not picked up yet are enabled by default
+    for mod_name, version in versions.items():
+        mods.append(ProfileMod(name=mod_name, version=version, enabled=True))
+
+    profile = Profile(name=name, mods=mods)
+    profiles = load_profiles(profiles_path)
+    profiles[name] = profile
+    _write_profiles(profiles, profiles_path)
+    return profile
+
+
+def delete_profile(name: str, profiles_path: Optional[str] = None):
+    """
+    Delete a saved profile.
+
+    Args:
+        name: Profile name
+        profiles_path: Profiles file, defaults to default_profiles_path()
+
+    Raises:
+        ProfileError: If the profile does not exist
+    """
+    profiles = load_profiles(profiles_path)
+    if name not in profiles:
+        raise ProfileError(f"Profile {name} does not exist")
+
+    del profiles[name]
+    _write_profiles(profiles, profiles_path)
+
+
+def switch_profile(
+    name: str,
+    mods_dir: str,
+    profiles_path: Optional[str] = None,
+    log: Optional[Callable[[str], None]] = None,
+) -> List[str]:
+    """
+    Switch a mods directory to a saved profile.
+
+    Downloads the profile's mods that are missing from the directory, then
+    enables exactly the profile's enabled mods in mod-list.json.
+
+    Args:
+        name: Profile name
+        mods_dir: Factorio mods directory
+        profiles_path: Profiles file, defaults to default_profiles_path()
+        log: Callback receiving progress messages, defaults to standard output
+
+    Returns:
+        Paths of the files that had to be downloaded
+
+    Raises:
+        ProfileError: If the profile does not exist
+    """
+    log = log or sys.stdout.write
+    profiles = load_profiles(profiles_path)
+    if name not in profiles:
+        raise ProfileError(f"Profile {name} does not exist")
+
+    profile = profiles[name]
+    downloaded = []
+
+    for mod in profile.mods:
+        if not mod.version or (Path(mods_dir) / f"{mod.name}_{mod.version}.zip").exists():
+            continue
+
+        log(f"Downloading {mod.name}_{mod.version}.zip.\n")
+        downloaded.append(download_release(mod.name, mod.version, mods_dir))
+
+    enable_only(mods_dir, [mod.name for mod in profile.mods if mod.enabled])
+    log(f"Switched to profile {name}.\n")
+    return downloaded
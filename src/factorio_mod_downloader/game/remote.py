@@ -0,0 +1,139 @@
+"""
+Upload of downloaded mods to the mods directory of a remote server over SFTP.
+
+Requires paramiko, which is only imported when uploading.
+"""
+
+import os
+import posixpath
+import shlex
+import sys
+from dataclasses import dataclass
+from dataclasses import field
+from typing import Callable
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+from factorio_mod_downloader.downloader.batch import ModEntry
+
+
+class RemoteDeployError(Exception):
+    """Raised when the remote server cannot be reached or written to."""
+
+
+@dataclass
+class RemoteDeployResult:
+    """Outcome of deploy_over_sftp, holding file names."""
+
+    uploaded: List[str] = field(default_factory=list)
+    # Files already present on the server with the same size and sha1
+    skipped: List[str] = field(default_factory=list)
+    errors: List[str] = field(default_factory=list)
+
+
+def _connect(host: str, port: int, username: Optional[str], key_filename: Optional[str]):
+    try:
+        import paramiko
+    except ImportError as e:
+        raise RemoteDeployError(
+            "SFTP deployment requires paramiko, install it with: pip install paramiko"
+        ) from e
+
+    ssh = paramiko.SSHClient()
+    ssh.load_system_host_keys()
+    # Unknown hosts are refused, add them to known_hosts with ssh first
+    ssh.set_missing_host_key_policy(paramiko.RejectPolicy())
+    try:
+        ssh.connect(host, port=port, username=username, key_filename=key_filename)
+    except (paramiko.SSHException, OSError) as e:
+        raise RemoteDeployError(f"Could not connect to {host}: {e}") from e
+    return ssh
+
+
+def _remote_sha1(ssh, path: str) -> Optional[str]:
+    """sha1 of a remote file computed by sha1sum, None if the server has no sha1sum."""
+    _, stdout, _ = ssh.exec_command(f"sha1sum {shlex.quote(path)}")
+    output = stdout.read().decode(errors="ignore").split()
+    return output[0] if stdout.channel.recv_exit_status() == 0 and output else None
+
+
+def _is_current(ssh, sftp, entry: ModEntry, remote_path: str) -> bool:
+    """Whether the remote file has the size and sha1 of the local one."""
+    try:
+        if sftp.stat(remote_path).st_size != entry.size_bytes:
+            return False
+    except OSError:
+        return False
+    return _remote_sha1(ssh, remote_path) == entry.sha1
+
+
+def deploy_over_sftp(
+    result: DownloadResult,
+    host: str,
+    remote_mods_dir: str,
+    username: Optional[str] = None,
+    key_filename: Optional[str] = None,
+    port: int = 22,
+    log: Optional[Callable[[str], None]] = None,
+) -> RemoteDeployResult:
+    """
+    Upload downloaded mod zips to a remote mods directory.
+
+    Files already on the server with a matching size and sha1 are skipped.
+    Uploads go to a `.part` file renamed once complete, so a server starting
+    mid-upload never sees a truncated zip.
+
+    Args:
+        result: Downloads to upload, they must not have been extracted
+        host: Server to connect to
+        remote_mods_dir: Mods directory on the server, created if missing
+        username: SSH user, defaults to the local user
+        key_filename: Private key to authenticate with, defaults to the SSH
+            agent and the usual keys in ~/.ssh
+        port: SSH port
+        log: Callback receiving progress messages, defaults to standard output
+
+    Returns:
+        Uploaded, skipped and failed files
+
+    Raises:
+        RemoteDeployError: If the server cannot be reached
+    """
+    log = log or sys.stdout.write
+    deploy_result = RemoteDeployResult()
+    ssh = _connect(host, port, username, key_filename)
+
+    try:
+        sftp = ssh.open_sftp()
+        try:
+            sftp.stat(remote_mods_dir)
+        except OSError:
+            sftp.mkdir(remote_mods_dir)
+
+        for entry in result.mods:
+            if not os.path.isfile(entry.path):
+                deploy_result.errors.append(f"{entry.file_name}: extracted mods cannot be uploaded")
+                continue
+
+            remote_path = posixpath.join(remote_mods_dir, entry.file_name)
+            try:
+                if _is_current(ssh, sftp, entry, remote_path):
+                    deploy_result.skipped.append(entry.file_name)
+                    log(f"Already on {host}: {entry.file_name}.\n")
+                    continue
+
+                temp_path = f"{remote_path}.part"
+                sftp.put(entry.path, temp_path)
+                sftp.posix_rename(temp_path, remote_path)
+            except OSError as e:
+                deploy_result.errors.append(f"{entry.file_name}: {e}")
+                log(f"Failed to upload {entry.file_name}: {e}\n")
+                continue
+
+            deploy_result.uploaded.append(entry.file_name)
+            log(f"Uploaded to {host}: {entry.file_name}.\n")
+    finally:
+        ssh.close()
+
+    return deploy_result
@@ -0,0 +1,293 @@
+"""
+Deployment of modpacks to Factorio headless servers.
+
+A headless server keeps its mods next to the game, in the `mods` folder of
+the directory the server archive was extracted to.
+
+Running this module prints the startup report of a server as JSON and exits
+with the report's exit code, see exit_codes.py, to gate server restarts in
+scripts:
+
+    python -m factorio_mod_downloader.game.server /opt/factorio
+"""
+
+import dataclasses
+import json
+import sys
+from dataclasses import dataclass
+from dataclasses import field
+from datetime import datetime
+from datetime import timezone
+from pathlib import Path
+from typing import Callable
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.exit_codes import EXIT_ERROR
+from factorio_mod_downloader.downloader.exit_codes import EXIT_SUCCESS
+from factorio_mod_downloader.downloader.exit_codes import EXIT_UNKNOWN_VERSION
+from factorio_mod_downloader.downloader.exit_codes import EXIT_VERIFICATION_FAILURE
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.transaction import Transaction
+from factorio_mod_downloader.game.installation import read_game_version
+from factorio_mod_downloader.game.installed import read_mod_info
+from factorio_mod_downloader.game.mod_list import enable_mods
+from factorio_mod_downloader.game.mod_settings import ModSettingsError
+from factorio_mod_downloader.game.mod_settings import read_mod_settings
+from factorio_mod_downloader.game.modpack import MOD_SETTINGS_FILE
+from factorio_mod_downloader.game.modpack import Modpack
+from factorio_mod_downloader.game.modpack import apply_modpack_settings
+from factorio_mod_downloader.game.verify import DependencyProblem
+from factorio_mod_downloader.game.verify import enabled_mods
+from factorio_mod_downloader.game.verify import verify_mods
+
+
+class ServerDeployError(Exception):
+    """Raised when a server cannot be deployed to."""
+
+
+@dataclass
+class DeployResult:
+    """Outcome of deploy_to_server."""
+
+    mods_dir: str
+    # Full version of the server, None if it could not be read
+    game_version: Optional[str]
+    download: DownloadResult
+    # Downloaded releases targeting another Factorio version than the server's
+    incompatible: List[str] = field(default_factory=list)
+
+    @property
+    def ok(self) -> bool:
+        """Whether every mod was installed and the server configured."""
+        return not (self.download.errors or self.incompatible)
+
+
+def deploy_to_server(
+    modpack: Modpack,
+    server_dir: str,
+    write_settings: bool = True,
+    check_compatibility: bool = True,
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+) -> DeployResult:
+    """
+    Install a modpack on a headless server and configure the server to load it.
+
+    Downloads the modpack's mods and their dependencies into the server's mods
+    directory, then enables them in mod-list.json and applies the modpack's
+    settings. The mods directory is restored, mod-list.json and mod-settings.dat
+    included, when a mod failed to download or is incompatible, so the server
+    keeps starting with its previous mods. A dry run reports the downloads and edits it leaves
+    out in the download's planned_changes.
+
+    Args:
+        modpack: Modpack to deploy
+        server_dir: Directory the headless server was extracted to
+        write_settings: Apply the modpack's settings to mod-settings.dat
+        check_compatibility: Select releases for the server's game version and
+            verify every downloaded release targets it
+        config: Resolution options, defaults to Config()
+        log: Callback receiving progress messages, defaults to standard output
+        client: Source of metadata and releases, defaults to the endpoints in config
+
+    Returns:
+        Installed mods and the problems found
+
+    Raises:
+        ServerDeployError: If the server's game version is needed but cannot be
+            read, or its mod-settings.dat cannot be read while there are settings
+            to apply
+    """
+    log = log or sys.stdout.write
+    server_dir = Path(server_dir)
+    mods_dir = server_dir / "mods"
+    config = config or Config()
+
+    game_version = read_game_version(server_dir)
+    if check_compatibility:
+        if not game_version:
+            raise ServerDeployError(f"Could not read the game version of {server_dir}")
+        # Releases must match the server, whatever version the config asks for
+        server_version = ".".join(game_version.split(".")[:2])
+        config = dataclasses.replace(config, factorio_version=server_version)
+        log(f"Deploying to Factorio {game_version} server at {server_dir}.\n")
+
+    if not write_settings:
+        modpack = dataclasses.replace(modpack, settings={})
+    settings_path = mods_dir / MOD_SETTINGS_FILE
+    if modpack.settings and settings_path.is_file():
+        # Found unreadable once the mods are downloaded, the deployment would be rolled back
+        try:
+            read_mod_settings(str(settings_path))
+        except ModSettingsError as e:
+            raise ServerDeployError(f"Could not read {settings_path}: {e}") from e
+
+    started_at = datetime.now(timezone.utc)
+    # Rather than an atomic download, incompatible releases are only found once downloaded
+    transaction = None if config.dry_run else Transaction(str(mods_dir))
+    try:
+        # Recorded once the server is configured as well
+        download = batch_download_mods(
+            modpack.mods,
+            str(mods_dir),
+            dataclasses.replace(config, keep_history=False),
+            log=log,
+            client=client,
+            mod_options=modpack.options,
+        )
+        result = DeployResult(str(mods_dir), game_version, download)
+
+        if check_compatibility:
+            for entry in download.mods:
+                factorio_version = read_mod_info(entry.path).get("factorio_version")
+                if factorio_version != server_version:
+                    result.incompatible.append(
+                        f"{entry.file_name} targets Factorio {factorio_version}, "
+                        f"the server runs {game_version}"
+                    )
+
+        if not result.ok:
+            for problem in download.errors + result.incompatible:
+                log(f"{problem}\n")
+            if transaction:
+                transaction.rollback()
+                download.rolled_back = True
+            log("Server mods and configuration left unchanged.\n")
+            download.record("deploy", str(mods_dir), config, started_at)
+            return result
+
+        if config.dry_run:
+            resolved = [name for name, version in download.graph.versions.items() if version]
+            changes = enable_mods(str(mods_dir), resolved, dry_run=True)
+            changes += apply_modpack_settings(modpack, str(mods_dir), dry_run=True)
+            # The modpack's mods are in both lists, the settings' state is the one applied
+            planned = {(change.kind, change.mod): change for change in changes}
+            for change in planned.values():
+                log(f"{change}.\n")
+            download.planned_changes.extend(planned.values())
+            download.record("deploy", str(mods_dir), config, started_at)
+            return result
+
+        enable_mods(str(mods_dir), [entry.name for entry in download.mods])
+        apply_modpack_settings(modpack, str(mods_dir))
+        transaction.commit()
+    except BaseException:
+        if transaction:
+            transaction.rollback()
+        raise
+
+    log(f"Deployed {len(download.mods)} mods to {mods_dir}.\n")
+    download.record("deploy", str(mods_dir), config, started_at)
+    return result
+
+
+@dataclass
+class ServerReport:
+    """Whether a server will start with all of its enabled mods loaded."""
+
+    server_dir: str
+    # Full version of the server, None if it could not be read
+    game_version: Optional[str]
+    # Enabled mods as {"name", "version", "factorio_version", "compatible"}
+    mods: List[dict] = field(default_factory=list)
+    # Enabled mods targeting another Factorio version than the server's
+    incompatible: List[str] = field(default_factory=list)
+    # Required dependencies that are not installed
+    missing: List[DependencyProblem] = field(default_factory=list)
+    # Required dependencies that are installed but disabled
+    disabled: List[DependencyProblem] = field(default_factory=list)
+    # Incompatible mods enabled together and unsatisfied versions
+    conflicts: List[DependencyProblem] = field(default_factory=list)
+    corrupted: List[str] = field(default_factory=list)
+
+    @property
+    def ok(self) -> bool:
+        """Whether the server will load every enabled mod."""
+        return not (
+            self.incompatible or self.missing or self.disabled or self.conflicts or self.corrupted
+        )
+
+    @property
+    def exit_code(self) -> int:
+        """EXIT_VERIFICATION_FAILURE, EXIT_UNKNOWN_VERSION if not checked, else EXIT_SUCCESS."""
+        if not self.ok:
+            return EXIT_VERIFICATION_FAILURE
+        if not self.game_version:
+            return EXIT_UNKNOWN_VERSION
+        return EXIT_SUCCESS
+
+    def to_dict(self) -> dict:
+        """Serializable form of the report."""
+        report = dataclasses.asdict(self)
+        report["ok"] = self.ok
+        report["exit_code"] = self.exit_code
+        return report
+
+
+def server_report(server_dir: str, check_checksums: bool = False) -> ServerReport:
+    """
+    Check whether a server will start with the mods in its mods directory.
+
+    Args:
+        server_dir: Directory the headless server was extracted to
+        check_checksums: Also compare the checksums of the zips with the portal
+
+    Returns:
+        Compatibility report of the enabled mods
+    """
+    server_dir = Path(server_dir)
+    mods_dir = str(server_dir / "mods")
+    game_version = read_game_version(server_dir)
+    server_version = ".".join(game_version.split(".")[:2]) if game_version else None
+
+    verification = verify_mods(mods_dir, check_checksums)
+    report = ServerReport(
+        str(server_dir),
+        game_version,
+        conflicts=verification.conflicts,
+        corrupted=verification.corrupted,
+    )
+
+    for problem in verification.missing:
+        if problem.reason == "disabled":
+            report.disabled.append(problem)
+        else:
+            report.missing.append(problem)
+
+    for mod in enabled_mods(mods_dir):
+        compatible = server_version is None or mod.factorio_version == server_version
+        report.mods.append(
+            {
+                "name": mod.name,
+                "version": mod.version,
+                "factorio_version": mod.factorio_version,
+                "compatible": compatible,
+            }
+        )
+        if not compatible:
+            report.incompatible.append(
+                f"{mod.path.name} targets Factorio {mod.factorio_version}, "
+                f"the server runs {game_version}"
+            )
+
+    return report
+
+
+def main():
+    """Print the startup report of the server given on the command line."""
+    if len(sys.argv) != 2:
+        sys.stderr.write("Usage: python -m factorio_mod_downloader.game.server <server_dir>\n")
+        sys.exit(EXIT_ERROR)
+
+    report = server_report(sys.argv[1])
+    sys.stdout.write(json.dumps(report.to_dict(), indent=2) + "\n")
+    sys.exit(report.exit_code)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,234 @@
+"""
+Auditing of an existing mods directory against the portal and dependencies.
+"""
+
+import zipfile
+from concurrent.futures import Executor
+from dataclasses import dataclass
+from dataclasses import field
+from pathlib import Path
+from typing import Collection
+from typing import Dict
+from typing import List
+from typing import Optional
+from typing import Set
+from typing import Tuple
+
+import requests
+
+from factorio_mod_downloader.downloader.dependencies import DLC_MODS
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+from factorio_mod_downloader.downloader.dependencies import parse_dependency
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.portal import get_mod_releases
+from factorio_mod_downloader.downloader.verification import verification_pool
+from factorio_mod_downloader.game.installation import target_builtin_mods
+from factorio_mod_downloader.game.installation import target_has_dlc
+from factorio_mod_downloader.game.installed import InstalledMod
+from factorio_mod_downloader.game.installed import load_installed_mod
+from factorio_mod_downloader.game.installed import sha1_file
+from factorio_mod_downloader.game.mod_list import BASE_MOD
+from factorio_mod_downloader.game.mod_list import read_mod_list
+
+
+@dataclass
+class DependencyProblem:
+    """A dependency of an enabled mod that prevents the game from loading it."""
+
+    mod: str
+    dependency: str
+    reason: str
+
+
+@dataclass
+class VerificationResult:
+    """Outcome of verify_mods."""
+
+    verified: List[str] = field(default_factory=list)
+    unverified: List[str] = field(default_factory=list)
+    corrupted: List[str] = field(default_factory=list)
+    missing: List[DependencyProblem] = field(default_factory=list)
+    conflicts: List[DependencyProblem] = field(default_factory=list)
+
+    @property
+    def ok(self) -> bool:
+        """Whether the mods directory is intact and loadable."""
+        return not (self.corrupted or self.missing or self.conflicts)
+
+
+def _releases(mod_name: str) -> List[dict]:
+    try:
+        return get_mod_releases(mod_name)
+    except requests.RequestException:
+        return []
+
+
+def _check_checksums(mods: List[InstalledMod], result: VerificationResult, executor: Executor):
+    names = sorted({mod.name for mod in mods})
+    releases = dict(zip(names, executor.map(_releases, names)))
+
+    expected: Dict[str, Optional[str]] = {}
+    for mod in mods:
+        release = next((r for r in releases[mod.name] if r.get("version") == mod.version), None)
+        expected[mod.path.name] = (release or {}).get("sha1")
+
+    hashed = [mod for mod in mods if expected[mod.path.name]]
+    hashes = executor.map(sha1_file, [mod.path for mod in hashed])
+    sha1s = {mod.path.name: sha1 for mod, sha1 in zip(hashed, hashes)}
+    for mod in mods:
+        file_name = mod.path.name
+        if not expected[file_name]:
+            result.unverified.append(file_name)
+        elif sha1s[file_name] == expected[file_name]:
+            result.verified.append(file_name)
+        else:
+            result.corrupted.append(file_name)
+
+
+def _load(path: Path) -> Optional[InstalledMod]:
+    try:
+        return load_installed_mod(path)
+    except (zipfile.BadZipFile, ValueError, OSError):
+        return None
+
+
+def _active_mods(
+    installed: List[InstalledMod], mods_directory: str
+) -> Tuple[Dict[str, InstalledMod], Set[str]]:
+    """The version of each mod the game loads, and the names of the enabled mods."""
+    mod_list = {entry["name"]: entry for entry in read_mod_list(mods_directory)}
+
+    # The game loads the version pinned in mod-list.json, or the newest one
+    active: Dict[str, InstalledMod] = {}
+    for mod in installed:
+        pinned = mod_list.get(mod.name, {}).get("version")
+        current = active.get(mod.name)
+        if pinned:
+            if mod.version == pinned:
+                active[mod.name] = mod
+        elif not current or parse_version(mod.version) > parse_version(current.version):
+            active[mod.name] = mod
+
+    # Mods missing from mod-list.json get enabled by the game on startup
+    enabled = {name for name, entry in mod_list.items() if entry.get("enabled", True)}
+    enabled |= {name for name in active if name not in mod_list}
+    enabled.add(BASE_MOD)
+    return active, enabled
+
+
+def enabled_mods(mods_directory: str) -> List[InstalledMod]:
+    """
+    List the mods the game loads from a mods directory.
+
+    Args:
+        mods_directory: Factorio mods directory
+
+    Returns:
+        The loaded version of every enabled mod, unreadable zips are left out
+    """
+    installed = []
+    for path in sorted(Path(mods_directory).glob("*.zip")):
+        try:
+            installed.append(load_installed_mod(path))
+        except (zipfile.BadZipFile, ValueError, OSError):
+            continue
+
+    active, enabled = _active_mods(installed, mods_directory)
+    return [mod for name, mod in active.items() if name in enabled]
+
+
+def verify_mods(
+    mods_directory: str,
+    check_checksums: bool = True,
+    builtin_mods: Optional[Collection[str]] = None,
+    has_dlc: Optional[bool] = None,
+    max_workers: Optional[int] = None,
+) -> VerificationResult:
+    """
+    Audit the mods installed in a mods directory.
+
+    Checks every zip's sha1 against the portal and validates that every
+    dependency of every enabled mod is present and enabled at a compatible
+    version, and that no incompatible mods are enabled together.
+
+    Args:
+        mods_directory: Factorio mods directory
+        check_checksums: Whether to compare checksums with the portal
+        builtin_mods: Mods shipped with the game, see target_builtin_mods
+        has_dlc: Whether the game has the Space Age DLC, see target_has_dlc
+        max_workers: Number of zips read and hashed at once, defaults to the
+            number of cores
+
+    Returns:
+        Structured verification result
+    """
+    builtin_mods = target_builtin_mods(mods_directory, builtin_mods)
+    if not target_has_dlc(mods_directory, has_dlc):
+        builtin_mods = builtin_mods - set(DLC_MODS)
+    result = VerificationResult()
+    installed: List[InstalledMod] = []
+
+    with verification_pool(max_workers) as executor:
+        paths = sorted(Path(mods_directory).glob("*.zip"))
+        for path, mod in zip(paths, executor.map(_load, paths)):
+            if mod:
+                installed.append(mod)
+            else:
+                result.corrupted.append(path.name)
+
+        if check_checksums:
+            _check_checksums(installed, result, executor)
+
+    active, enabled = _active_mods(installed, mods_directory)
+
+    for mod in active.values():
+        if mod.name not in enabled:
+            continue
+
+        for dependency_string in mod.dependencies:
+            try:
+                dependency = parse_dependency(dependency_string)
+            except ValueError:
+                result.conflicts.append(
+                    DependencyProblem(mod.name, dependency_string, "invalid dependency")
+                )
+                continue
+
+            is_enabled = dependency.name in enabled
+            is_builtin = dependency.name in builtin_mods
+            is_installed = is_builtin or dependency.name in active
+
+            if dependency.kind == DependencyKind.INCOMPATIBLE:
+                if is_enabled and is_installed:
+                    result.conflicts.append(
+                        DependencyProblem(mod.name, dependency_string, "incompatible mod enabled")
+                    )
+                continue
+
+            if not is_installed:
+                if dependency.is_required:
+                    reason = "not installed"
+                    if dependency.name in DLC_MODS:
+                        reason = "requires the Space Age DLC"
+                    result.missing.append(DependencyProblem(mod.name, dependency_string, reason))
+                continue
+
+            if not is_enabled:
+                if dependency.is_required:
+                    result.missing.append(
+                        DependencyProblem(mod.name, dependency_string, "disabled")
+                    )
+                continue
+
+            if is_builtin:
+                continue
+
+            installed_version = active[dependency.name].version
+            if not dependency.is_satisfied_by(installed_version):
+                result.conflicts.append(
+                    DependencyProblem(
+                        mod.name, dependency_string, f"version {installed_version} installed"
+                    )
+                )
+
+    return result
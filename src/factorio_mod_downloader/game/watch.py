@@ -0,0 +1,293 @@
+"""
+Periodic checking of a mods directory for newer releases on the portal.
+
+Running this module watches a mods directory until interrupted:
+
+    python -m factorio_mod_downloader.game.watch /opt/factorio/mods --apply \\
+        --on-update "systemctl restart factorio"
+
+With --metrics-port or --metrics-file, the checks and updates are exposed as
+Prometheus metrics, see metrics.py.
+"""
+
+import dataclasses
+import os
+import subprocess
+import sys
+import threading
+from contextlib import nullcontext
+from dataclasses import dataclass
+from datetime import datetime
+from datetime import timezone
+from typing import Callable
+from typing import Dict
+from typing import Final
+from typing import List
+from typing import Optional
+
+import requests
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.changes import ChangeKind
+from factorio_mod_downloader.downloader.changes import PlannedChange
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.config_file import load_settings
+from factorio_mod_downloader.downloader.exit_codes import ArgumentParser
+from factorio_mod_downloader.downloader.exit_codes import exit_code_for_error
+from factorio_mod_downloader.downloader.helpers import parse_version
+from factorio_mod_downloader.downloader.lock import DirectoryLock
+from factorio_mod_downloader.downloader.lock import DirectoryLockedError
+from factorio_mod_downloader.downloader.metrics import Metrics
+from factorio_mod_downloader.downloader.metrics import serve_metrics
+from factorio_mod_downloader.downloader.notify import Notifier
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.downloader.portal import find_latest_release
+from factorio_mod_downloader.game.backup import backup_mods_dir
+from factorio_mod_downloader.game.installation import target_factorio_version
+from factorio_mod_downloader.game.installed import InstalledMod
+from factorio_mod_downloader.game.installed import scan_mods_dir
+
+
+DEFAULT_INTERVAL: Final = 3600  # seconds
+
+
+@dataclass
+class ModUpdate:
+    """A newer compatible release of an installed mod."""
+
+    name: str
+    installed_version: str
+    latest_version: str
+
+
+def find_updates(
+    mods_dir: str,
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+) -> List[ModUpdate]:
+    """
+    Compare the newest installed version of every mod with the portal.
+
+    Args:
+        mods_dir: Factorio mods directory
+        config: Portal options, defaults to Config()
+        log: Callback receiving errors of mods that could not be checked, defaults
+            to standard output
+        client: Source of metadata, defaults to the endpoints in config
+
+    Returns:
+        Available updates sorted by mod name
+    """
+    log = log or sys.stdout.write
+    config = config or Config()
+    client = client or PortalClient.from_config(config)
+    factorio_version = target_factorio_version(mods_dir, config.factorio_version, config.channel)
+
+    newest: Dict[str, InstalledMod] = {}
+    for mod in scan_mods_dir(mods_dir):
+        current = newest.get(mod.name)
+        if not current or parse_version(mod.version) > parse_version(current.version):
+            newest[mod.name] = mod
+
+    updates = []
+    for name in sorted(newest):
+        try:
+            latest = find_latest_release(client.get_mod_releases(name), factorio_version)
+        except requests.RequestException as e:
+            log(f"Could not check {name} for updates: {e}\n")
+            continue
+
+        installed_version = newest[name].version
+        if latest and parse_version(latest["version"]) > parse_version(installed_version):
+            updates.append(ModUpdate(name, installed_version, latest["version"]))
+    return updates
+
+
+def apply_updates(
+    updates: List[ModUpdate],
+    mods_dir: str,
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+) -> DownloadResult:
+    """
+    Download updated mods with their dependencies and remove the versions they replace.
+
+    The mods directory is backed up first, see restore_backup(). A dry run
+    reports the downloads and deletions in the result's planned_changes.
+
+    Args:
+        updates: Updates returned by find_updates
+        mods_dir: Factorio mods directory
+        config: Resolution options, defaults to those of the configuration files
+        log: Callback receiving progress messages, defaults to standard output
+        client: Source of metadata and releases, defaults to the endpoints in config
+
+    Returns:
+        Outcome of the download
+
+    Raises:
+        DirectoryLockedError: If another instance is running on the mods
+            directory, see config.lock_timeout
+    """
+    log = log or sys.stdout.write
+    config = config or load_settings().config
+    started_at = datetime.now(timezone.utc)
+    # A dry run touches nothing, not even the lock file
+    with nullcontext() if config.dry_run else DirectoryLock(mods_dir, config.lock_timeout):
+        if updates and not config.dry_run:
+            backup = backup_mods_dir(mods_dir)
+            if backup:
+                log(f"Backed up {mods_dir} to {backup.name}.\n")
+
+        # Recorded once the replaced releases are removed as well
+        result = batch_download_mods(
+            [update.name for update in updates],
+            mods_dir,
+            dataclasses.replace(config, keep_history=False),
+            log=log,
+            client=client,
+        )
+
+        updated = {entry.name: entry.version for entry in result.mods}
+        if config.dry_run:
+            updated = {name: version for name, version in result.graph.versions.items() if version}
+        for mod in scan_mods_dir(mods_dir):
+            if mod.name in updated and mod.version != updated[mod.name]:
+                if config.dry_run:
+                    change = PlannedChange(ChangeKind.DELETE, mod.path.name, mod.name, mod.version)
+                    result.planned_changes.append(change)
+                    log(f"{change}.\n")
+                    continue
+                os.remove(mod.path)
+                result.removed_files.append(mod.path.name)
+                log(f"Removed {mod.path.name}, replaced by version {updated[mod.name]}.\n")
+    result.record("update", mods_dir, config, started_at)
+    return result
+
+
+def watch_mods(
+    mods_dir: str,
+    interval: float = DEFAULT_INTERVAL,
+    apply: bool = False,
+    on_update: Optional[str] = None,
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+    stop: Optional[threading.Event] = None,
+    metrics: Optional[Metrics] = None,
+    metrics_file: Optional[str] = None,
+):
+    """
+    Check a mods directory for updates every interval until stopped.
+
+    Args:
+        mods_dir: Factorio mods directory
+        interval: Seconds between two checks
+        apply: Install updates instead of only reporting them
+        on_update: Shell command run after updates were installed, e.g. to
+            restart a server
+        config: Resolution options, defaults to Config()
+        log: Callback receiving progress messages, defaults to standard output
+        client: Source of metadata and releases, defaults to the endpoints in config
+        stop: Event ending the loop once set, runs forever if None
+        metrics: Metrics recording every check and update run
+        metrics_file: File the metrics are written to after every check, for
+            node_exporter's textfile collector
+    """
+    log = log or sys.stdout.write
+    config = config or Config()
+    client = client or PortalClient.from_config(config, log=log)
+    stop = stop or threading.Event()
+    notifier = Notifier.from_config(config, log)
+    notified: List[ModUpdate] = []
+    if metrics_file:
+        metrics = metrics or Metrics()
+
+    while not stop.is_set():
+        # Installing updates resolves the mods just checked, their details are fetched once
+        with client.memoized():
+            updates = find_updates(mods_dir, config, log, client)
+            if metrics:
+                metrics.record_check(len(updates))
+            for update in updates:
+                log(
+                    f"Update available: {update.name} {update.installed_version} -> "
+                    f"{update.latest_version}.\n"
+                )
+
+            # Only notify once of the same updates when they are not applied
+            if notifier and updates and updates != notified:
+                notifier.updates_available([dataclasses.asdict(update) for update in updates])
+            notified = updates
+
+            result = None
+            if updates and apply:
+                try:
+                    result = apply_updates(updates, mods_dir, config, log, client)
+                except DirectoryLockedError as e:
+                    log(f"Skipping the updates until the next check. {e}.\n")
+
+        if result:
+            if metrics:
+                metrics.record_run(result)
+            if result.errors:
+                log("Some updates failed, skipping the update command.\n")
+            elif on_update:
+                log(f"Running: {on_update}\n")
+                returncode = subprocess.run(on_update, shell=True, check=False).returncode
+                if returncode:
+                    log(f"Update command exited with code {returncode}.\n")
+        elif not updates:
+            log("All mods are up to date.\n")
+
+        if metrics_file:
+            metrics.write_textfile(metrics_file)
+        stop.wait(interval)
+
+
+def main():
+    """Watch the mods directory given on the command line."""
+    parser = ArgumentParser(description="Watch a Factorio mods directory for updates.")
+    parser.add_argument("mods_dir", help="Factorio mods directory")
+    parser.add_argument(
+        "--interval", type=float, default=DEFAULT_INTERVAL, help="Seconds between checks"
+    )
+    parser.add_argument("--apply", action="store_true", help="Install updates automatically")
+    parser.add_argument("--on-update", help="Command to run after installing updates")
+    parser.add_argument(
+        "--metrics-port", type=int, help="Port to serve Prometheus metrics on, see metrics.py"
+    )
+    parser.add_argument(
+        "--metrics-file",
+        help="File to write Prometheus metrics to for node_exporter, see metrics.py",
+    )
+    args = parser.parse_args()
+
+    metrics = Metrics() if args.metrics_port is not None or args.metrics_file else None
+    server = None
+    try:
+        if args.metrics_port is not None:
+            server = serve_metrics(metrics, args.metrics_port)
+        watch_mods(
+            args.mods_dir,
+            args.interval,
+            args.apply,
+            args.on_update,
+            metrics=metrics,
+            metrics_file=args.metrics_file,
+        )
+    except KeyboardInterrupt:
+        pass
+    except Exception as e:
+        sys.stderr.write(f"Error: {e}\n")
+        sys.exit(exit_code_for_error(e))
+    finally:
+        if server:
+            server.shutdown()
+
+
+if __name__ == "__main__":
+    main()
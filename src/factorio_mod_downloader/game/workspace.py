@@ -0,0 +1,242 @@
+"""
+Named targets pairing a mods directory with the Factorio version it is played with.
+
+Targets let installs of several game versions be managed side by side
+without repeating their mods directory and version every time. They are
+stored in workspace.json:
+
+    {
+        "targets": {
+            "stable-1.1": {"mods_dir": "/games/factorio-1.1/mods", "factorio_version": "1.1"},
+            "space-age-2.0": {"mods_dir": "/games/factorio-2.0/mods", "factorio_version": "2.0"}
+        }
+    }
+"""
+
+import dataclasses
+import json
+import os
+import re
+import sys
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Callable
+from typing import Dict
+from typing import List
+from typing import Optional
+
+from factorio_mod_downloader.downloader.batch import DownloadResult
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.portal import PortalClient
+from factorio_mod_downloader.game.profiles import default_profiles_path
+from factorio_mod_downloader.game.watch import ModUpdate
+from factorio_mod_downloader.game.watch import find_updates
+
+
+WORKSPACE_FILE = "workspace.json"
+FACTORIO_VERSION_PATTERN = re.compile(r"^\d+\.\d+$")
+
+
+class WorkspaceError(Exception):
+    """Raised when a target does not exist or is invalid."""
+
+
+@dataclass
+class Target:
+    """A mods directory and the Factorio version its mods are selected for."""
+
+    name: str
+    mods_dir: str
+    # "major.minor", None to detect it from the game owning the mods directory
+    factorio_version: Optional[str] = None
+
+    def config(self, config: Optional[Config] = None) -> Config:
+        """
+        Apply the target's Factorio version to resolution options.
+
+        Args:
+            config: Options to start from, defaults to Config()
+
+        Returns:
+            Copy of the options selecting releases for the target
+        """
+        config = config or Config()
+        if not self.factorio_version:
+            return config
+        return dataclasses.replace(config, factorio_version=self.factorio_version)
+
+
+def default_workspace_path() -> Path:
+    """
+    Get the path of the workspace file in the user's configuration directory.
+
+    Returns:
+        Path to workspace.json, next to the profiles file
+    """
+    return default_profiles_path().with_name(WORKSPACE_FILE)
+
+
+def load_targets(workspace_path: Optional[str] = None) -> Dict[str, Target]:
+    """
+    Load all targets of the workspace.
+
+    Args:
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Returns:
+        Targets keyed by name
+    """
+    path = Path(workspace_path) if workspace_path else default_workspace_path()
+    if not path.is_file():
+        return {}
+
+    with open(path, encoding="utf-8") as file:
+        data = json.load(file)
+
+    return {
+        name: Target(name=name, **target) for name, target in data.get("targets", {}).items()
+    }
+
+
+def _write_targets(targets: Dict[str, Target], workspace_path: Optional[str]):
+    path = Path(workspace_path) if workspace_path else default_workspace_path()
+    path.parent.mkdir(parents=True, exist_ok=True)
+
+    data = {
+        "targets": {
+            name: {"mods_dir": target.mods_dir, "factorio_version": target.factorio_version}
+            for name, target in targets.items()
+        }
+    }
+    temp_path = path.with_name(path.name + ".tmp")
+    with open(temp_path, "w", encoding="utf-8") as file:
+        json.dump(data, file, indent=2)
+        file.write("\n")
+    os.replace(temp_path, path)
+
+
+def get_target(name: str, workspace_path: Optional[str] = None) -> Target:
+    """
+    Look up a target by name.
+
+    Args:
+        name: Target name
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Returns:
+        The target
+
+    Raises:
+        WorkspaceError: If no target has this name
+    """
+    targets = load_targets(workspace_path)
+    if name not in targets:
+        available = ", ".join(sorted(targets)) or "none"
+        raise WorkspaceError(f"Target {name!r} does not exist, available targets: {available}")
+    return targets[name]
+
+
+def add_target(
+    name: str,
+    mods_dir: str,
+    factorio_version: Optional[str] = None,
+    workspace_path: Optional[str] = None,
+) -> Target:
+    """
+    Add a target to the workspace, replacing any target of the same name.
+
+    Args:
+        name: Target name
+        mods_dir: Mods directory of the target
+        factorio_version: "major.minor" version, None to detect it from the game
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Returns:
+        The added target
+
+    Raises:
+        WorkspaceError: If factorio_version is not of the form "major.minor"
+    """
+    if factorio_version and not FACTORIO_VERSION_PATTERN.match(factorio_version):
+        raise WorkspaceError(f"Factorio version must be major.minor, got {factorio_version!r}")
+
+    targets = load_targets(workspace_path)
+    target = Target(name, str(Path(mods_dir).expanduser().resolve()), factorio_version)
+    targets[name] = target
+    _write_targets(targets, workspace_path)
+    return target
+
+
+def remove_target(name: str, workspace_path: Optional[str] = None):
+    """
+    Remove a target from the workspace. Its mods directory is left untouched.
+
+    Args:
+        name: Target name
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Raises:
+        WorkspaceError: If no target has this name
+    """
+    targets = load_targets(workspace_path)
+    if name not in targets:
+        raise WorkspaceError(f"Target {name!r} does not exist")
+
+    del targets[name]
+    _write_targets(targets, workspace_path)
+
+
+def download_to_target(
+    name: str,
+    mod_urls: List[str],
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+    workspace_path: Optional[str] = None,
+) -> DownloadResult:
+    """
+    Download mods and their dependencies into a target, see batch_download_mods.
+
+    Args:
+        name: Target name
+        mod_urls: Mods to download
+        config: Resolution options, the target's Factorio version takes precedence
+        log: Callback receiving progress messages, defaults to standard output
+        client: Source of metadata and releases, defaults to the endpoints in config
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Returns:
+        Downloaded mods, errors and the dependency graph
+    """
+    log = log or sys.stdout.write
+    target = get_target(name, workspace_path)
+    return batch_download_mods(
+        mod_urls, target.mods_dir, target.config(config), log=log, client=client
+    )
+
+
+def target_updates(
+    name: str,
+    config: Optional[Config] = None,
+    log: Optional[Callable[[str], None]] = None,
+    client: Optional[PortalClient] = None,
+    workspace_path: Optional[str] = None,
+) -> List[ModUpdate]:
+    """
+    Check the mods of a target for updates, see find_updates.
+
+    Args:
+        name: Target name
+        config: Portal options, the target's Factorio version takes precedence
+        log: Callback receiving errors of mods that could not be checked, defaults
+            to standard output
+        client: Source of metadata, defaults to the endpoints in config
+        workspace_path: Workspace file, defaults to default_workspace_path()
+
+    Returns:
+        Available updates sorted by mod name
+    """
+    log = log or sys.stdout.write
+    target = get_target(name, workspace_path)
+    return find_updates(target.mods_dir, target.config(config), log, client)
@@ -21,7 +21,7 @@ class App(customtkinter.CTk):
         super().__init__()
         self.resizable(0, 0)
         self.title("Factorio Mod Downloader v0.3.0")
-        self.geometry(f"{1080}x{590}")
+        self.geometry(f"{1080}x{630}")
 
         try:
             self.iconbitmap(resource_path("factorio_downloader.ico"))
@@ -2,6 +2,7 @@
 GUI Components and Frames for the Factorio Mod Downloader.
 """
 
+import dataclasses
 import re
 import webbrowser
 from pathlib import Path
@@ -12,6 +13,9 @@ import customtkinter
 from CTkMessagebox import CTkMessagebox
 from PIL import Image
 
+from factorio_mod_downloader.downloader.config_file import ConfigFileError
+from factorio_mod_downloader.downloader.config_file import Settings
+from factorio_mod_downloader.downloader.config_file import load_settings
 from factorio_mod_downloader.gui.utils import resource_path
 
 
@@ -150,8 +154,23 @@ class BodyFrame(customtkinter.CTkFrame):
         self.frame_0.rowconfigure(5, weight=1)
 
         self.downloader_frame = downloader_frame
+        self.settings = self._load_settings()
         self._setup_ui()
 
+    def _load_settings(self) -> Settings:
+        """Read the defaults of the configuration files, ignoring invalid files."""
+        try:
+            return load_settings()
+        except (ConfigFileError, OSError) as e:
+            CTkMessagebox(
+                title="Warning",
+                width=500,
+                wraplength=500,
+                message=f"Ignoring configuration files.\n{e}",
+                icon="warning",
+            )
+            return Settings()
+
     def _setup_ui(self):
         """Initialize all UI components."""
         self._setup_title_frame()
@@ -216,6 +235,8 @@ class BodyFrame(customtkinter.CTkFrame):
             self.body_frame, placeholder_text="Download Path", width=500
         )
         self.download_path.grid(row=1, column=0, columnspan=3, padx=10, pady=(0, 10), sticky="nsew")
+        if self.settings.destination:
+            self.download_path.insert(0, self.settings.destination)
 
         self.path_button = customtkinter.CTkButton(
             master=self.body_frame,
@@ -258,6 +279,27 @@ class BodyFrame(customtkinter.CTkFrame):
         )
         text_label.pack(side="left", padx=(8, 0), fill="x", expand=True)
 
+        graph_container = customtkinter.CTkFrame(master=self.body_frame, fg_color="transparent")
+        graph_container.grid(row=3, column=0, columnspan=4, padx=10, pady=(0, 10), sticky="nsew")
+
+        graph_label = customtkinter.CTkLabel(
+            master=graph_container,
+            text="Save dependency graph:",
+            font=customtkinter.CTkFont(family="Segoe UI"),
+            anchor="w",
+        )
+        graph_label.pack(side="left")
+
+        self.graph_format = customtkinter.StringVar(value="None")
+        self.graph_format_menu = customtkinter.CTkOptionMenu(
+            master=graph_container,
+            values=["None", "DOT", "Mermaid"],
+            variable=self.graph_format,
+            width=120,
+            font=customtkinter.CTkFont(family="Segoe UI"),
+        )
+        self.graph_format_menu.pack(side="left", padx=(8, 0))
+
         self.download_button = customtkinter.CTkButton(
             master=self.body_frame,
             text="Start Download",
@@ -269,7 +311,7 @@ class BodyFrame(customtkinter.CTkFrame):
             text_color_disabled=["gray74", "gray60"],
         )
         self.download_button.grid(
-            row=3, column=0, columnspan=4, padx=10, pady=(0, 10), sticky="nsew"
+            row=4, column=0, columnspan=4, padx=10, pady=(0, 10), sticky="nsew"
         )
 
     def _setup_downloads_frame(self):
@@ -394,7 +436,10 @@ class BodyFrame(customtkinter.CTkFrame):
             # Import here to avoid circular imports
             from factorio_mod_downloader.downloader.mod_downloader import ModDownloader
 
-            mod_downloader = ModDownloader(mod_url, download_path, self)
+            config = dataclasses.replace(
+                self.settings.config, include_optional=self.optional_deps.get()
+            )
+            mod_downloader = ModDownloader(mod_url, download_path, self, config)
             mod_downloader.start()
 
         except Exception as e:
@@ -0,0 +1,111 @@
+"""
+Resolution and batch results do not depend on the order mods are listed in.
+"""
+
+import random
+import tempfile
+import unittest
+
+from factorio_mod_downloader.downloader import batch
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+
+
+# Dependency strings by mod, a diamond, a shared library and a missing mod
+GRAPH = {
+    "app": ["base", "left", "right", "? extras"],
+    "left": ["base", "core >= 1.1.0"],
+    "right": ["base", "core", "lib"],
+    "core": ["base", "lib"],
+    "lib": ["base"],
+    "extras": ["base", "lib"],
+    "tools": ["base", "lib", "broken"],
+    "broken": ["base", "gone"],
+    "flaky": ["base", "core"],
+}
+REQUESTED = ["app", "tools", "flaky"]
+
+
+def shuffled_client(seed: int) -> MockPortalClient:
+    """Mock serving GRAPH, with mods and their dependencies added in a shuffled order."""
+    rng = random.Random(seed)
+    client = MockPortalClient()
+    names = list(GRAPH)
+    rng.shuffle(names)
+    for name in names:
+        dependencies = list(GRAPH[name])
+        rng.shuffle(dependencies)
+        for version in ("1.0.0", "1.1.0"):
+            client.add_release(name, version, dependencies=dependencies)
+    # Fails every attempt, so the batch reports a download failure
+    client.failures["flaky"] = 1_000
+    return client
+
+
+class DeterminismTest(unittest.TestCase):
+    def setUp(self):
+        self.config = Config(factorio_version="2.0", keep_history=False)
+        # Retries of the failing download would otherwise wait between attempts
+        self.retry_delay = batch.RETRY_DELAY
+        batch.RETRY_DELAY = 0
+
+    def tearDown(self):
+        batch.RETRY_DELAY = self.retry_delay
+
+    def test_resolution_order(self):
+        results = []
+        for seed in (1, 2):
+            resolver = Resolver("2.0", self.config, client=shuffled_client(seed))
+            requested = list(REQUESTED)
+            random.Random(seed).shuffle(requested)
+            results.append(resolver.resolve(requested))
+
+        first, second = results
+        self.assertEqual(list(first.mods), list(second.mods))
+        self.assertEqual(
+            [mod.version for mod in first.mods.values()],
+            [mod.version for mod in second.mods.values()],
+        )
+        self.assertEqual(first.errors, second.errors)
+        # Dependencies come before the mods requiring them
+        order = list(first.mods)
+        self.assertLess(order.index("lib"), order.index("core"))
+        self.assertLess(order.index("core"), order.index("left"))
+        self.assertLess(order.index("left"), order.index("app"))
+
+    def test_download_result_order(self):
+        results = []
+        for seed in (1, 2):
+            requested = list(REQUESTED)
+            random.Random(seed).shuffle(requested)
+            with tempfile.TemporaryDirectory() as output_path:
+                results.append(
+                    batch_download_mods(
+                        requested,
+                        output_path,
+                        self.config,
+                        max_workers=4,
+                        log=lambda message: None,
+                        client=shuffled_client(seed),
+                    )
+                )
+
+        first, second = results
+        # Shuffled dependencies change the info.json, so the zips differ by their sha1
+        self.assertEqual(
+            [(entry.name, entry.version, entry.file_name) for entry in first.mods],
+            [(entry.name, entry.version, entry.file_name) for entry in second.mods],
+        )
+        self.assertEqual(first.resolution_failures, second.resolution_failures)
+        self.assertEqual(first.download_failures, second.download_failures)
+        self.assertEqual(first.skipped_mods, second.skipped_mods)
+        self.assertEqual(first.warnings, second.warnings)
+        self.assertEqual(first.errors, second.errors)
+        self.assertTrue(first.resolution_failures)
+        self.assertTrue(first.download_failures)
+
+
+if __name__ == "__main__":
+    unittest.main()
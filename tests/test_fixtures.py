@@ -0,0 +1,83 @@
+"""
+Resolution against the fixture corpus in tests/fixtures.
+
+fixtures/rails holds `/full` responses in the portal's format, as loaded by
+MockPortalClient.from_directory. rail-pack declares every kind of
+dependency: a load order one on gui-core ("~"), an optional one on
+rail-decor ("?"), a hidden optional one on rail-debug ("(?)") and an
+incompatibility with old-rails ("!"). rail-decor pins signal-lib ("=") to
+a release older than the one rail-pack would get otherwise.
+"""
+
+import os
+import tempfile
+import unittest
+
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.dependencies import DependencyKind
+from factorio_mod_downloader.downloader.graph import DependencyEdge
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+
+
+FIXTURE_DIR = os.path.join(os.path.dirname(__file__), "fixtures", "rails")
+
+
+class RailsFixtureTest(unittest.TestCase):
+    def setUp(self):
+        self.client = MockPortalClient.from_directory(FIXTURE_DIR)
+
+    def resolve(self, **options) -> dict:
+        config = Config(factorio_version="2.0", keep_history=False, **options)
+        result = Resolver("2.0", config, client=self.client).resolve(["rail-pack"])
+        self.assertEqual(result.errors, [])
+        self.assertEqual(result.cycles, [])
+        return {name: mod.version for name, mod in result.mods.items()}
+
+    def test_required_dependencies(self):
+        versions = self.resolve()
+
+        self.assertEqual(
+            versions, {"gui-core": "2.2.0", "signal-lib": "0.4.0", "rail-pack": "1.1.0"}
+        )
+
+    def test_optional_dependency_pinning_a_release(self):
+        versions = self.resolve(include_optional=True)
+
+        self.assertEqual(versions["rail-decor"], "1.0.0")
+        self.assertEqual(versions["signal-lib"], "0.3.0")
+        self.assertNotIn("rail-debug", versions)
+
+    def test_hidden_optional_dependency(self):
+        versions = self.resolve(include_hidden_optional=True)
+
+        self.assertEqual(versions["rail-debug"], "0.1.0")
+        self.assertNotIn("rail-decor", versions)
+
+    def test_graph_edges(self):
+        config = Config(factorio_version="2.0", keep_history=False)
+        graph = Resolver("2.0", config, client=self.client).resolve(["rail-pack"]).graph
+
+        load_order = DependencyEdge("rail-pack", "gui-core", DependencyKind.LOAD_ORDER)
+        required = DependencyEdge("rail-pack", "signal-lib", DependencyKind.REQUIRED)
+        self.assertIn(load_order, graph.edges)
+        self.assertIn(required, graph.edges)
+        self.assertNotIn("old-rails", graph.versions)
+
+    def test_download(self):
+        config = Config(factorio_version="2.0", keep_history=False, include_optional=True)
+        with tempfile.TemporaryDirectory() as output_path:
+            result = batch_download_mods(
+                ["rail-pack"], output_path, config, log=lambda message: None, client=self.client
+            )
+
+            self.assertEqual(result.errors, [])
+            self.assertEqual(
+                sorted(entry.file_name for entry in result.mods),
+                sorted(name for name in os.listdir(output_path) if name.endswith(".zip")),
+            )
+
+
+if __name__ == "__main__":
+    unittest.main()
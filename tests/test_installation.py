@@ -0,0 +1,67 @@
+"""
+Downloads into a user data directory whose game installation is not found.
+"""
+
+import tempfile
+import unittest
+from pathlib import Path
+from unittest import mock
+
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.dependencies import BUILTIN_MODS
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+from factorio_mod_downloader.game import installation
+from factorio_mod_downloader.game.installation import AUTO_FACTORIO_VERSION
+from factorio_mod_downloader.game.installation import FactorioInstallation
+from factorio_mod_downloader.game.installation import GameVersionError
+from factorio_mod_downloader.game.installation import target_builtin_mods
+from factorio_mod_downloader.game.installation import target_factorio_version
+from factorio_mod_downloader.game.installation import target_has_dlc
+
+
+class UserDataOnlyTest(unittest.TestCase):
+    def setUp(self):
+        directory = tempfile.TemporaryDirectory()
+        self.addCleanup(directory.cleanup)
+        # Like ~/.factorio on a machine the game is not detected on
+        user_data_dir = Path(directory.name) / ".factorio"
+        self.mods_dir = user_data_dir / "mods"
+        self.mods_dir.mkdir(parents=True)
+
+        detected = FactorioInstallation(
+            install_dir=None, user_data_dir=user_data_dir, mods_dir=self.mods_dir, version=None
+        )
+        patcher = mock.patch.object(installation, "detect_factorio", return_value=detected)
+        patcher.start()
+        self.addCleanup(patcher.stop)
+
+    def test_builtin_mods(self):
+        self.assertEqual(target_builtin_mods(str(self.mods_dir)), frozenset(BUILTIN_MODS))
+
+    def test_has_dlc(self):
+        self.assertTrue(target_has_dlc(str(self.mods_dir)))
+
+    def test_auto_factorio_version(self):
+        with self.assertRaises(GameVersionError) as raised:
+            target_factorio_version(str(self.mods_dir), AUTO_FACTORIO_VERSION)
+
+        message = str(raised.exception)
+        self.assertNotIn("None", message)
+        self.assertIn(str(self.mods_dir.parent), message)
+
+    def test_download(self):
+        client = MockPortalClient()
+        client.add_release("foo", "1.0.0")
+        config = Config(factorio_version="2.0", keep_history=False)
+
+        result = batch_download_mods(
+            ["foo"], str(self.mods_dir), config, log=lambda message: None, client=client
+        )
+
+        self.assertEqual(result.errors, [])
+        self.assertTrue((self.mods_dir / "foo_1.0.0.zip").exists())
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,57 @@
+"""
+Saved modpacks are canonical, whatever order their contents come in.
+"""
+
+import os
+import tempfile
+import unittest
+
+from factorio_mod_downloader.game.modpack import ModOptions
+from factorio_mod_downloader.game.modpack import Modpack
+from factorio_mod_downloader.game.modpack import load_modpack
+from factorio_mod_downloader.game.modpack import save_modpack
+
+
+MODS = ["flib", "Krastorio2@1.3.24", "https://mods.factorio.com/mod/helmod", "even-distribution"]
+
+
+class SaveModpackTest(unittest.TestCase):
+    def setUp(self):
+        self.directory = tempfile.TemporaryDirectory()
+        self.addCleanup(self.directory.cleanup)
+
+    def save(self, modpack: Modpack, name: str) -> bytes:
+        path = os.path.join(self.directory.name, name)
+        save_modpack(modpack, path, checksum=True)
+        with open(path, "rb") as file:
+            return file.read()
+
+    def test_reordered_mods_are_byte_identical(self):
+        modpack = Modpack(
+            list(MODS),
+            settings={"startup": {"b-setting": 2, "a-setting": True}, "runtime-global": {}},
+            startup={"helmod": False, "flib": True},
+            options={"even-distribution": ModOptions(include_optional=True, skip_newest=1)},
+        )
+        reordered = Modpack(
+            list(reversed(MODS)),
+            settings={"runtime-global": {}, "startup": {"a-setting": True, "b-setting": 2}},
+            startup={"flib": True, "helmod": False},
+            options={"even-distribution": ModOptions(include_optional=True, skip_newest=1)},
+        )
+
+        first = self.save(modpack, "first.json")
+        second = self.save(reordered, "second.json")
+
+        self.assertEqual(first, second)
+        self.assertNotIn(b"\r", first)
+
+    def test_saving_a_loaded_modpack_is_byte_identical(self):
+        saved = self.save(Modpack(list(reversed(MODS))), "first.json")
+        loaded = load_modpack(os.path.join(self.directory.name, "first.json"))
+
+        self.assertEqual(self.save(loaded, "second.json"), saved)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,65 @@
+"""
+Failed deployments leave the server's mods directory as it was.
+"""
+
+import json
+import os
+import tempfile
+import unittest
+from pathlib import Path
+from unittest import mock
+
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+from factorio_mod_downloader.game import server
+from factorio_mod_downloader.game.modpack import Modpack
+from factorio_mod_downloader.game.server import ServerDeployError
+from factorio_mod_downloader.game.server import deploy_to_server
+
+
+class DeployRollbackTest(unittest.TestCase):
+    def setUp(self):
+        directory = tempfile.TemporaryDirectory()
+        self.addCleanup(directory.cleanup)
+        self.server_dir = Path(directory.name)
+        (self.server_dir / "data" / "base").mkdir(parents=True)
+        with open(self.server_dir / "data" / "base" / "info.json", "w", encoding="utf-8") as file:
+            json.dump({"version": "2.0.28"}, file)
+        self.mods_dir = self.server_dir / "mods"
+        self.mods_dir.mkdir()
+        self.mod_list = json.dumps({"mods": [{"name": "base", "enabled": True}]})
+        (self.mods_dir / "mod-list.json").write_text(self.mod_list, encoding="utf-8")
+
+        self.client = MockPortalClient()
+        self.client.add_release("foo", "1.0.0")
+        self.options = {
+            "config": Config(keep_history=False),
+            "log": lambda message: None,
+            "client": self.client,
+        }
+
+    def test_corrupt_mod_settings(self):
+        (self.mods_dir / "mod-settings.dat").write_bytes(b"\x01\x00")
+        modpack = Modpack(["foo"], settings={"startup": {"foo-setting": True}})
+
+        with self.assertRaises(ServerDeployError):
+            deploy_to_server(modpack, str(self.server_dir), **self.options)
+
+        self.assertEqual(self.client.downloads, [])
+        self.assertEqual(sorted(os.listdir(self.mods_dir)), ["mod-list.json", "mod-settings.dat"])
+
+    def test_failure_while_configuring(self):
+        with mock.patch.object(server, "apply_modpack_settings", side_effect=OSError("disk full")):
+            with self.assertRaises(OSError):
+                deploy_to_server(Modpack(["foo"]), str(self.server_dir), **self.options)
+
+        self.assertEqual(self.client.downloads, [("foo", "1.0.0")])
+        self.assertFalse((self.mods_dir / "foo_1.0.0.zip").exists())
+        self.assertFalse((self.mods_dir / ".fmd-staging").exists())
+        self.assertEqual(
+            (self.mods_dir / "mod-list.json").read_text(encoding="utf-8"), self.mod_list
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
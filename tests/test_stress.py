@@ -0,0 +1,91 @@
+"""
+Resolution and download of a synthetic pack of a thousand mods.
+"""
+
+import os
+import random
+import tempfile
+import tracemalloc
+import unittest
+
+from factorio_mod_downloader.downloader.batch import batch_download_mods
+from factorio_mod_downloader.downloader.config import Config
+from factorio_mod_downloader.downloader.resolver import Resolver
+from factorio_mod_downloader.downloader.testing import MockPortalClient
+
+
+MOD_COUNT = 1000
+# Well above what the pack needs, far below keeping every response and zip around
+MAX_PEAK_MEMORY = 32 * 1024 * 1024
+
+
+def synthetic_client(seed: int = 0) -> MockPortalClient:
+    """
+    Mock serving MOD_COUNT mods with three releases each.
+
+    Every mod depends on up to four mods with a lower number, so the graph is
+    acyclic and mod_0 ends up shared by most of the pack. Some dependencies
+    are optional or need a minimum version.
+    """
+    rng = random.Random(seed)
+    client = MockPortalClient()
+    for index in range(MOD_COUNT):
+        dependencies = ["base"]
+        for dependency in sorted(rng.sample(range(index), min(index, rng.randint(0, 4)))):
+            kind = rng.random()
+            if kind < 0.2:
+                dependencies.append(f"? mod_{dependency}")
+            elif kind < 0.4:
+                dependencies.append(f"mod_{dependency} >= 1.1.0")
+            else:
+                dependencies.append(f"mod_{dependency}")
+        for version in ("1.0.0", "1.1.0", "1.2.0"):
+            client.add_release(f"mod_{index}", version, dependencies=dependencies)
+    return client
+
+
+class StressTest(unittest.TestCase):
+    def setUp(self):
+        self.client = synthetic_client()
+        self.config = Config(factorio_version="2.0", keep_history=False)
+        self.requested = [f"mod_{index}" for index in range(MOD_COUNT)]
+
+    def test_resolve(self):
+        resolver = Resolver("2.0", self.config, client=self.client)
+        result = resolver.resolve(self.requested)
+
+        self.assertEqual(result.errors, [])
+        self.assertEqual(len(result.mods), MOD_COUNT)
+        self.assertTrue(all(mod.version == "1.2.0" for mod in result.mods.values()))
+        order = list(result.mods)
+        for mod in result.mods.values():
+            for dependency in mod.dependencies:
+                if dependency.name in result.mods:
+                    self.assertLess(order.index(dependency.name), order.index(mod.name))
+
+    def test_download(self):
+        with tempfile.TemporaryDirectory() as output_path:
+            tracemalloc.start()
+            try:
+                result = batch_download_mods(
+                    self.requested,
+                    output_path,
+                    self.config,
+                    max_workers=8,
+                    log=lambda message: None,
+                    client=self.client,
+                )
+                _, peak = tracemalloc.get_traced_memory()
+            finally:
+                tracemalloc.stop()
+
+            self.assertEqual(result.errors, [])
+            self.assertEqual(len(result.mods), MOD_COUNT)
+            self.assertEqual(len(self.client.downloads), MOD_COUNT)
+            zips = [name for name in os.listdir(output_path) if name.endswith(".zip")]
+            self.assertEqual(sorted(zips), sorted(entry.file_name for entry in result.mods))
+            self.assertLess(peak, MAX_PEAK_MEMORY)
+
+
+if __name__ == "__main__":
+    unittest.main()